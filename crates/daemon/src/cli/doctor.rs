@@ -0,0 +1,289 @@
+//! `doctor` command
+//!
+//! Checks the pieces a working setup needs: a valid config, a healthy and
+//! migrated database, free (or daemon-owned) ports, and Claude Code settings
+//! that export to this daemon.
+
+use std::fmt::Display;
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyhow::Result;
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+use super::status;
+use crate::config::{self, Config};
+
+/// Tally of the check results printed so far
+#[derive(Default)]
+struct Report {
+    warnings: usize,
+    failures: usize,
+}
+
+impl Report {
+    fn ok(&mut self, message: impl Display) {
+        println!("  [ok]   {}", message);
+    }
+
+    fn warn(&mut self, message: impl Display) {
+        self.warnings += 1;
+        println!("  [warn] {}", message);
+    }
+
+    fn fail(&mut self, message: impl Display) {
+        self.failures += 1;
+        println!("  [fail] {}", message);
+    }
+}
+
+/// Run all checks; fails if any check failed
+pub async fn doctor() -> Result<ExitCode> {
+    let mut report = Report::default();
+
+    println!("Configuration");
+    let config = match config::load() {
+        Ok(config) => {
+            if config.config_path.exists() {
+                report.ok(format!("{} is valid", config.config_path.display()));
+            } else {
+                report.ok(format!(
+                    "No {}; using defaults",
+                    config.config_path.display()
+                ));
+            }
+            config
+        }
+        Err(e) => {
+            report.fail(format!("{:#}", e));
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    println!("Database");
+    check_database(&mut report).await?;
+
+    println!("Listeners");
+    check_listeners(&config, &mut report).await;
+
+    println!("Claude Code settings");
+    check_claude_settings(&config, &mut report)?;
+
+    println!(
+        "\n{} failure(s), {} warning(s)",
+        report.failures, report.warnings
+    );
+    Ok(if report.failures == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+async fn check_database(report: &mut Report) -> Result<()> {
+    let db_path = shared::get_db_path()?;
+    let Ok(metadata) = std::fs::metadata(&db_path) else {
+        report.warn(format!(
+            "{} does not exist yet; start the daemon or run `lumo-daemon migrate`",
+            db_path.display()
+        ));
+        return Ok(());
+    };
+    report.ok(format!(
+        "{} ({} KiB)",
+        db_path.display(),
+        metadata.len() / 1024
+    ));
+
+    let pool = match shared::create_pool(&db_path).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            report.fail(format!("Cannot open the database: {}", e));
+            return Ok(());
+        }
+    };
+
+    match shared::integrity_check(&pool).await {
+        Ok(messages) if messages == ["ok"] => report.ok("Integrity check passed"),
+        Ok(messages) => report.fail(format!(
+            "Integrity check found problems: {}",
+            messages.join("; ")
+        )),
+        Err(e) => report.fail(format!("Integrity check failed: {}", e)),
+    }
+
+    match shared::pending_migrations(&pool).await {
+        Ok(pending) if pending.is_empty() => report.ok("Schema is up to date"),
+        Ok(pending) => report.warn(format!(
+            "{} pending migration(s); run `lumo-daemon migrate`",
+            pending.len()
+        )),
+        Err(e) => report.fail(format!("Cannot read migrations: {}", e)),
+    }
+
+    Ok(())
+}
+
+async fn check_listeners(config: &Config, report: &mut Report) {
+    // A running daemon owns its ports; otherwise they must be free to bind
+    let running = status::get(config, "/health")
+        .await
+        .is_ok_and(|(_, body)| body.contains("lumo-daemon"));
+
+    match &config.server_address {
+        Some(server_address) if running => {
+            report.ok(format!("HTTP {} is served by lumo-daemon", server_address))
+        }
+        Some(server_address) => check_port("HTTP", server_address, report).await,
+        None => report.ok("HTTP listener is off; the Unix socket serves all endpoints"),
+    }
+
+    if let Some(grpc_address) = &config.grpc_address {
+        if running {
+            report.ok(format!("gRPC {} is served by lumo-daemon", grpc_address));
+        } else {
+            check_port("gRPC", grpc_address, report).await;
+        }
+    }
+
+    #[cfg(unix)]
+    if let Some(socket_path) = &config.socket_path {
+        if tokio::net::UnixStream::connect(socket_path).await.is_ok() {
+            report.ok(format!(
+                "Socket {} is accepting connections",
+                socket_path.display()
+            ));
+        } else if socket_path.exists() {
+            report.warn(format!(
+                "Socket {} exists but nothing is listening",
+                socket_path.display()
+            ));
+        } else if running {
+            report.warn(format!(
+                "Socket {} is missing although the daemon is running",
+                socket_path.display()
+            ));
+        } else {
+            report.ok(format!(
+                "Socket {} will be created on start",
+                socket_path.display()
+            ));
+        }
+    }
+}
+
+async fn check_port(label: &str, address: &str, report: &mut Report) {
+    match TcpListener::bind(address).await {
+        Ok(_) => report.ok(format!("{} {} is available", label, address)),
+        Err(e) => report.fail(format!(
+            "{} {} cannot be bound ({}); another process may be using it",
+            label, address, e
+        )),
+    }
+}
+
+fn check_claude_settings(config: &Config, report: &mut Report) -> Result<()> {
+    let db_path = shared::get_db_path()?;
+    let Some(home) = db_path.parent().and_then(Path::parent) else {
+        report.fail("Cannot determine the home directory");
+        return Ok(());
+    };
+    let settings_path = home.join(".claude").join("settings.json");
+
+    let settings: Value = match std::fs::read_to_string(&settings_path) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(settings) => settings,
+            Err(e) => {
+                report.fail(format!(
+                    "{} is not valid JSON: {}",
+                    settings_path.display(),
+                    e
+                ));
+                return Ok(());
+            }
+        },
+        Err(_) => {
+            report.fail(format!(
+                "{} not found; open the Lumo app to configure Claude Code",
+                settings_path.display()
+            ));
+            return Ok(());
+        }
+    };
+    let env = &settings["env"];
+
+    if env["CLAUDE_CODE_ENABLE_TELEMETRY"] == "1" {
+        report.ok("Telemetry is enabled");
+    } else {
+        report.fail("CLAUDE_CODE_ENABLE_TELEMETRY is not \"1\"");
+    }
+
+    for key in ["OTEL_LOGS_EXPORTER", "OTEL_METRICS_EXPORTER"] {
+        if env[key] != "otlp" {
+            report.warn(format!("{} is not \"otlp\"", key));
+        }
+    }
+
+    // Exporters reach the daemon over TCP, through HTTP or gRPC
+    let addresses: Vec<&str> = [&config.server_address, &config.grpc_address]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    let reaches = |endpoint: &str| {
+        addresses.iter().any(|address| {
+            let port = address.rsplit(':').next().unwrap_or_default();
+            endpoint
+                .trim_end_matches('/')
+                .ends_with(&format!(":{}", port))
+        })
+    };
+    match env["OTEL_EXPORTER_OTLP_ENDPOINT"].as_str() {
+        // Claude Code exports and runs hooks over TCP; it cannot use the socket
+        _ if config.server_address.is_none() => report.fail(
+            "The HTTP listener is off (server_address = \"off\"); Claude Code cannot reach a daemon that serves the Unix socket only",
+        ),
+        Some(endpoint) if reaches(endpoint) => {
+            report.ok(format!("OTLP endpoint {} reaches the daemon", endpoint))
+        }
+        Some(endpoint) => report.fail(format!(
+            "OTLP endpoint {} does not match the daemon address {}",
+            endpoint,
+            addresses.join(" or ")
+        )),
+        None => report.fail("OTEL_EXPORTER_OTLP_ENDPOINT is not set"),
+    }
+
+    let token = if config.auth_enabled {
+        std::fs::read_to_string(shared::get_token_path()?)
+            .ok()
+            .map(|token| token.trim().to_string())
+    } else {
+        None
+    };
+    if config.auth_enabled {
+        let headers = env["OTEL_EXPORTER_OTLP_HEADERS"]
+            .as_str()
+            .unwrap_or_default();
+        match &token {
+            Some(token) if headers.contains(token.as_str()) => {
+                report.ok("OTLP headers carry the daemon token")
+            }
+            Some(_) => report
+                .fail("OTEL_EXPORTER_OTLP_HEADERS lacks the daemon token; reopen the Lumo app"),
+            None => report.warn("No daemon token yet; it is created when the daemon starts"),
+        }
+    }
+
+    let hooks = settings["hooks"].to_string();
+    if !hooks.contains("/notify") {
+        report.warn("No hooks forward notifications to the daemon");
+    } else if token.is_some_and(|token| !hooks.contains(&token)) {
+        report.fail("Notification hooks lack the daemon token; reopen the Lumo app");
+    } else {
+        report.ok("Notification hooks are configured");
+    }
+
+    Ok(())
+}
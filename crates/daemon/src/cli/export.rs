@@ -0,0 +1,115 @@
+//! `export` command
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use anyhow::{Context, Result};
+use futures_util::TryStreamExt;
+use serde::Serialize;
+use shared::{
+    Event, EventRow, Metric, MetricRow, Notification, NotificationRow, Session, Span, SpanRow,
+};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, SqlitePool};
+
+use super::{open_database, ExportOptions};
+
+/// Tables that can be exported
+pub const TABLES: &[&str] = &["events", "metrics", "spans", "notifications", "sessions"];
+
+/// Write the rows of a table within the time range as JSON Lines
+///
+/// Rows are streamed from the database, so exporting a large table does not
+/// hold it in memory.
+pub async fn export(options: ExportOptions) -> Result<ExitCode> {
+    let pool = open_database().await?;
+    let range = (options.since, options.until);
+
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("Failed to create {}", path.display())
+            })?))
+        }
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    // `?1` is the start and `?2` the end of the range
+    let count = match options.table.as_str() {
+        "events" => {
+            write_rows::<EventRow, Event>(
+                &mut out,
+                &pool,
+                "SELECT * FROM events WHERE timestamp >= ?1 AND timestamp <= ?2 ORDER BY timestamp",
+                range,
+            )
+            .await?
+        }
+        "metrics" => write_rows::<MetricRow, Metric>(
+            &mut out,
+            &pool,
+            "SELECT * FROM metrics WHERE timestamp >= ?1 AND timestamp <= ?2 ORDER BY timestamp",
+            range,
+        )
+        .await?,
+        "spans" => write_rows::<SpanRow, Span>(
+            &mut out,
+            &pool,
+            "SELECT * FROM spans WHERE start_time >= ?1 AND start_time <= ?2 ORDER BY start_time",
+            range,
+        )
+        .await?,
+        "notifications" => {
+            write_rows::<NotificationRow, Notification>(
+                &mut out,
+                &pool,
+                "SELECT * FROM notifications WHERE created_at >= ?1 AND created_at <= ?2 \
+                 ORDER BY created_at",
+                range,
+            )
+            .await?
+        }
+        "sessions" => {
+            write_rows::<Session, Session>(
+                &mut out,
+                &pool,
+                "SELECT * FROM sessions WHERE start_time <= ?2 AND end_time >= ?1 \
+                 AND id != 'unknown' ORDER BY start_time DESC",
+                range,
+            )
+            .await?
+        }
+        table => unreachable!("unknown table {} passed argument parsing", table),
+    };
+    out.flush()?;
+
+    eprintln!("Exported {} {}", count, options.table);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Stream the rows selected by `sql` over `(start, end)` to `out`, converted
+/// to their entity
+async fn write_rows<R, T>(
+    out: &mut dyn Write,
+    pool: &SqlitePool,
+    sql: &str,
+    (start, end): (i64, i64),
+) -> Result<usize>
+where
+    R: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+    T: From<R> + Serialize,
+{
+    let mut rows = sqlx::query_as::<_, R>(sql)
+        .bind(start)
+        .bind(end)
+        .fetch(pool);
+
+    let mut count = 0;
+    while let Some(row) = rows.try_next().await? {
+        serde_json::to_writer(&mut *out, &T::from(row))?;
+        out.write_all(b"\n")?;
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,85 @@
+//! Database maintenance commands: `migrate`, `prune`, `vacuum` and
+//! `rebuild-sessions`
+
+use std::process::ExitCode;
+
+use anyhow::Result;
+use shared::{
+    EventRepository, MetricRepository, NotificationRepository, SessionRepository, SpanRepository,
+};
+
+use super::open_database;
+
+/// Apply pending migrations
+pub async fn migrate() -> Result<ExitCode> {
+    let pool = open_database().await?;
+    let pending = shared::pending_migrations(&pool).await?;
+    shared::run_migrations(&pool).await?;
+
+    println!("Applied {} migration(s)", pending.len());
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Delete telemetry and notifications older than `before` (Unix milliseconds)
+pub async fn prune(before: i64) -> Result<ExitCode> {
+    let pool = open_database().await?;
+
+    let events = EventRepository::delete_before(&pool, before).await?;
+    let metrics = MetricRepository::delete_before(&pool, before).await?;
+    let spans = SpanRepository::delete_before(&pool, before).await?;
+    let notifications = NotificationRepository::delete_before(&pool, before).await?;
+
+    println!(
+        "Deleted {} events, {} metrics, {} spans and {} notifications",
+        events, metrics, spans, notifications
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Reclaim free space in the database file
+///
+/// Databases from before incremental auto-vacuum are converted on the way,
+/// which only works while the daemon is stopped.
+pub async fn vacuum() -> Result<ExitCode> {
+    let db_path = shared::get_db_path()?;
+    let before = std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0);
+
+    // Create the database if needed, then release it for the conversion
+    open_database().await?.close().await;
+    let converted = match shared::enable_incremental_vacuum(&db_path).await {
+        Ok(converted) => converted,
+        Err(e) => {
+            eprintln!(
+                "Incremental auto-vacuum not enabled ({}); stop the daemon and run vacuum again",
+                e
+            );
+            false
+        }
+    };
+    if converted {
+        println!("Enabled incremental auto-vacuum");
+    } else {
+        let pool = open_database().await?;
+        shared::vacuum(&pool).await?;
+    }
+
+    let after = std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0);
+    println!(
+        "Vacuumed {}: {} KiB -> {} KiB",
+        db_path.display(),
+        before / 1024,
+        after / 1024
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Recompute the sessions table from events
+///
+/// Sessions with pruned events keep their totals.
+pub async fn rebuild_sessions() -> Result<ExitCode> {
+    let pool = open_database().await?;
+    let sessions = SessionRepository::rebuild(&pool).await?;
+
+    println!("Rebuilt {} session(s)", sessions);
+    Ok(ExitCode::SUCCESS)
+}
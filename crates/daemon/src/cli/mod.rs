@@ -0,0 +1,217 @@
+//! Command-line interface
+//!
+//! Without arguments the binary runs the server, as it always has. The other
+//! subcommands are one-shot operations for maintaining a daemon and its
+//! database from a shell, e.g. on headless machines without the desktop app.
+
+mod doctor;
+mod export;
+mod maintenance;
+mod status;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::{bail, Context, Result};
+
+use crate::services::parse_time;
+
+pub const USAGE: &str = "\
+Usage: lumo-daemon [COMMAND]
+
+Commands:
+  serve                 Run the daemon (default)
+  migrate               Apply pending database migrations
+  status                Query the running daemon
+  prune --before TIME   Delete telemetry and notifications older than TIME
+  vacuum                Reclaim free space in the database
+  rebuild-sessions      Recompute the sessions table from events
+  export [--table TABLE] [--since TIME] [--until TIME] [--output FILE]
+                        Write rows as JSON Lines (tables: events, metrics,
+                        spans, notifications, sessions; default events)
+  doctor                Check the database, ports and Claude Code settings
+  help                  Print this message
+  version               Print the version
+
+TIME is a date (2025-01-31), an RFC 3339 timestamp, a number of days ago
+(30d) or Unix milliseconds.";
+
+/// A parsed command line
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Serve,
+    Migrate,
+    Status,
+    Prune { before: i64 },
+    Vacuum,
+    RebuildSessions,
+    Export(ExportOptions),
+    Doctor,
+    Help,
+    Version,
+}
+
+/// Options of `export`
+#[derive(Debug, PartialEq)]
+pub struct ExportOptions {
+    pub table: String,
+    /// Unix milliseconds, inclusive
+    pub since: i64,
+    /// Unix milliseconds, inclusive
+    pub until: i64,
+    /// Standard output when `None`
+    pub output: Option<PathBuf>,
+}
+
+impl Command {
+    /// Parse the arguments following the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        let Some(name) = args.next() else {
+            return Ok(Self::Serve);
+        };
+
+        let mut flags = Flags::parse(args)?;
+        let command = match name.as_str() {
+            "serve" => Self::Serve,
+            "migrate" => Self::Migrate,
+            "status" => Self::Status,
+            "prune" => Self::Prune {
+                before: parse_time(
+                    &flags
+                        .take("--before")
+                        .context("prune requires --before TIME")?,
+                )?,
+            },
+            "vacuum" => Self::Vacuum,
+            "rebuild-sessions" => Self::RebuildSessions,
+            "export" => {
+                let table = flags
+                    .take("--table")
+                    .unwrap_or_else(|| "events".to_string());
+                if !export::TABLES.contains(&table.as_str()) {
+                    bail!("Unknown table: {}", table);
+                }
+                Self::Export(ExportOptions {
+                    table,
+                    since: flags.take("--since").map_or(Ok(0), |v| parse_time(&v))?,
+                    until: flags
+                        .take("--until")
+                        .map_or(Ok(i64::MAX), |v| parse_time(&v))?,
+                    output: flags.take("--output").map(PathBuf::from),
+                })
+            }
+            "doctor" => Self::Doctor,
+            "help" | "--help" | "-h" => Self::Help,
+            "version" | "--version" | "-V" => Self::Version,
+            other => bail!("Unknown command: {}", other),
+        };
+        flags.finish()?;
+
+        Ok(command)
+    }
+}
+
+/// Run a one-shot command
+pub async fn run(command: Command) -> Result<ExitCode> {
+    match command {
+        Command::Serve => unreachable!("serve is run by main"),
+        Command::Migrate => maintenance::migrate().await,
+        Command::Status => status::status().await,
+        Command::Prune { before } => maintenance::prune(before).await,
+        Command::Vacuum => maintenance::vacuum().await,
+        Command::RebuildSessions => maintenance::rebuild_sessions().await,
+        Command::Export(options) => export::export(options).await,
+        Command::Doctor => doctor::doctor().await,
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Version => {
+            println!("lumo-daemon {}", env!("CARGO_PKG_VERSION"));
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+/// `--name value` pairs following a subcommand
+struct Flags(Vec<(String, String)>);
+
+impl Flags {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut flags = Vec::new();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                bail!("Unexpected argument: {}", arg);
+            }
+            // Accept both `--name value` and `--name=value`
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .with_context(|| format!("{} requires a value", arg))?;
+                    (arg, value)
+                }
+            };
+            flags.push((name, value));
+        }
+        Ok(Self(flags))
+    }
+
+    fn take(&mut self, name: &str) -> Option<String> {
+        let index = self.0.iter().position(|(n, _)| n == name)?;
+        Some(self.0.remove(index).1)
+    }
+
+    /// Fail on flags the command does not know
+    fn finish(self) -> Result<()> {
+        match self.0.first() {
+            Some((name, _)) => bail!("Unknown option: {}", name),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Open the database the daemon uses
+async fn open_database() -> Result<sqlx::SqlitePool> {
+    let db_path = shared::get_db_path()?;
+    Ok(shared::create_pool(&db_path).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse(&[]).unwrap(), Command::Serve);
+        assert_eq!(
+            parse(&["prune", "--before", "2025-01-31"]).unwrap(),
+            Command::Prune {
+                before: 1_738_281_600_000
+            }
+        );
+        assert_eq!(
+            parse(&["export", "--table=spans", "--since", "1000"]).unwrap(),
+            Command::Export(ExportOptions {
+                table: "spans".to_string(),
+                since: 1000,
+                until: i64::MAX,
+                output: None,
+            })
+        );
+        assert_eq!(
+            parse(&["rebuild-sessions"]).unwrap(),
+            Command::RebuildSessions
+        );
+        assert!(parse(&["prune"]).is_err());
+        assert!(parse(&["export", "--table", "users"]).is_err());
+        assert!(parse(&["vacuum", "--force", "yes"]).is_err());
+        assert!(parse(&["start"]).is_err());
+    }
+}
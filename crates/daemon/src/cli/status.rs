@@ -0,0 +1,202 @@
+//! `status` command, and a minimal HTTP client for talking to the running
+//! daemon
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::config::Config;
+
+/// How long to wait for the daemon to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Report whether the daemon is running and healthy
+pub async fn status() -> Result<ExitCode> {
+    let config = Config::load()?;
+
+    let (code, body) = match get(&config, "/health").await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("lumo-daemon is not running: {:#}", e);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    let health: Value = serde_json::from_str(&body).context("Invalid /health response")?;
+
+    println!(
+        "lumo-daemon {} is {}",
+        health["version"].as_str().unwrap_or("unknown"),
+        health["status"].as_str().unwrap_or("unknown"),
+    );
+    match &config.server_address {
+        Some(server_address) => println!("  HTTP:     http://{}", server_address),
+        None => println!("  HTTP:     off"),
+    }
+    if let Some(grpc_address) = &config.grpc_address {
+        println!("  gRPC:     {}", grpc_address);
+    }
+    if let Some(socket_path) = &config.socket_path {
+        println!("  Socket:   {}", socket_path.display());
+    }
+    println!(
+        "  Database: {}",
+        health["database"].as_str().unwrap_or("unknown")
+    );
+
+    // Older daemons have no /status
+    if let Ok((200, body)) = get(&config, "/status").await {
+        if let Ok(status) = serde_json::from_str::<Value>(&body) {
+            print_status(&status);
+        }
+    }
+
+    Ok(if code == 200 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Print the details reported by `/status`
+fn print_status(status: &Value) {
+    let (ingest, queue, database) = (&status["ingest"], &status["queue"], &status["database"]);
+    let count = |value: &Value| value.as_u64().unwrap_or_default();
+
+    println!(
+        "  Uptime:   {}",
+        format_duration(status["uptime_seconds"].as_i64().unwrap_or_default())
+    );
+    let last_data = [
+        &ingest["last_received_at"],
+        &ingest["latest_event_at"],
+        &ingest["latest_metric_at"],
+    ]
+    .iter()
+    .filter_map(|time| time.as_i64())
+    .max();
+    match last_data {
+        Some(time) => {
+            let ago = (chrono::Utc::now().timestamp_millis() - time) / 1000;
+            println!("  Received: {} ago", format_duration(ago));
+        }
+        None => println!("  Received: never"),
+    }
+    println!(
+        "  Ingest:   {} events/min, {} metrics/min, {} spans/min, {} parse failures",
+        count(&ingest["events_per_minute"]),
+        count(&ingest["metrics_per_minute"]),
+        count(&ingest["spans_per_minute"]),
+        count(&ingest["parse_failures"]),
+    );
+    println!(
+        "  Queue:    {}/{} batches, {} KiB spooled, {} entries quarantined",
+        count(&queue["depth"]),
+        count(&queue["capacity"]),
+        count(&queue["spool_bytes"]) / 1024,
+        count(&queue["quarantined_entries"]),
+    );
+    println!(
+        "  Storage:  {} KiB (+{} KiB WAL), migration {} ({} pending)",
+        count(&database["size_bytes"]) / 1024,
+        count(&database["wal_bytes"]) / 1024,
+        database["migration_version"]
+            .as_i64()
+            .map_or("none".to_string(), |version| version.to_string()),
+        count(&database["pending_migrations"]),
+    );
+}
+
+/// Format seconds as e.g. "2d 3h", "3h 5m", "5m 10s"
+fn format_duration(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m {}s", minutes, seconds % 60)
+    }
+}
+
+/// Send a GET request to the running daemon and return the status code and
+/// body
+///
+/// Uses the Unix socket when there is one, so no token is needed; otherwise
+/// TCP with the bearer token.
+pub async fn get(config: &Config, path: &str) -> Result<(u16, String)> {
+    #[cfg(unix)]
+    if let Some(socket_path) = &config.socket_path {
+        let connect = tokio::net::UnixStream::connect(socket_path);
+        if let Ok(Ok(stream)) = tokio::time::timeout(REQUEST_TIMEOUT, connect).await {
+            return with_timeout(request(stream, "localhost", path, None)).await;
+        }
+    }
+
+    get_tcp(config, path).await
+}
+
+/// Send a GET request to the daemon's TCP address
+pub async fn get_tcp(config: &Config, path: &str) -> Result<(u16, String)> {
+    let Some(server_address) = &config.server_address else {
+        bail!("The HTTP listener is off and the Unix socket is not reachable");
+    };
+    with_timeout(async {
+        let stream = TcpStream::connect(server_address)
+            .await
+            .with_context(|| format!("Cannot connect to {}", server_address))?;
+        let token = if config.auth_enabled {
+            std::fs::read_to_string(shared::get_token_path()?)
+                .ok()
+                .map(|token| token.trim().to_string())
+        } else {
+            None
+        };
+        request(stream, server_address, path, token.as_deref()).await
+    })
+    .await
+}
+
+async fn with_timeout<T>(future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+    tokio::time::timeout(REQUEST_TIMEOUT, future)
+        .await
+        .context("Timed out waiting for the daemon")?
+}
+
+async fn request<S>(
+    mut stream: S,
+    host: &str,
+    path: &str,
+    token: Option<&str>,
+) -> Result<(u16, String)>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let authorization = token
+        .map(|token| format!("Authorization: Bearer {}\r\n", token))
+        .unwrap_or_default();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\n{}Connection: close\r\n\r\n",
+        path, host, authorization
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    let response = String::from_utf8_lossy(&response);
+
+    let Some((head, body)) = response.split_once("\r\n\r\n") else {
+        bail!("Malformed HTTP response");
+    };
+    let code = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .context("Malformed HTTP status line")?;
+
+    Ok((code, body.to_string()))
+}
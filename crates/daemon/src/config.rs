@@ -1,37 +1,47 @@
+//! Daemon configuration
+//!
+//! Settings are resolved by [`shared::DaemonConfig`], which the desktop app
+//! uses too; this module adds the checks that need the daemon's own
+//! dependencies. The log level, redaction policy and retention policy are
+//! reloaded when their files change; everything else takes effect on restart.
+
 use anyhow::{Context, Result};
-use std::env;
+use std::path::Path;
 
-#[derive(Debug, Clone)]
-pub struct Config {
-    /// Server listening address (e.g., "127.0.0.1:4318")
-    pub server_address: String,
+pub use shared::DaemonConfig as Config;
 
-    /// Log level (e.g., "info", "debug", "trace")
-    pub log_level: String,
+/// Load `~/.lumo/daemon.toml` (or `LUMO_CONFIG`) and environment variables,
+/// and validate the result
+pub fn load() -> Result<Config> {
+    load_from(&Config::default_path()?)
 }
 
-impl Config {
-    /// Load configuration from environment variables
-    pub fn from_env() -> Result<Self> {
-        let server_address = env::var("LUMO_SERVER_ADDRESS")
-            .unwrap_or_else(|_| "127.0.0.1:4318".to_string());
-
-        let log_level =
-            env::var("RUST_LOG").unwrap_or_else(|_| "lumo_daemon=info,tower_http=info".to_string());
+/// Load the given file and environment variables, and validate the result
+pub fn load_from(config_path: &Path) -> Result<Config> {
+    let config = Config::load_from(config_path)?;
+    config.validate()?;
+    tracing_subscriber::EnvFilter::try_new(&config.log_level)
+        .with_context(|| format!("Invalid log level: {}", config.log_level))?;
 
-        Ok(Config {
-            server_address,
-            log_level,
-        })
-    }
-
-    /// Validate configuration
-    pub fn validate(&self) -> Result<()> {
-        // Parse address to ensure it's valid
-        self.server_address
-            .parse::<std::net::SocketAddr>()
-            .context("Invalid server address")?;
+    Ok(config)
+}
 
-        Ok(())
+/// Defaults with every file under `dir` and only in-process listeners,
+/// independent of the environment and `~/.lumo`
+#[cfg(test)]
+pub(crate) fn for_test(dir: &Path) -> Config {
+    Config {
+        config_path: dir.join("daemon.toml"),
+        server_address: None,
+        grpc_address: None,
+        log_level: "lumo_daemon=debug".to_string(),
+        max_body_bytes: shared::config::DEFAULT_MAX_BODY_BYTES,
+        queue_capacity: shared::config::DEFAULT_QUEUE_CAPACITY,
+        spool_path: dir.join("spool.jsonl"),
+        attribute_mapping_path: dir.join("attribute-mapping.json"),
+        redaction_path: dir.join("redaction.json"),
+        retention_path: dir.join("retention.json"),
+        auth_enabled: false,
+        socket_path: None,
     }
 }
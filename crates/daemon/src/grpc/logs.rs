@@ -0,0 +1,49 @@
+//! gRPC `LogsService` implementation
+
+use opentelemetry_proto::tonic::collector::logs::v1::logs_service_server::LogsService;
+use opentelemetry_proto::tonic::collector::logs::v1::{
+    ExportLogsPartialSuccess, ExportLogsServiceRequest, ExportLogsServiceResponse,
+};
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+use crate::server::AppState;
+use crate::services::ingest_logs;
+
+/// Receives OTLP logs over gRPC
+pub struct LogsReceiver {
+    state: AppState,
+}
+
+impl LogsReceiver {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl LogsService for LogsReceiver {
+    async fn export(
+        &self,
+        request: Request<ExportLogsServiceRequest>,
+    ) -> Result<Response<ExportLogsServiceResponse>, Status> {
+        info!("Received OTLP logs export request (gRPC)");
+
+        // UNAVAILABLE tells the exporter to retry later
+        let outcome = ingest_logs(&self.state, request.get_ref())
+            .await
+            .map_err(|e| {
+                error!("{}", e);
+                Status::unavailable(e.to_string())
+            })?;
+
+        let partial_success = outcome
+            .error_message
+            .map(|error_message| ExportLogsPartialSuccess {
+                rejected_log_records: outcome.rejected as i64,
+                error_message,
+            });
+
+        Ok(Response::new(ExportLogsServiceResponse { partial_success }))
+    }
+}
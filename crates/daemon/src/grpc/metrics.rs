@@ -0,0 +1,52 @@
+//! gRPC `MetricsService` implementation
+
+use opentelemetry_proto::tonic::collector::metrics::v1::metrics_service_server::MetricsService;
+use opentelemetry_proto::tonic::collector::metrics::v1::{
+    ExportMetricsPartialSuccess, ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+use crate::server::AppState;
+use crate::services::ingest_metrics;
+
+/// Receives OTLP metrics over gRPC
+pub struct MetricsReceiver {
+    state: AppState,
+}
+
+impl MetricsReceiver {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl MetricsService for MetricsReceiver {
+    async fn export(
+        &self,
+        request: Request<ExportMetricsServiceRequest>,
+    ) -> Result<Response<ExportMetricsServiceResponse>, Status> {
+        info!("Received OTLP metrics export request (gRPC)");
+
+        // UNAVAILABLE tells the exporter to retry later
+        let outcome = ingest_metrics(&self.state, request.get_ref())
+            .await
+            .map_err(|e| {
+                error!("{}", e);
+                Status::unavailable(e.to_string())
+            })?;
+
+        let partial_success =
+            outcome
+                .error_message
+                .map(|error_message| ExportMetricsPartialSuccess {
+                    rejected_data_points: outcome.rejected as i64,
+                    error_message,
+                });
+
+        Ok(Response::new(ExportMetricsServiceResponse {
+            partial_success,
+        }))
+    }
+}
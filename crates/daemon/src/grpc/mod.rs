@@ -0,0 +1,165 @@
+//! OTLP/gRPC receiver
+//!
+//! Serves the OTLP `LogsService`, `MetricsService` and `TraceService`
+//! alongside the HTTP endpoints, sharing the same application state and
+//! ingestion path.
+
+mod logs;
+mod metrics;
+mod traces;
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use opentelemetry_proto::tonic::collector::logs::v1::logs_service_server::LogsServiceServer;
+use opentelemetry_proto::tonic::collector::metrics::v1::metrics_service_server::MetricsServiceServer;
+use opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::TraceServiceServer;
+use tonic::codec::CompressionEncoding;
+use tonic::service::Interceptor;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Status};
+use tracing::{info, warn};
+
+use crate::server::{auth, AppState};
+
+use logs::LogsReceiver;
+use metrics::MetricsReceiver;
+use traces::TraceReceiver;
+
+/// Run the OTLP/gRPC server until `shutdown` resolves
+pub async fn serve<F>(addr: SocketAddr, state: AppState, shutdown: F) -> Result<()>
+where
+    F: Future<Output = ()> + Send,
+{
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind gRPC listener to {}", addr))?;
+    let local_addr = listener.local_addr()?;
+    let incoming = TcpIncoming::from_listener(listener, true, None)
+        .map_err(|e| anyhow::anyhow!("Failed to accept gRPC connections: {}", e))?;
+
+    let max_message_size = state.config.max_body_bytes;
+    let auth_token = state.auth_token.clone();
+
+    let logs = LogsServiceServer::new(LogsReceiver::new(state.clone()))
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(max_message_size);
+    let metrics = MetricsServiceServer::new(MetricsReceiver::new(state.clone()))
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(max_message_size);
+    let traces = TraceServiceServer::new(TraceReceiver::new(state))
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(max_message_size);
+
+    info!("OTLP gRPC receiver listening on {}", local_addr);
+
+    Server::builder()
+        .layer(tonic::service::interceptor(TokenInterceptor(auth_token)))
+        .add_service(logs)
+        .add_service(metrics)
+        .add_service(traces)
+        .serve_with_incoming_shutdown(incoming, shutdown)
+        .await
+        .context("gRPC server error")?;
+
+    Ok(())
+}
+
+/// Requires the daemon token in the `authorization` metadata, if one is set
+#[derive(Clone)]
+struct TokenInterceptor(Option<Arc<str>>);
+
+impl Interceptor for TokenInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(token) = self.0.as_deref() else {
+            return Ok(request);
+        };
+
+        let authorization = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok());
+        if auth::is_authorized(token, authorization) {
+            Ok(request)
+        } else {
+            warn!("Rejected unauthenticated gRPC request");
+            Err(Status::unauthenticated("Missing or invalid bearer token"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_proto::tonic::collector::logs::v1::logs_service_client::LogsServiceClient;
+    use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+    use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue, KeyValue};
+    use opentelemetry_proto::tonic::logs::v1::{LogRecord, ResourceLogs, ScopeLogs};
+
+    fn string_attr(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue {
+                value: Some(any_value::Value::StringValue(value.to_string())),
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grpc_logs_export_is_stored() {
+        let (state, _dir) = AppState::for_test().await;
+        let pool = state.db.clone();
+
+        // Bind first to learn the ephemeral port, then hand it to the server
+        let probe = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = probe.local_addr().unwrap();
+        drop(probe);
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve(addr, state, async {
+            let _ = rx.await;
+        }));
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        let mut client = LogsServiceClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+        let request = ExportLogsServiceRequest {
+            resource_logs: vec![ResourceLogs {
+                scope_logs: vec![ScopeLogs {
+                    log_records: vec![LogRecord {
+                        time_unix_nano: 1_700_000_000_000_000_000,
+                        attributes: vec![
+                            string_attr("event.name", "user_prompt"),
+                            string_attr("session.id", "grpc-session"),
+                        ],
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+        client.export(request).await.unwrap();
+
+        // Events are written by the background queue shortly after the ACK
+        let mut events = Vec::new();
+        for _ in 0..50 {
+            events = shared::EventRepository::find_by_session(&pool, "grpc-session")
+                .await
+                .unwrap();
+            if !events.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "claude_code.user_prompt");
+
+        let _ = tx.send(());
+        server.await.unwrap().unwrap();
+    }
+}
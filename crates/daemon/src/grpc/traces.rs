@@ -0,0 +1,52 @@
+//! gRPC `TraceService` implementation
+
+use opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::TraceService;
+use opentelemetry_proto::tonic::collector::trace::v1::{
+    ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+use crate::server::AppState;
+use crate::services::ingest_traces;
+
+/// Receives OTLP traces over gRPC
+pub struct TraceReceiver {
+    state: AppState,
+}
+
+impl TraceReceiver {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl TraceService for TraceReceiver {
+    async fn export(
+        &self,
+        request: Request<ExportTraceServiceRequest>,
+    ) -> Result<Response<ExportTraceServiceResponse>, Status> {
+        info!("Received OTLP traces export request (gRPC)");
+
+        // UNAVAILABLE tells the exporter to retry later
+        let outcome = ingest_traces(&self.state, request.get_ref())
+            .await
+            .map_err(|e| {
+                error!("{}", e);
+                Status::unavailable(e.to_string())
+            })?;
+
+        let partial_success =
+            outcome
+                .error_message
+                .map(|error_message| ExportTracePartialSuccess {
+                    rejected_spans: outcome.rejected as i64,
+                    error_message,
+                });
+
+        Ok(Response::new(ExportTraceServiceResponse {
+            partial_success,
+        }))
+    }
+}
@@ -0,0 +1,371 @@
+//! Query API handlers
+//!
+//! Read-only JSON endpoints over the local database, so scripts, editor
+//! plugins and status lines can read usage data without opening SQLite.
+//!
+//! `since` and `until` accept the same formats as the CLI: a date
+//! (2025-01-31), an RFC 3339 timestamp, a number of days ago (30d) or Unix
+//! milliseconds. Lists are paginated with `limit` and `offset`. Invalid
+//! parameters are reported in the same JSON body as other errors.
+
+use axum::{
+    extract::{FromRequestParts, Path, Query, State},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use shared::{
+    Event, EventRepository, EventTotals, MetricTotals, Notification, NotificationRepository,
+    RollupGroup, RollupRepository, Session, SessionRepository,
+};
+use tracing::error;
+
+use crate::server::AppState;
+use crate::services::parse_time;
+
+/// Event carrying the cost and token usage of each model request
+const API_REQUEST: &str = "claude_code.api_request";
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+
+/// Error response of the query API
+#[derive(Debug)]
+pub struct ApiError(StatusCode, String);
+
+impl From<shared::Error> for ApiError {
+    fn from(e: shared::Error) -> Self {
+        match e {
+            shared::Error::NotFound(message) => Self(StatusCode::NOT_FOUND, message),
+            shared::Error::InvalidData(message) => Self(StatusCode::BAD_REQUEST, message),
+            e => {
+                error!("Query failed: {}", e);
+                Self(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(json!({
+            "status": "error",
+            "message": self.1,
+        }));
+        (self.0, body).into_response()
+    }
+}
+
+/// Query string extractor that rejects invalid parameters with an
+/// [`ApiError`]
+pub struct ApiQuery<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ApiQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::from_request_parts(parts, state)
+            .await
+            .map_err(|e| ApiError(e.status(), e.body_text()))?;
+        Ok(Self(query))
+    }
+}
+
+/// Time range parameters
+#[derive(Debug, Deserialize)]
+pub struct RangeQuery {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Telemetry source, e.g. "claude_code" or "codex"
+    pub source: Option<String>,
+}
+
+impl RangeQuery {
+    /// Bounds in Unix milliseconds, unbounded by default
+    fn bounds(&self) -> Result<(i64, i64), ApiError> {
+        let parse = |value: &Option<String>, default: i64| match value {
+            Some(value) => parse_time(value).map_err(|e| invalid(e.to_string())),
+            None => Ok(default),
+        };
+        Ok((parse(&self.since, 0)?, parse(&self.until, i64::MAX)?))
+    }
+}
+
+/// Pagination parameters
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl PageQuery {
+    fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+/// One page of a list
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub limit: i64,
+    pub offset: i64,
+    /// Offset of the next page, `None` on the last page
+    pub next_offset: Option<i64>,
+}
+
+impl<T> Page<T> {
+    /// Build a page from up to `limit + 1` items, the extra one only telling
+    /// that another page follows
+    fn new(mut items: Vec<T>, page: &PageQuery) -> Self {
+        let (limit, offset) = (page.limit(), page.offset());
+        let next_offset = if items.len() as i64 > limit {
+            items.truncate(limit as usize);
+            Some(offset + limit)
+        } else {
+            None
+        };
+        Self {
+            items,
+            limit,
+            offset,
+            next_offset,
+        }
+    }
+}
+
+/// Session filters
+#[derive(Debug, Deserialize)]
+pub struct SessionQuery {
+    /// "active", "idle" or "ended"
+    pub status: Option<String>,
+}
+
+/// GET /api/v1/sessions — sessions active within the range, most recent first
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    ApiQuery(range): ApiQuery<RangeQuery>,
+    ApiQuery(page): ApiQuery<PageQuery>,
+    ApiQuery(query): ApiQuery<SessionQuery>,
+) -> Result<Json<Page<Session>>, ApiError> {
+    let (since, until) = range.bounds()?;
+    let sessions = SessionRepository::find_filtered(
+        &state.db,
+        since,
+        until,
+        range.source.as_deref(),
+        query.status.as_deref(),
+        page.limit() + 1,
+        page.offset(),
+    )
+    .await?;
+    Ok(Json(Page::new(sessions, &page)))
+}
+
+/// GET /api/v1/sessions/{id}
+pub async fn get_session(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Session>, ApiError> {
+    Ok(Json(SessionRepository::find_by_id(&state.db, &id).await?))
+}
+
+/// GET /api/v1/sessions/{id}/events — events of a session, oldest first
+pub async fn list_session_events(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ApiQuery(range): ApiQuery<RangeQuery>,
+    ApiQuery(page): ApiQuery<PageQuery>,
+) -> Result<Json<Page<Event>>, ApiError> {
+    let (since, until) = range.bounds()?;
+    let events = EventRepository::find_by_session_paginated(
+        &state.db,
+        &id,
+        since,
+        until,
+        page.limit() + 1,
+        page.offset(),
+    )
+    .await?;
+    Ok(Json(Page::new(events, &page)))
+}
+
+/// Usage totals of a time range
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    pub since: i64,
+    pub until: i64,
+    pub sessions: i64,
+    pub api_requests: i64,
+    pub cost_usd: f64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub lines_added: f64,
+    pub lines_removed: f64,
+    pub commits: f64,
+    pub pull_requests: f64,
+}
+
+/// GET /api/v1/stats/summary — cost, tokens and activity within the range
+pub async fn get_summary(
+    State(state): State<AppState>,
+    ApiQuery(range): ApiQuery<RangeQuery>,
+) -> Result<Json<Summary>, ApiError> {
+    let (since, until) = range.bounds()?;
+    let source = range.source.as_deref();
+
+    let totals = RollupRepository::sum_events(
+        &state.db,
+        API_REQUEST,
+        since,
+        until,
+        source,
+        RollupGroup::default(),
+    )
+    .await?
+    .pop()
+    .unwrap_or_default();
+    let sessions = SessionRepository::count_by_time_range(&state.db, since, until, source).await?;
+
+    let sum =
+        |name: &'static str| RollupRepository::sum_metrics(&state.db, name, since, until, source);
+    let lines = sum("claude_code.lines_of_code.count").await?;
+    let commits = sum("claude_code.commit.count").await?;
+    let pull_requests = sum("claude_code.pull_request.count").await?;
+
+    Ok(Json(Summary {
+        since,
+        until,
+        sessions,
+        api_requests: totals.event_count,
+        cost_usd: totals.cost_usd,
+        input_tokens: totals.input_tokens,
+        output_tokens: totals.output_tokens,
+        cache_read_tokens: totals.cache_read_tokens,
+        cache_creation_tokens: totals.cache_creation_tokens,
+        lines_added: sum_where(&lines, |t| t.metric_type.as_deref() == Some("added")),
+        lines_removed: sum_where(&lines, |t| t.metric_type.as_deref() == Some("removed")),
+        commits: sum_where(&commits, |_| true),
+        pull_requests: sum_where(&pull_requests, |_| true),
+    }))
+}
+
+/// Trend parameters
+#[derive(Debug, Deserialize)]
+pub struct TrendQuery {
+    /// "hour" or "day" (default), in local time
+    pub period: Option<String>,
+    /// "model" to split each period by model
+    pub by: Option<String>,
+}
+
+/// GET /api/v1/stats/trends — API request totals per period
+pub async fn get_trends(
+    State(state): State<AppState>,
+    ApiQuery(range): ApiQuery<RangeQuery>,
+    ApiQuery(query): ApiQuery<TrendQuery>,
+) -> Result<Json<Vec<EventTotals>>, ApiError> {
+    let (since, until) = range.bounds()?;
+    let period = match query.period.as_deref() {
+        Some("hour") => "%Y-%m-%d %H:00",
+        Some("day") | None => "%Y-%m-%d",
+        Some(other) => return Err(invalid(format!("Unknown period: {}", other))),
+    };
+    let model = match query.by.as_deref() {
+        Some("model") => true,
+        None => false,
+        Some(other) => return Err(invalid(format!("Unknown grouping: {}", other))),
+    };
+
+    let trends = RollupRepository::sum_events(
+        &state.db,
+        API_REQUEST,
+        since,
+        until,
+        range.source.as_deref(),
+        RollupGroup {
+            period: Some(period),
+            model,
+        },
+    )
+    .await?;
+    Ok(Json(trends))
+}
+
+/// Notification filters
+#[derive(Debug, Deserialize)]
+pub struct NotificationQuery {
+    pub session_id: Option<String>,
+}
+
+/// GET /api/v1/notifications — hook notifications, most recent first
+pub async fn list_notifications(
+    State(state): State<AppState>,
+    ApiQuery(range): ApiQuery<RangeQuery>,
+    ApiQuery(page): ApiQuery<PageQuery>,
+    ApiQuery(query): ApiQuery<NotificationQuery>,
+) -> Result<Json<Page<Notification>>, ApiError> {
+    let (since, until) = range.bounds()?;
+    let notifications = NotificationRepository::find_filtered(
+        &state.db,
+        since,
+        until,
+        query.session_id.as_deref(),
+        page.limit() + 1,
+        page.offset(),
+    )
+    .await?;
+    Ok(Json(Page::new(notifications, &page)))
+}
+
+fn invalid(message: String) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, message)
+}
+
+/// Sum the values of metric totals matching `keep`
+fn sum_where(totals: &[MetricTotals], keep: impl Fn(&MetricTotals) -> bool) -> f64 {
+    totals
+        .iter()
+        .filter(|t| keep(t))
+        .fold(0.0, |sum, t| sum + t.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_has_next_offset_only_with_extra_item() {
+        let query = PageQuery {
+            limit: Some(2),
+            offset: Some(4),
+        };
+        let page = Page::new(vec![1, 2, 3], &query);
+        assert_eq!(page.items, [1, 2]);
+        assert_eq!(page.next_offset, Some(6));
+
+        let last = Page::new(vec![1, 2], &query);
+        assert_eq!(last.next_offset, None);
+
+        let clamped = PageQuery {
+            limit: Some(0),
+            offset: Some(-1),
+        };
+        assert_eq!((clamped.limit(), clamped.offset()), (1, 0));
+        assert_eq!(sum_where(&[], |_| true).to_bits(), 0.0f64.to_bits());
+    }
+}
@@ -0,0 +1,45 @@
+//! Attribute catalog handler
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use shared::AttributeCatalogRepository;
+use tracing::error;
+
+use crate::server::AppState;
+
+/// Query parameters for the catalog
+#[derive(Debug, Deserialize)]
+pub struct CatalogQuery {
+    /// "log" or "metric"
+    pub signal: Option<String>,
+    /// Only entries first seen at or after this Unix millisecond timestamp
+    pub since: Option<i64>,
+}
+
+/// GET /catalog — attribute keys seen per event and metric name
+pub async fn get_catalog(
+    State(state): State<AppState>,
+    Query(query): Query<CatalogQuery>,
+) -> impl IntoResponse {
+    match AttributeCatalogRepository::find_all(&state.db, query.signal.as_deref(), query.since)
+        .await
+    {
+        Ok(entries) => (StatusCode::OK, Json(json!(entries))),
+        Err(e) => {
+            error!("Failed to load attribute catalog: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("Failed to load attribute catalog: {}", e),
+                })),
+            )
+        }
+    }
+}
@@ -2,57 +2,37 @@
 //!
 //! Handles POST /v1/metrics - OTLP metrics endpoint
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
-use serde_json::json;
-use shared::MetricRepository;
+use axum::{extract::State, http::StatusCode, response::Response};
+use opentelemetry_proto::tonic::collector::metrics::v1::{
+    ExportMetricsPartialSuccess, ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
 use tracing::{error, info};
 
+use super::otlp_codec::{OtlpError, OtlpPayload};
 use crate::server::AppState;
-use crate::services::parse_metrics;
+use crate::services::ingest_metrics;
 
 /// POST /v1/metrics - OTLP metrics endpoint
 pub async fn export_metrics(
     State(state): State<AppState>,
-    Json(payload): Json<ExportMetricsServiceRequest>,
-) -> impl IntoResponse {
-    info!("Received OTLP metrics export request");
-
-    // Parse OTLP metrics into our entities
-    let metrics = parse_metrics(&payload);
-    let count = metrics.len();
-
-    if count == 0 {
-        return (
-            StatusCode::OK,
-            Json(json!({
-                "status": "success",
-                "message": "No metrics to process",
-            })),
-        );
-    }
-
-    info!("Parsed {} metrics", count);
-
-    // Insert metrics into database
-    if let Err(e) = MetricRepository::insert_batch(&state.db, &metrics).await {
-        error!("Failed to insert metrics: {}", e);
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
-                "status": "error",
-                "message": format!("Failed to store metrics: {}", e),
-            })),
-        );
-    }
-
-    info!("Stored {} metrics", count);
-
-    (
+    OtlpPayload { encoding, payload }: OtlpPayload<ExportMetricsServiceRequest>,
+) -> Result<Response, OtlpError> {
+    info!("Received OTLP metrics export request ({:?})", encoding);
+
+    let outcome = ingest_metrics(&state, &payload).await.map_err(|e| {
+        error!("{}", e);
+        OtlpError::unavailable(encoding, e.to_string())
+    })?;
+
+    let partial_success = outcome
+        .error_message
+        .map(|error_message| ExportMetricsPartialSuccess {
+            rejected_data_points: outcome.rejected as i64,
+            error_message,
+        });
+
+    Ok(encoding.encode(
         StatusCode::OK,
-        Json(json!({
-            "status": "success",
-            "metrics_received": count,
-        })),
-    )
+        &ExportMetricsServiceResponse { partial_success },
+    ))
 }
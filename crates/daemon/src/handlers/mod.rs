@@ -1,11 +1,26 @@
 //! HTTP request handlers
 
+mod api;
+mod catalog;
 mod health;
 mod logs;
 mod metrics;
 mod notify;
+mod otlp_codec;
+mod prometheus;
+mod redaction;
+mod status;
+mod traces;
 
+pub use api::{
+    get_session, get_summary, get_trends, list_notifications, list_session_events, list_sessions,
+};
+pub use catalog::get_catalog;
 pub use health::health_check;
 pub use logs::export_logs;
 pub use metrics::export_metrics;
 pub use notify::notify;
+pub use prometheus::get_prometheus_metrics;
+pub use redaction::get_redaction_report;
+pub use status::get_status;
+pub use traces::export_traces;
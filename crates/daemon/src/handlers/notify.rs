@@ -1,10 +1,12 @@
 //! Notification handler
 
+use std::sync::atomic::Ordering;
+
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use serde::Deserialize;
 use serde_json::json;
-use shared::{NewNotification, NotificationRepository};
-use tracing::{error, info};
+use shared::{NewNotification, NotificationRepository, SessionRepository};
+use tracing::{error, info, warn};
 
 use crate::server::AppState;
 
@@ -27,6 +29,7 @@ pub async fn notify(
     State(state): State<AppState>,
     Json(payload): Json<NotifyRequest>,
 ) -> impl IntoResponse {
+    state.counters.notifications.fetch_add(1, Ordering::Relaxed);
     let hook_event = payload.hook_event.unwrap_or_else(|| "Unknown".to_string());
 
     let title = payload.title.unwrap_or_else(|| default_title(&hook_event));
@@ -34,7 +37,7 @@ pub async fn notify(
         .message
         .unwrap_or_else(|| default_message(&hook_event));
 
-    let notif = NewNotification {
+    let mut notif = NewNotification {
         session_id: payload.session_id,
         hook_event: hook_event.clone(),
         notification_type: payload.notification_type,
@@ -43,6 +46,20 @@ pub async fn notify(
         cwd: payload.cwd,
         transcript_path: payload.transcript_path,
     };
+    state.redactor.redact_notification(&mut notif);
+
+    // Hooks carry the session's working directory and lifecycle
+    if let Err(e) = SessionRepository::record_hook(
+        &state.db,
+        &notif.session_id,
+        session_status(&hook_event),
+        notif.cwd.as_deref(),
+        chrono::Utc::now().timestamp_millis(),
+    )
+    .await
+    {
+        warn!("Failed to update session {}: {}", notif.session_id, e);
+    }
 
     match NotificationRepository::insert(&state.db, &notif).await {
         Ok(id) => {
@@ -68,6 +85,16 @@ pub async fn notify(
     }
 }
 
+/// Session status a hook event moves its session to
+fn session_status(hook_event: &str) -> Option<&'static str> {
+    match hook_event {
+        "SessionStart" | "UserPromptSubmit" => Some("active"),
+        "Stop" => Some("idle"),
+        "SessionEnd" => Some("ended"),
+        _ => None,
+    }
+}
+
 fn default_title(hook_event: &str) -> String {
     match hook_event {
         "Notification" => "Claude Code".to_string(),
@@ -0,0 +1,229 @@
+//! OTLP/HTTP payload codec
+//!
+//! Negotiates between the `http/json` and `http/protobuf` OTLP encodings based
+//! on the request `Content-Type`, and encodes responses (including error
+//! `Status` bodies) to match.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use prost::Message;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::warn;
+
+const CONTENT_TYPE_PROTOBUF: &str = "application/x-protobuf";
+
+/// Back-off suggested to exporters when data cannot be accepted right now
+const RETRY_AFTER_SECS: u64 = 5;
+
+/// Wire encoding of an OTLP/HTTP request or response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtlpEncoding {
+    Json,
+    Protobuf,
+}
+
+impl OtlpEncoding {
+    /// Determine the encoding from the request `Content-Type` header
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())?;
+
+        // Ignore parameters such as `; charset=utf-8`
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        match mime.as_str() {
+            "application/x-protobuf" | "application/protobuf" => Some(Self::Protobuf),
+            "application/json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Encode an OTLP response message in this encoding
+    pub fn encode<T>(self, status: StatusCode, message: &T) -> Response
+    where
+        T: Message + Serialize,
+    {
+        match self {
+            Self::Json => (status, Json(message)).into_response(),
+            Self::Protobuf => (
+                status,
+                [(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(CONTENT_TYPE_PROTOBUF),
+                )],
+                message.encode_to_vec(),
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// Extractor for OTLP export requests in either JSON or protobuf encoding
+pub struct OtlpPayload<T> {
+    pub encoding: OtlpEncoding,
+    pub payload: T,
+}
+
+impl<T, S> FromRequest<S> for OtlpPayload<T>
+where
+    T: Message + Default + DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = OtlpError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let encoding = OtlpEncoding::from_headers(req.headers()).ok_or_else(|| OtlpError {
+            encoding: OtlpEncoding::Json,
+            status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            message: "Expected Content-Type application/json or application/x-protobuf".to_string(),
+            retry_after: None,
+        })?;
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| OtlpError {
+                encoding,
+                status: e.status(),
+                message: e.body_text(),
+                retry_after: None,
+            })?;
+
+        let payload = match encoding {
+            OtlpEncoding::Json => serde_json::from_slice(&body).map_err(|e| {
+                OtlpError::bad_request(encoding, format!("Invalid OTLP JSON: {}", e))
+            })?,
+            OtlpEncoding::Protobuf => T::decode(body).map_err(|e| {
+                OtlpError::bad_request(encoding, format!("Invalid OTLP protobuf: {}", e))
+            })?,
+        };
+
+        Ok(Self { encoding, payload })
+    }
+}
+
+/// `google.rpc.Status`, the body of OTLP/HTTP error responses
+///
+/// `details` is never populated, so it is left out.
+#[derive(Clone, PartialEq, Message, Serialize)]
+pub struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    pub code: i32,
+    #[prost(string, tag = "2")]
+    pub message: String,
+}
+
+/// An OTLP/HTTP error response
+///
+/// 4xx responses tell the exporter not to retry; 503 with `Retry-After`
+/// tells it to back off and send the same data again.
+#[derive(Debug)]
+pub struct OtlpError {
+    pub encoding: OtlpEncoding,
+    pub status: StatusCode,
+    pub message: String,
+    /// Seconds the exporter should wait before retrying
+    pub retry_after: Option<u64>,
+}
+
+impl OtlpError {
+    /// The request is malformed and must not be retried
+    pub fn bad_request(encoding: OtlpEncoding, message: String) -> Self {
+        Self {
+            encoding,
+            status: StatusCode::BAD_REQUEST,
+            message,
+            retry_after: None,
+        }
+    }
+
+    /// The data could not be accepted right now and should be retried
+    pub fn unavailable(encoding: OtlpEncoding, message: String) -> Self {
+        Self {
+            encoding,
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            message,
+            retry_after: Some(RETRY_AFTER_SECS),
+        }
+    }
+}
+
+impl IntoResponse for OtlpError {
+    fn into_response(self) -> Response {
+        warn!("Rejected OTLP request ({}): {}", self.status, self.message);
+
+        // google.rpc.Code values
+        let code = match self.status {
+            StatusCode::SERVICE_UNAVAILABLE => 14,   // UNAVAILABLE
+            StatusCode::PAYLOAD_TOO_LARGE => 8,      // RESOURCE_EXHAUSTED
+            status if status.is_client_error() => 3, // INVALID_ARGUMENT
+            _ => 13,                                 // INTERNAL
+        };
+
+        let mut response = self.encoding.encode(
+            self.status,
+            &RpcStatus {
+                code,
+                message: self.message,
+            },
+        );
+        if let Some(seconds) = self.retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(content_type: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, content_type.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_encoding_from_content_type() {
+        assert_eq!(
+            OtlpEncoding::from_headers(&headers("application/x-protobuf")),
+            Some(OtlpEncoding::Protobuf)
+        );
+        assert_eq!(
+            OtlpEncoding::from_headers(&headers("application/json; charset=utf-8")),
+            Some(OtlpEncoding::Json)
+        );
+        assert_eq!(OtlpEncoding::from_headers(&headers("text/plain")), None);
+        assert_eq!(OtlpEncoding::from_headers(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_unavailable_sets_retry_after() {
+        let response =
+            OtlpError::unavailable(OtlpEncoding::Protobuf, "busy".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "5");
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            CONTENT_TYPE_PROTOBUF
+        );
+
+        let response =
+            OtlpError::bad_request(OtlpEncoding::Json, "bad".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
+    }
+}
@@ -0,0 +1,26 @@
+//! Prometheus metrics handler
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use tracing::error;
+
+use crate::server::AppState;
+use crate::services::{render_metrics, PROMETHEUS_CONTENT_TYPE};
+
+/// GET /metrics — usage and ingestion metrics in the Prometheus text format
+pub async fn get_prometheus_metrics(State(state): State<AppState>) -> Response {
+    match render_metrics(&state.db, &state.counters).await {
+        Ok(body) => ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], body).into_response(),
+        Err(e) => {
+            error!("Failed to render metrics: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to render metrics: {}", e),
+            )
+                .into_response()
+        }
+    }
+}
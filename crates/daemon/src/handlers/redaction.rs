@@ -0,0 +1,10 @@
+//! Redaction report handler
+
+use axum::{extract::State, response::IntoResponse, Json};
+
+use crate::server::AppState;
+
+/// GET /redaction — values redacted since the daemon started
+pub async fn get_redaction_report(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.redactor.report())
+}
@@ -0,0 +1,18 @@
+//! Status handler
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+
+use crate::server::AppState;
+use crate::services::collect_status;
+
+/// GET /status — ingestion, queue and database status of the running daemon
+pub async fn get_status(State(state): State<AppState>) -> impl IntoResponse {
+    let status = collect_status(&state).await;
+    let code = if status.database.connected {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (code, Json(status))
+}
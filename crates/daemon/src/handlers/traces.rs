@@ -0,0 +1,38 @@
+//! Traces handler
+//!
+//! Handles POST /v1/traces - OTLP traces endpoint
+
+use axum::{extract::State, http::StatusCode, response::Response};
+use opentelemetry_proto::tonic::collector::trace::v1::{
+    ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use tracing::{error, info};
+
+use super::otlp_codec::{OtlpError, OtlpPayload};
+use crate::server::AppState;
+use crate::services::ingest_traces;
+
+/// POST /v1/traces - OTLP traces endpoint
+pub async fn export_traces(
+    State(state): State<AppState>,
+    OtlpPayload { encoding, payload }: OtlpPayload<ExportTraceServiceRequest>,
+) -> Result<Response, OtlpError> {
+    info!("Received OTLP traces export request ({:?})", encoding);
+
+    let outcome = ingest_traces(&state, &payload).await.map_err(|e| {
+        error!("{}", e);
+        OtlpError::unavailable(encoding, e.to_string())
+    })?;
+
+    let partial_success = outcome
+        .error_message
+        .map(|error_message| ExportTracePartialSuccess {
+            rejected_spans: outcome.rejected as i64,
+            error_message,
+        });
+
+    Ok(encoding.encode(
+        StatusCode::OK,
+        &ExportTraceServiceResponse { partial_success },
+    ))
+}
@@ -1,36 +1,58 @@
 //! Lumo Daemon
 //!
 //! Receives OTLP telemetry data from Claude Code and stores it in SQLite.
+//! Subcommands cover operations on the daemon and its database (see [`cli`]).
+
+use std::process::ExitCode;
+use std::sync::Arc;
 
 use anyhow::Result;
 use tracing::{error, info};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
 
+mod cli;
 mod config;
+mod grpc;
 mod handlers;
 mod routes;
 mod server;
 mod services;
 
-use config::Config;
-use server::{create_app, shutdown_signal, AppState};
+use cli::Command;
+use server::{create_app, watch_config, AppState, LogHandle, Shutdown};
+use services::{AttributeMapping, IngestCounters, IngestQueue, Redactor, Retention};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
+    let command = match Command::parse(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            return Ok(ExitCode::from(2));
+        }
+    };
+
+    match command {
+        Command::Serve => serve().await.map(|()| ExitCode::SUCCESS),
+        command => cli::run(command).await,
+    }
+}
+
+/// Run the server until a shutdown signal arrives
+async fn serve() -> Result<()> {
     // Load configuration
-    let config = Config::from_env()?;
-    config.validate()?;
+    let config = config::load()?;
 
-    // Initialize tracing/logging
+    // Initialize tracing/logging; the filter can be swapped on config reload
+    let (log_filter, log_handle): (_, LogHandle) =
+        reload::Layer::new(EnvFilter::new(&config.log_level));
     tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| config.log_level.clone().into()),
-        )
+        .with(log_filter)
         .with(tracing_subscriber::fmt::layer())
         .init();
 
     info!("Starting Lumo Daemon v{}", env!("CARGO_PKG_VERSION"));
+    info!("Config file: {}", config.config_path.display());
 
     // Initialize database
     let db_path = shared::get_db_path()?;
@@ -43,31 +65,161 @@ async fn main() -> Result<()> {
     shared::run_migrations(&pool).await?;
     info!("Database migrations completed");
 
+    let mapping = AttributeMapping::load(&config.attribute_mapping_path)?;
+    info!(
+        "Attribute mapping: {}",
+        config.attribute_mapping_path.display()
+    );
+
+    let redactor = Redactor::load(&config.redaction_path, &mapping)?;
+    info!("Redaction policy: {}", config.redaction_path.display());
+
+    let retention = Retention::load(&config.retention_path)?;
+    info!(
+        "Retention policy: {} ({:?})",
+        config.retention_path.display(),
+        retention.policy()
+    );
+
+    let auth_token = if config.auth_enabled {
+        let token = shared::load_or_create_token()?;
+        info!("Auth token: {}", shared::get_token_path()?.display());
+        Some(token)
+    } else {
+        info!("Authentication disabled (set LUMO_AUTH=on to require the token)");
+        None
+    };
+
+    let shutdown = Shutdown::listen();
+
+    // Start the background writer that stores parsed exports
+    let counters = Arc::new(IngestCounters::default());
+    let (queue, queue_task) = IngestQueue::start(
+        pool.clone(),
+        config.queue_capacity,
+        config.spool_path.clone(),
+        counters.clone(),
+        shutdown.clone().wait(),
+    );
+    info!("Spool path: {}", config.spool_path.display());
+
     // Create application state
-    let state = AppState::new(pool, config.clone());
+    let state = AppState::new(
+        pool,
+        config.clone(),
+        queue,
+        counters,
+        mapping,
+        redactor,
+        retention,
+    )
+    .with_auth_token(auth_token);
 
     // Create Axum app
-    let app = create_app(state);
-
-    // Create TCP listener
-    let listener = tokio::net::TcpListener::bind(&config.server_address)
-        .await
-        .map_err(|e| {
-            error!("Failed to bind to {}: {}", config.server_address, e);
-            e
-        })?;
-
-    info!("Server listening on http://{}", listener.local_addr()?);
-    info!("Health check: http://{}/health", listener.local_addr()?);
-    info!("OTLP endpoints:");
-    info!("  - Metrics: http://{}/v1/metrics", listener.local_addr()?);
-    info!("  - Logs:    http://{}/v1/logs", listener.local_addr()?);
+    let app = create_app(state.clone());
+
+    // Create TCP listener; "off" leaves only the Unix socket
+    let listener = match &config.server_address {
+        Some(server_address) => {
+            let listener = tokio::net::TcpListener::bind(server_address)
+                .await
+                .map_err(|e| {
+                    error!("Failed to bind to {}: {}", server_address, e);
+                    e
+                })?;
+
+            info!("Server listening on http://{}", listener.local_addr()?);
+            info!("Health check: http://{}/health", listener.local_addr()?);
+            info!("OTLP endpoints:");
+            info!("  - Metrics: http://{}/v1/metrics", listener.local_addr()?);
+            info!("  - Logs:    http://{}/v1/logs", listener.local_addr()?);
+            info!("  - Traces:  http://{}/v1/traces", listener.local_addr()?);
+            info!(
+                "Attribute catalog: http://{}/catalog",
+                listener.local_addr()?
+            );
+            info!(
+                "Redaction report: http://{}/redaction",
+                listener.local_addr()?
+            );
+            Some(listener)
+        }
+        None => {
+            info!("HTTP listener is off; serving the Unix socket only");
+            None
+        }
+    };
     info!("Press Ctrl+C to stop");
 
-    // Run server with graceful shutdown
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // The gRPC receiver is opt-in, and failing to start it (e.g. port 4317 is
+    // taken by another collector) must not take the HTTP endpoints down.
+    let grpc_task = match &config.grpc_address {
+        Some(grpc_address) => {
+            let addr = grpc_address.parse()?;
+            let grpc_shutdown = shutdown.clone().wait();
+            let grpc_state = state.clone();
+            Some(tokio::spawn(async move {
+                if let Err(e) = grpc::serve(addr, grpc_state, grpc_shutdown).await {
+                    error!("OTLP gRPC receiver stopped: {:#}", e);
+                }
+            }))
+        }
+        None => None,
+    };
+
+    #[cfg(unix)]
+    let mut socket_task = config.socket_path.clone().map(|path| {
+        let socket_state = state.clone();
+        let socket_shutdown = shutdown.clone().wait();
+        tokio::spawn(async move {
+            let result = server::serve_unix(&path, socket_state, socket_shutdown).await;
+            if let Err(e) = &result {
+                error!("Unix socket listener stopped: {:#}", e);
+            }
+            result
+        })
+    });
+
+    // Delete telemetry older than the retention policy allows
+    let retention_state = state.clone();
+    let retention_shutdown = shutdown.clone().wait();
+    tokio::spawn(async move {
+        retention_state
+            .retention
+            .run(retention_state.db.clone(), retention_shutdown)
+            .await
+    });
+
+    // Apply log level and policy changes without a restart
+    tokio::spawn(watch_config(
+        state.clone(),
+        log_handle,
+        shutdown.clone().wait(),
+    ));
+
+    // Run server with graceful shutdown. Without TCP the Unix socket
+    // listener is the server; config validation guarantees there is one
+    if let Some(listener) = listener {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown.wait())
+            .await?;
+    } else {
+        #[cfg(unix)]
+        if let Some(task) = socket_task.take() {
+            task.await??;
+        }
+    }
+
+    if let Some(task) = grpc_task {
+        let _ = task.await;
+    }
+    #[cfg(unix)]
+    if let Some(task) = socket_task {
+        let _ = task.await;
+    }
+
+    // Store whatever is still queued before exiting
+    let _ = queue_task.await;
 
     info!("Server shut down gracefully");
     Ok(())
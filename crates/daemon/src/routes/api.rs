@@ -0,0 +1,56 @@
+//! Query API routes
+//!
+//! Versioned, read-only JSON endpoints under `/api/v1`.
+
+use axum::{routing::get, Router};
+
+use crate::handlers;
+use crate::server::AppState;
+
+/// Create query API routes
+pub fn api_routes() -> Router<AppState> {
+    let v1 = Router::new()
+        .route("/sessions", get(handlers::list_sessions))
+        .route("/sessions/{id}", get(handlers::get_session))
+        .route("/sessions/{id}/events", get(handlers::list_session_events))
+        .route("/stats/summary", get(handlers::get_summary))
+        .route("/stats/trends", get(handlers::get_trends))
+        .route("/notifications", get(handlers::list_notifications));
+
+    Router::new().nest("/api/v1", v1)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_invalid_parameters_are_json_errors() {
+        let (state, _dir) = AppState::for_test().await;
+        let app = api_routes().with_state(state);
+
+        for uri in [
+            "/api/v1/sessions?limit=ten",
+            "/api/v1/sessions?since=yesterday",
+            "/api/v1/stats/trends?period=week",
+        ] {
+            let response = app
+                .clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+
+            let body: Value =
+                serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap())
+                    .unwrap();
+            assert_eq!(body["status"], json!("error"), "{}", uri);
+            assert!(body["message"].is_string(), "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Attribute catalog routes
+
+use axum::{routing::get, Router};
+
+use crate::handlers;
+use crate::server::AppState;
+
+/// Create attribute catalog routes
+pub fn catalog_routes() -> Router<AppState> {
+    Router::new().route("/catalog", get(handlers::get_catalog))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use serde_json::{json, Value};
+    use shared::{AttributeCatalogRepository, NewAttributeCatalogEntry};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_catalog_lists_entries_by_signal() {
+        let (state, _dir) = AppState::for_test().await;
+        let entries: Vec<_> = [
+            ("log", "claude_code.api_request"),
+            ("metric", "claude_code.cost.usage"),
+        ]
+        .into_iter()
+        .map(|(signal, name)| NewAttributeCatalogEntry {
+            signal: signal.to_string(),
+            name: name.to_string(),
+            attribute_key: "model".to_string(),
+            value_type: "string".to_string(),
+            app_version: None,
+            first_seen: 1000,
+            last_seen: 2000,
+            count: 4,
+        })
+        .collect();
+        AttributeCatalogRepository::upsert_batch(&state.db, &entries)
+            .await
+            .unwrap();
+
+        let response = catalog_routes()
+            .with_state(state)
+            .oneshot(
+                Request::get("/catalog?signal=metric")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap())
+                .unwrap();
+        assert_eq!(
+            body,
+            json!([{
+                "signal": "metric",
+                "name": "claude_code.cost.usage",
+                "attributeKey": "model",
+                "valueType": "string",
+                "firstSeen": 1000,
+                "lastSeen": 2000,
+                "count": 4,
+            }])
+        );
+    }
+}
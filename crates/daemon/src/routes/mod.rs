@@ -2,10 +2,20 @@
 //!
 //! Organizes routes by functionality.
 
+mod api;
+mod catalog;
 mod health;
 mod notify;
 mod otlp;
+mod prometheus;
+mod redaction;
+mod status;
 
+pub use api::api_routes;
+pub use catalog::catalog_routes;
 pub use health::health_routes;
 pub use notify::notify_routes;
 pub use otlp::otlp_routes;
+pub use prometheus::prometheus_routes;
+pub use redaction::redaction_routes;
+pub use status::status_routes;
@@ -2,14 +2,143 @@
 //!
 //! Routes for receiving OpenTelemetry data.
 
-use axum::{routing::post, Router};
+use axum::{extract::DefaultBodyLimit, routing::post, Router};
+use tower_http::decompression::RequestDecompressionLayer;
 
 use crate::handlers;
 use crate::server::AppState;
 
 /// Create OTLP routes
-pub fn otlp_routes() -> Router<AppState> {
+///
+/// Request bodies sent with `Content-Encoding: gzip` or `deflate` are
+/// decompressed transparently. `max_body_bytes` caps the decompressed size,
+/// so a small compressed payload cannot expand without bound.
+pub fn otlp_routes(max_body_bytes: usize) -> Router<AppState> {
     Router::new()
         .route("/v1/metrics", post(handlers::export_metrics))
         .route("/v1/logs", post(handlers::export_logs))
+        .route("/v1/traces", post(handlers::export_traces))
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+        .layer(RequestDecompressionLayer::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use flate2::{write::GzEncoder, Compression};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn gzip(body: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn logs_request(body: Vec<u8>) -> Request<Body> {
+        Request::post("/v1/logs")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn logs_export(session_id: &str, padding: usize) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "resourceLogs": [{
+                "scopeLogs": [{
+                    "logRecords": [{
+                        "timeUnixNano": "1700000000000000000",
+                        "body": { "stringValue": "x".repeat(padding) },
+                        "attributes": [
+                            { "key": "event.name", "value": { "stringValue": "user_prompt" } },
+                            { "key": "session.id", "value": { "stringValue": session_id } }
+                        ]
+                    }]
+                }]
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_gzip_json_export_is_stored() {
+        let (state, _dir) = AppState::for_test().await;
+        let app = otlp_routes(64 * 1024).with_state(state.clone());
+
+        let response = app
+            .oneshot(logs_request(gzip(&logs_export("gzip-session", 0))))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Events are written by the background queue shortly after the ACK
+        let mut events = Vec::new();
+        for _ in 0..50 {
+            events = shared::EventRepository::find_by_session(&state.db, "gzip-session")
+                .await
+                .unwrap();
+            if !events.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "claude_code.user_prompt");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_export_is_accepted_while_database_is_unavailable() {
+        let (state, _dir) = AppState::for_test().await;
+        state.db.close().await;
+        let app = otlp_routes(64 * 1024).with_state(state);
+
+        // A cumulative Sum from a series the daemon has not seen yet
+        let body = serde_json::to_vec(&serde_json::json!({
+            "resourceMetrics": [{
+                "scopeMetrics": [{
+                    "metrics": [{
+                        "name": "claude_code.token.usage",
+                        "sum": {
+                            "aggregationTemporality": 2,
+                            "isMonotonic": true,
+                            "dataPoints": [{
+                                "startTimeUnixNano": "1700000000000000000",
+                                "timeUnixNano": "1700000060000000000",
+                                "asDouble": 42.0,
+                                "attributes": [
+                                    { "key": "session.id", "value": { "stringValue": "closed-session" } }
+                                ]
+                            }]
+                        }
+                    }]
+                }]
+            }]
+        }))
+        .unwrap();
+        let request = Request::post("/v1/metrics")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_body_over_limit_after_decompression_is_rejected() {
+        let (state, _dir) = AppState::for_test().await;
+        let app = otlp_routes(64 * 1024).with_state(state);
+
+        // Compresses to well under the limit but expands past it
+        let body = gzip(&logs_export("large-session", 256 * 1024));
+        assert!(body.len() < 64 * 1024);
+
+        let response = app.oneshot(logs_request(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
@@ -0,0 +1,13 @@
+//! Prometheus routes
+
+use axum::{routing::get, Router};
+
+use crate::handlers;
+use crate::server::AppState;
+
+/// Create Prometheus scrape routes
+///
+/// Not to be confused with `POST /v1/metrics`, which receives OTLP metrics.
+pub fn prometheus_routes() -> Router<AppState> {
+    Router::new().route("/metrics", get(handlers::get_prometheus_metrics))
+}
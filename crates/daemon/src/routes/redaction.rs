@@ -0,0 +1,11 @@
+//! Redaction report routes
+
+use axum::{routing::get, Router};
+
+use crate::handlers;
+use crate::server::AppState;
+
+/// Create redaction report routes
+pub fn redaction_routes() -> Router<AppState> {
+    Router::new().route("/redaction", get(handlers::get_redaction_report))
+}
@@ -0,0 +1,11 @@
+//! Status routes
+
+use axum::{routing::get, Router};
+
+use crate::handlers;
+use crate::server::AppState;
+
+/// Create status routes
+pub fn status_routes() -> Router<AppState> {
+    Router::new().route("/status", get(handlers::get_status))
+}
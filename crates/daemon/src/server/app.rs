@@ -1,17 +1,27 @@
 //! Application router setup
 
-use axum::Router;
+use axum::{middleware, Router};
 use tower_http::trace::TraceLayer;
 
 use crate::routes;
-use crate::server::AppState;
+use crate::server::{auth, AppState};
 
 /// Create the Axum application router
 pub fn create_app(state: AppState) -> Router {
     Router::new()
-        .merge(routes::health_routes())
-        .merge(routes::otlp_routes())
+        .merge(routes::otlp_routes(state.config.max_body_bytes))
         .merge(routes::notify_routes())
+        .merge(routes::catalog_routes())
+        .merge(routes::redaction_routes())
+        .merge(routes::api_routes())
+        .merge(routes::prometheus_routes())
+        .merge(routes::status_routes())
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_token,
+        ))
+        // Health checks stay unauthenticated
+        .merge(routes::health_routes())
         .layer(TraceLayer::new_for_http())
         .with_state(state)
 }
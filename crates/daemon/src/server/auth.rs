@@ -0,0 +1,85 @@
+//! Bearer token authentication
+//!
+//! When enabled, every endpoint except `/health` requires an
+//! `Authorization: Bearer <token>` header carrying the token from
+//! `~/.lumo/auth-token`, so other local processes cannot write or read
+//! telemetry.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use tracing::warn;
+
+use super::AppState;
+
+/// Whether an `Authorization` header value carries `token`
+pub fn is_authorized(token: &str, authorization: Option<&str>) -> bool {
+    let Some((scheme, presented)) = authorization.and_then(|value| value.split_once(' ')) else {
+        return false;
+    };
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return false;
+    }
+
+    // Compare without short-circuiting so timing does not leak the token
+    let presented = presented.trim().as_bytes();
+    presented.len() == token.len()
+        && presented
+            .iter()
+            .zip(token.as_bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Reject requests that do not carry the daemon token
+pub async fn require_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(token) = state.auth_token.as_deref() else {
+        return next.run(request).await;
+    };
+
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if is_authorized(token, authorization) {
+        return next.run(request).await;
+    }
+
+    warn!(
+        "Rejected unauthenticated request to {}",
+        request.uri().path()
+    );
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        Json(json!({
+            "status": "error",
+            "message": "Missing or invalid bearer token",
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_authorized() {
+        assert!(is_authorized("abc123", Some("Bearer abc123")));
+        assert!(is_authorized("abc123", Some("bearer abc123")));
+        assert!(!is_authorized("abc123", Some("Bearer abc124")));
+        assert!(!is_authorized("abc123", Some("Bearer abc")));
+        assert!(!is_authorized("abc123", Some("Basic abc123")));
+        assert!(!is_authorized("abc123", None));
+    }
+}
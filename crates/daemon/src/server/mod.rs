@@ -1,11 +1,19 @@
 //! Server module
 //!
-//! Contains the HTTP server setup, application state, and graceful shutdown.
+//! Contains the HTTP server setup, application state, authentication, the
+//! Unix socket listener, config reloading and graceful shutdown.
 
 mod app;
+pub mod auth;
+mod reload;
 mod shutdown;
+#[cfg(unix)]
+mod socket;
 mod state;
 
 pub use app::create_app;
-pub use shutdown::shutdown_signal;
+pub use reload::{watch_config, LogHandle};
+pub use shutdown::Shutdown;
+#[cfg(unix)]
+pub use socket::serve_unix;
 pub use state::AppState;
@@ -0,0 +1,96 @@
+//! Configuration hot reload
+//!
+//! Polls the config file and the redaction and retention policies for
+//! changes and applies the settings that are safe to change while running:
+//! the log level and both policies. Other changes are logged and wait for a
+//! restart.
+
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+
+use tracing::{error, info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use super::AppState;
+use crate::config::{self, Config};
+
+/// Handle for swapping the log filter at runtime
+pub type LogHandle = reload::Handle<EnvFilter, Registry>;
+
+/// How often the watched files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Reload settings whenever the watched files change, until `shutdown` resolves
+pub async fn watch_config<F>(state: AppState, log: LogHandle, shutdown: F)
+where
+    F: Future<Output = ()>,
+{
+    let mut config = (*state.config).clone();
+    let mut seen = modified(&config);
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = interval.tick() => {}
+        }
+
+        if modified(&config) == seen {
+            continue;
+        }
+
+        let loaded = config::load_from(&config.config_path);
+        match loaded {
+            Ok(loaded) => {
+                apply(&state, &log, &config, &loaded);
+                config = loaded;
+            }
+            Err(e) => warn!("Ignoring invalid config change: {:#}", e),
+        }
+        // The policy paths may have changed, so take the times again
+        seen = modified(&config);
+    }
+}
+
+/// Apply the reloadable settings of `new`
+fn apply(state: &AppState, log: &LogHandle, old: &Config, new: &Config) {
+    if new.log_level != old.log_level {
+        match log.reload(EnvFilter::new(&new.log_level)) {
+            Ok(()) => info!("Log level set to {}", new.log_level),
+            Err(e) => error!("Failed to change log level: {}", e),
+        }
+    }
+
+    match state.redactor.reload(&new.redaction_path, &state.mapping) {
+        Ok(()) => info!(
+            "Reloaded redaction policy from {}",
+            new.redaction_path.display()
+        ),
+        Err(e) => warn!("Keeping the previous redaction policy: {:#}", e),
+    }
+
+    match state.retention.reload(&new.retention_path) {
+        Ok(true) => info!("Retention policy changed: {:?}", state.retention.policy()),
+        Ok(false) => {}
+        Err(e) => warn!("Keeping the previous retention policy: {:#}", e),
+    }
+
+    let pending = state.config.restart_required(new);
+    if !pending.is_empty() {
+        warn!(
+            "Restart the daemon to apply changes to: {}",
+            pending.join(", ")
+        );
+    }
+}
+
+/// Modification times of the config file and the policies
+fn modified(config: &Config) -> [Option<SystemTime>; 3] {
+    [
+        &config.config_path,
+        &config.redaction_path,
+        &config.retention_path,
+    ]
+    .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+}
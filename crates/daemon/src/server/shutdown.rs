@@ -1,6 +1,7 @@
 //! Graceful shutdown handling
 
 use tokio::signal;
+use tokio::sync::watch;
 use tracing::info;
 
 /// Wait for shutdown signal (SIGTERM, SIGINT, or Ctrl+C)
@@ -33,3 +34,26 @@ pub async fn shutdown_signal() {
 
     info!("Initiating graceful shutdown");
 }
+
+/// Fans a single shutdown signal out to every server and background task
+#[derive(Clone)]
+pub struct Shutdown {
+    rx: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Start listening for the shutdown signal
+    pub fn listen() -> Self {
+        let (tx, rx) = watch::channel(false);
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            let _ = tx.send(true);
+        });
+        Self { rx }
+    }
+
+    /// Resolve once shutdown has been requested
+    pub async fn wait(mut self) {
+        let _ = self.rx.wait_for(|requested| *requested).await;
+    }
+}
@@ -0,0 +1,122 @@
+//! Unix domain socket listener
+//!
+//! Serves the HTTP router on a socket under `~/.lumo/run`. Access is
+//! controlled by file permissions: the directory and socket are only
+//! accessible to the current user, so requests skip token authentication.
+
+use std::fs;
+use std::future::Future;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use tokio::net::{UnixListener, UnixStream};
+use tracing::info;
+
+use super::{create_app, AppState};
+
+/// Serve the application on the Unix socket at `path` until `shutdown`
+/// resolves
+pub async fn serve_unix<F>(path: &Path, state: AppState, shutdown: F) -> Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create socket directory {}", dir.display()))?;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+
+    // Remove a socket left behind by a daemon that did not shut down cleanly,
+    // unless another daemon still accepts connections on it (the TCP listener
+    // may be off, so binding it first proves nothing)
+    if UnixStream::connect(path).await.is_ok() {
+        bail!("Another daemon is listening on {}", path.display());
+    }
+    match fs::remove_file(path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("Failed to remove stale socket {}", path.display()))
+        }
+    }
+
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Failed to bind Unix socket {}", path.display()))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+    info!("Unix socket listening on {}", path.display());
+
+    let app = create_app(AppState {
+        auth_token: None,
+        ..state
+    });
+    let result = axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown)
+        .await
+        .context("Unix socket server error");
+
+    let _ = fs::remove_file(path);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    /// Send a GET request without a token and return the response status code
+    async fn get(path: &Path, uri: &str) -> u16 {
+        let mut stream = UnixStream::connect(path).await.unwrap();
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            uri
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_socket_serves_without_token() {
+        let (state, dir) = AppState::for_test().await;
+        let state = state.with_auth_token(Some("secret".to_string()));
+        let path = dir.path().join("daemon.sock");
+
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn({
+            let (path, state) = (path.clone(), state.clone());
+            async move {
+                serve_unix(&path, state, async {
+                    let _ = stopped.await;
+                })
+                .await
+            }
+        });
+        for _ in 0..50 {
+            if UnixStream::connect(&path).await.is_ok() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        assert_eq!(get(&path, "/health").await, 200);
+        assert_eq!(get(&path, "/status").await, 200);
+
+        // A second listener must not take over the live socket
+        assert!(serve_unix(&path, state, std::future::ready(()))
+            .await
+            .is_err());
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -1,9 +1,11 @@
 //! Application state
 
+use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
 use crate::config::Config;
+use crate::services::{AttributeMapping, IngestCounters, IngestQueue, Redactor, Retention};
 
 /// Shared application state
 #[derive(Clone)]
@@ -11,16 +13,84 @@ pub struct AppState {
     /// Database connection pool
     pub db: SqlitePool,
     /// Application configuration
-    #[allow(dead_code)]
     pub config: Arc<Config>,
+    /// Queue of parsed exports waiting to be written
+    pub queue: IngestQueue,
+    /// Ingestion counters since the daemon started, shared with the queue
+    pub counters: Arc<IngestCounters>,
+    /// Which OTLP attributes fill which columns
+    pub mapping: Arc<AttributeMapping>,
+    /// Redaction applied to rows before they are queued
+    pub redactor: Arc<Redactor>,
+    /// How long raw telemetry is kept
+    pub retention: Arc<Retention>,
+    /// Token clients must present; `None` when authentication is disabled
+    pub auth_token: Option<Arc<str>>,
+    /// When the daemon started
+    pub started_at: DateTime<Utc>,
 }
 
 impl AppState {
-    /// Create a new application state
-    pub fn new(db: SqlitePool, config: Config) -> Self {
+    /// Create a new application state, with authentication disabled
+    pub fn new(
+        db: SqlitePool,
+        config: Config,
+        queue: IngestQueue,
+        counters: Arc<IngestCounters>,
+        mapping: AttributeMapping,
+        redactor: Redactor,
+        retention: Retention,
+    ) -> Self {
         Self {
             db,
             config: Arc::new(config),
+            queue,
+            counters,
+            mapping: Arc::new(mapping),
+            redactor: Arc::new(redactor),
+            retention: Arc::new(retention),
+            auth_token: None,
+            started_at: Utc::now(),
         }
     }
+
+    /// Require `token` from clients, if any
+    pub fn with_auth_token(self, token: Option<String>) -> Self {
+        Self {
+            auth_token: token.map(Arc::from),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State over a fresh database in a temporary directory, with
+    /// authentication disabled
+    ///
+    /// The directory holds every file the state uses and is removed when the
+    /// returned guard is dropped, so keep it alive for the whole test.
+    pub(crate) async fn for_test() -> (Self, tempfile::TempDir) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pool = shared::create_pool(&dir.path().join("lumo.db"))
+            .await
+            .unwrap();
+        shared::run_migrations(&pool).await.unwrap();
+
+        let config = crate::config::for_test(dir.path());
+        let counters = Arc::new(IngestCounters::default());
+        let (queue, _) = IngestQueue::start(
+            pool.clone(),
+            config.queue_capacity,
+            config.spool_path.clone(),
+            counters.clone(),
+            std::future::pending(),
+        );
+        let mapping = AttributeMapping::default();
+        let redactor = Redactor::load(&config.redaction_path, &mapping).unwrap();
+        let retention = Retention::load(&config.retention_path).unwrap();
+        let state = Self::new(pool, config, queue, counters, mapping, redactor, retention);
+
+        (state, dir)
+    }
 }
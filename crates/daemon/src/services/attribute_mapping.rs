@@ -0,0 +1,384 @@
+//! Attribute mapping
+//!
+//! Decides which OTLP attribute fills each `NewEvent` / `NewMetric` column.
+//! The built-in rules match the attributes Claude Code emits today. A JSON
+//! file can add aliases for renamed attributes, change how a value is
+//! coerced, and override rules for a single event or metric name:
+//!
+//! ```json
+//! {
+//!   "events": {
+//!     "fields": {
+//!       "cost_usd": { "keys": ["cost_usd", "cost.usd"], "type": "float" }
+//!     },
+//!     "overrides": {
+//!       "claude_code.tool_decision": {
+//!         "decision_source": { "keys": ["decision.source", "source"] }
+//!       }
+//!     }
+//!   },
+//!   "metrics": {
+//!     "fields": { "metric_type": { "keys": ["type", "token.type"] } }
+//!   }
+//! }
+//! ```
+//!
+//! A rule in the file replaces the built-in rule for the same field; an empty
+//! `keys` list leaves the column empty. Values are converted to the column's
+//! type after coercion, so `type` only needs to be set to parse a value
+//! differently, e.g. `"type": "integer", "scale": 1000` for seconds stored in
+//! a milliseconds column.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// Built-in event rules: column, attribute key, coercion
+#[rustfmt::skip]
+const EVENT_FIELDS: &[(&str, &str, Coercion)] = &[
+    ("name", "event.name", Coercion::String),
+    ("session_id", "session.id", Coercion::String),
+    ("duration_ms", "duration_ms", Coercion::Integer),
+    ("success", "success", Coercion::Bool),
+    ("error", "error", Coercion::String),
+    ("model", "model", Coercion::String),
+    ("cost_usd", "cost_usd", Coercion::Float),
+    ("input_tokens", "input_tokens", Coercion::Integer),
+    ("output_tokens", "output_tokens", Coercion::Integer),
+    ("cache_read_tokens", "cache_read_tokens", Coercion::Integer),
+    ("cache_creation_tokens", "cache_creation_tokens", Coercion::Integer),
+    ("status_code", "status_code", Coercion::Integer),
+    ("attempt", "attempt", Coercion::Integer),
+    ("tool_name", "tool_name", Coercion::String),
+    ("tool_decision", "decision", Coercion::String),
+    ("decision_source", "source", Coercion::String),
+    ("tool_parameters", "tool_parameters", Coercion::String),
+    ("prompt_length", "prompt_length", Coercion::Integer),
+    ("prompt", "prompt", Coercion::String),
+    ("account_uuid", "user.account_uuid", Coercion::String),
+    ("organization_id", "organization.id", Coercion::String),
+    ("terminal_type", "terminal.type", Coercion::String),
+    ("app_version", "app.version", Coercion::String),
+    ("user_id", "user.id", Coercion::String),
+    ("user_email", "user.email", Coercion::String),
+    ("event_sequence", "event.sequence", Coercion::Integer),
+    ("tool_result_size_bytes", "tool_result_size_bytes", Coercion::Integer),
+];
+
+/// Built-in metric rules: column, attribute key, coercion
+const METRIC_FIELDS: &[(&str, &str, Coercion)] = &[
+    ("session_id", "session.id", Coercion::String),
+    ("metric_type", "type", Coercion::String),
+    ("model", "model", Coercion::String),
+    ("tool", "tool", Coercion::String),
+    ("decision", "decision", Coercion::String),
+    ("language", "language", Coercion::String),
+    ("account_uuid", "user.account_uuid", Coercion::String),
+    ("organization_id", "organization.id", Coercion::String),
+    ("terminal_type", "terminal.type", Coercion::String),
+    ("app_version", "app.version", Coercion::String),
+    ("user_id", "user.id", Coercion::String),
+    ("user_email", "user.email", Coercion::String),
+];
+
+/// How an attribute value is interpreted before it is stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Coercion {
+    #[default]
+    String,
+    /// Whole number; decimal values are rounded
+    Integer,
+    Float,
+    /// `true`, `1` or `yes`; any other value is false
+    Bool,
+}
+
+/// Where a column's value comes from
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldRule {
+    /// Attribute keys in order of preference; the first one present is used
+    pub keys: Vec<String>,
+    /// How the value is interpreted
+    #[serde(rename = "type", default)]
+    pub coercion: Coercion,
+    /// Multiplier for `integer` and `float` values, e.g. 1000 for seconds to milliseconds
+    #[serde(default)]
+    pub scale: Option<f64>,
+}
+
+impl FieldRule {
+    fn builtin(key: &str, coercion: Coercion) -> Self {
+        Self {
+            keys: vec![key.to_string()],
+            coercion,
+            scale: None,
+        }
+    }
+
+    /// Read and coerce the first present key
+    fn apply(&self, attrs: &HashMap<String, String>) -> Option<Value> {
+        let raw = self.keys.iter().find_map(|key| attrs.get(key))?.trim();
+        let scale = self.scale.unwrap_or(1.0);
+
+        match self.coercion {
+            Coercion::String => Some(Value::String(raw.to_string())),
+            Coercion::Integer => raw
+                .parse::<f64>()
+                .ok()
+                .map(|v| Value::Integer((v * scale).round() as i64)),
+            Coercion::Float => raw.parse::<f64>().ok().map(|v| Value::Float(v * scale)),
+            Coercion::Bool => Some(Value::Bool(matches!(
+                raw.to_ascii_lowercase().as_str(),
+                "true" | "1" | "yes"
+            ))),
+        }
+    }
+}
+
+/// A coerced attribute value
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+/// Rules for one signal (events or metrics)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignalMapping {
+    /// Rules by column name
+    #[serde(default)]
+    fields: HashMap<String, FieldRule>,
+    /// Rules that apply only to one event or metric name, by column name
+    #[serde(default)]
+    overrides: HashMap<String, HashMap<String, FieldRule>>,
+}
+
+impl SignalMapping {
+    fn builtin(fields: &[(&str, &str, Coercion)]) -> Self {
+        Self {
+            fields: fields
+                .iter()
+                .map(|&(field, key, coercion)| {
+                    (field.to_string(), FieldRule::builtin(key, coercion))
+                })
+                .collect(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Layer rules from a mapping file on top of these
+    fn merge(&mut self, file: SignalMapping, signal: &str) -> Result<()> {
+        let known = |field: &str| self.fields.contains_key(field);
+
+        for field in file.fields.keys() {
+            if !known(field) {
+                bail!("Unknown {} field in attribute mapping: {}", signal, field);
+            }
+        }
+        for (name, rules) in &file.overrides {
+            for field in rules.keys() {
+                if !known(field) {
+                    bail!(
+                        "Unknown {} field in attribute mapping override for {}: {}",
+                        signal,
+                        name,
+                        field
+                    );
+                }
+            }
+        }
+
+        self.fields.extend(file.fields);
+        self.overrides.extend(file.overrides);
+        Ok(())
+    }
+
+    /// Every attribute key that can fill `field`, including per-name overrides
+    pub fn keys<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.fields
+            .get(field)
+            .into_iter()
+            .chain(
+                self.overrides
+                    .values()
+                    .filter_map(move |rules| rules.get(field)),
+            )
+            .flat_map(|rule| rule.keys.iter().map(String::as_str))
+    }
+
+    /// Column values for a record called `name` with attributes `attrs`
+    pub fn fields<'a>(&'a self, name: &str, attrs: &'a HashMap<String, String>) -> Fields<'a> {
+        Fields {
+            mapping: self,
+            overrides: self.overrides.get(name),
+            attrs,
+        }
+    }
+}
+
+/// Column lookups for one record
+pub struct Fields<'a> {
+    mapping: &'a SignalMapping,
+    overrides: Option<&'a HashMap<String, FieldRule>>,
+    attrs: &'a HashMap<String, String>,
+}
+
+impl Fields<'_> {
+    fn value(&self, field: &str) -> Option<Value> {
+        self.overrides
+            .and_then(|rules| rules.get(field))
+            .or_else(|| self.mapping.fields.get(field))?
+            .apply(self.attrs)
+    }
+
+    pub fn string(&self, field: &str) -> Option<String> {
+        Some(match self.value(field)? {
+            Value::String(s) => s,
+            Value::Integer(i) => i.to_string(),
+            Value::Float(f) => f.to_string(),
+            Value::Bool(b) => b.to_string(),
+        })
+    }
+
+    pub fn integer(&self, field: &str) -> Option<i64> {
+        match self.value(field)? {
+            Value::String(s) => s.parse().ok(),
+            Value::Integer(i) => Some(i),
+            Value::Float(f) => Some(f.round() as i64),
+            Value::Bool(b) => Some(b as i64),
+        }
+    }
+
+    pub fn float(&self, field: &str) -> Option<f64> {
+        match self.value(field)? {
+            Value::String(s) => s.parse().ok(),
+            Value::Integer(i) => Some(i as f64),
+            Value::Float(f) => Some(f),
+            Value::Bool(b) => Some(if b { 1.0 } else { 0.0 }),
+        }
+    }
+
+    pub fn bool(&self, field: &str) -> Option<bool> {
+        match self.value(field)? {
+            Value::String(s) => Some(s == "true"),
+            Value::Integer(i) => Some(i != 0),
+            Value::Float(f) => Some(f != 0.0),
+            Value::Bool(b) => Some(b),
+        }
+    }
+}
+
+/// Attribute-to-column rules for events and metrics
+#[derive(Debug, Clone)]
+pub struct AttributeMapping {
+    pub events: SignalMapping,
+    pub metrics: SignalMapping,
+}
+
+/// Layout of the mapping file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MappingFile {
+    #[serde(default)]
+    events: SignalMapping,
+    #[serde(default)]
+    metrics: SignalMapping,
+}
+
+impl Default for AttributeMapping {
+    fn default() -> Self {
+        Self {
+            events: SignalMapping::builtin(EVENT_FIELDS),
+            metrics: SignalMapping::builtin(METRIC_FIELDS),
+        }
+    }
+}
+
+impl AttributeMapping {
+    /// Load the built-in rules, layered with `path` if it exists
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut mapping = Self::default();
+
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(mapping),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read attribute mapping {}", path.display())
+                })
+            }
+        };
+
+        let file: MappingFile = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid attribute mapping {}", path.display()))?;
+        mapping.events.merge(file.events, "event")?;
+        mapping.metrics.merge(file.metrics, "metric")?;
+
+        Ok(mapping)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_coercion_and_overrides() {
+        let mut mapping = AttributeMapping::default();
+        let file: MappingFile = serde_json::from_str(
+            r#"{
+                "events": {
+                    "fields": {
+                        "cost_usd": { "keys": ["cost.usd", "cost_usd"], "type": "float" },
+                        "duration_ms": { "keys": ["duration_s"], "type": "integer", "scale": 1000 }
+                    },
+                    "overrides": {
+                        "claude_code.tool_decision": {
+                            "decision_source": { "keys": ["decision.source"] }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        mapping.events.merge(file.events, "event").unwrap();
+
+        let attrs: HashMap<String, String> = [
+            ("cost_usd", "0.5"),
+            ("duration_s", "1.25"),
+            ("success", "true"),
+            ("source", "config"),
+            ("decision.source", "user"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let fields = mapping.events.fields("claude_code.api_request", &attrs);
+        assert_eq!(fields.float("cost_usd"), Some(0.5));
+        assert_eq!(fields.integer("duration_ms"), Some(1250));
+        assert_eq!(fields.bool("success"), Some(true));
+        assert_eq!(fields.string("decision_source").as_deref(), Some("config"));
+
+        let fields = mapping.events.fields("claude_code.tool_decision", &attrs);
+        assert_eq!(fields.string("decision_source").as_deref(), Some("user"));
+
+        // Unrecognised booleans are false
+        let attrs = HashMap::from([("success".to_string(), "failed".to_string())]);
+        let fields = mapping.events.fields("claude_code.api_request", &attrs);
+        assert_eq!(fields.bool("success"), Some(false));
+
+        let file: MappingFile = serde_json::from_str(
+            r#"{ "events": { "fields": { "costs": { "keys": [], "type": "float" } } } }"#,
+        )
+        .unwrap();
+        assert!(mapping.events.merge(file.events, "event").is_err());
+    }
+}
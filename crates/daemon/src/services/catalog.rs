@@ -0,0 +1,81 @@
+//! Attribute catalog collection
+//!
+//! Tallies the attribute keys and value types seen per event / metric name
+//! in one export, so the catalog is updated with one row per combination
+//! rather than one per record.
+
+use std::collections::HashMap;
+
+use opentelemetry_proto::tonic::common::v1::{any_value, KeyValue};
+use shared::NewAttributeCatalogEntry;
+
+/// Signal, name, attribute key, value type and app version
+type CatalogKey = (String, String, String, &'static str, Option<String>);
+
+/// Catalog entries observed in one export
+#[derive(Debug, Default)]
+pub struct CatalogCollector {
+    entries: HashMap<CatalogKey, NewAttributeCatalogEntry>,
+}
+
+impl CatalogCollector {
+    /// Record the attributes of one log record or data point
+    pub fn observe(
+        &mut self,
+        signal: &str,
+        name: &str,
+        app_version: Option<&str>,
+        timestamp: i64,
+        attrs: &[KeyValue],
+    ) {
+        for kv in attrs {
+            let value_type = value_type_name(kv);
+            let key = (
+                signal.to_string(),
+                name.to_string(),
+                kv.key.clone(),
+                value_type,
+                app_version.map(String::from),
+            );
+
+            let entry = self
+                .entries
+                .entry(key)
+                .or_insert_with(|| NewAttributeCatalogEntry {
+                    signal: signal.to_string(),
+                    name: name.to_string(),
+                    attribute_key: kv.key.clone(),
+                    value_type: value_type.to_string(),
+                    app_version: app_version.map(String::from),
+                    first_seen: timestamp,
+                    last_seen: timestamp,
+                    count: 0,
+                });
+            entry.first_seen = entry.first_seen.min(timestamp);
+            entry.last_seen = entry.last_seen.max(timestamp);
+            entry.count += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn into_entries(self) -> Vec<NewAttributeCatalogEntry> {
+        self.entries.into_values().collect()
+    }
+}
+
+/// OTLP value type of an attribute
+fn value_type_name(kv: &KeyValue) -> &'static str {
+    match kv.value.as_ref().and_then(|v| v.value.as_ref()) {
+        Some(any_value::Value::StringValue(_)) => "string",
+        Some(any_value::Value::BoolValue(_)) => "bool",
+        Some(any_value::Value::IntValue(_)) => "int",
+        Some(any_value::Value::DoubleValue(_)) => "double",
+        Some(any_value::Value::BytesValue(_)) => "bytes",
+        Some(any_value::Value::ArrayValue(_)) => "array",
+        Some(any_value::Value::KvlistValue(_)) => "kvlist",
+        None => "empty",
+    }
+}
@@ -0,0 +1,109 @@
+//! Ingestion counters
+//!
+//! In-process totals since the daemon started, exposed on `/metrics` and
+//! `/status`.
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const MINUTE_MS: i64 = 60 * 1000;
+
+/// Counters of one OTLP signal
+#[derive(Debug, Default)]
+pub struct SignalCounters {
+    /// Export requests received
+    pub requests: AtomicU64,
+    /// Records parsed successfully
+    pub accepted: AtomicU64,
+    /// Records that could not be parsed
+    pub rejected: AtomicU64,
+    /// Records accepted per minute
+    pub per_minute: PerMinute,
+    /// When records were last accepted, in Unix milliseconds (0 if never)
+    pub last_accepted_at: AtomicI64,
+}
+
+impl SignalCounters {
+    /// Count one export request
+    pub fn record(&self, accepted: usize, rejected: usize) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.accepted.fetch_add(accepted as u64, Ordering::Relaxed);
+        self.rejected.fetch_add(rejected as u64, Ordering::Relaxed);
+        if accepted > 0 {
+            let now = chrono::Utc::now().timestamp_millis();
+            self.per_minute.add(accepted as u64, now);
+            self.last_accepted_at.store(now, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Ingestion counters shared by the receivers and the queue
+#[derive(Debug, Default)]
+pub struct IngestCounters {
+    pub logs: SignalCounters,
+    pub metrics: SignalCounters,
+    pub traces: SignalCounters,
+    /// Hook notifications received
+    pub notifications: AtomicU64,
+    /// Rows written to the database, including replayed ones
+    pub stored: AtomicU64,
+    /// Rows appended to the spool
+    pub spooled: AtomicU64,
+    /// Spool entries moved to the quarantine file
+    pub quarantined: AtomicU64,
+}
+
+/// Counts of the current and the previous minute
+#[derive(Debug, Default)]
+pub struct PerMinute {
+    /// (minute since the epoch, count in it, count in the minute before)
+    window: Mutex<(i64, u64, u64)>,
+}
+
+impl PerMinute {
+    fn add(&self, count: u64, now: i64) {
+        let minute = now / MINUTE_MS;
+        let mut window = self.window.lock().unwrap();
+        if window.0 != minute {
+            let previous = if window.0 + 1 == minute { window.1 } else { 0 };
+            *window = (minute, 0, previous);
+        }
+        window.1 += count;
+    }
+
+    /// Count of the last complete minute before `now` (Unix milliseconds)
+    pub fn last_minute(&self, now: i64) -> u64 {
+        let minute = now / MINUTE_MS;
+        let (current, count, previous) = *self.window.lock().unwrap();
+        if current == minute {
+            previous
+        } else if current + 1 == minute {
+            count
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_minute_reports_last_complete_minute() {
+        let rate = PerMinute::default();
+        rate.add(5, 10 * MINUTE_MS);
+        rate.add(2, 10 * MINUTE_MS + 59_000);
+        assert_eq!(rate.last_minute(10 * MINUTE_MS + 30_000), 0);
+        assert_eq!(rate.last_minute(11 * MINUTE_MS), 7);
+
+        rate.add(1, 11 * MINUTE_MS + 1);
+        assert_eq!(rate.last_minute(11 * MINUTE_MS + 2), 7);
+        assert_eq!(rate.last_minute(12 * MINUTE_MS), 1);
+
+        // Idle minutes in between count as zero
+        rate.add(3, 20 * MINUTE_MS);
+        assert_eq!(rate.last_minute(20 * MINUTE_MS), 0);
+        assert_eq!(rate.last_minute(22 * MINUTE_MS), 0);
+    }
+}
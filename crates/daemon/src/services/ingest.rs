@@ -0,0 +1,161 @@
+//! Ingestion service
+//!
+//! Parses OTLP export requests, redacts them and queues the results for
+//! storage. Shared by the HTTP handlers and the gRPC receiver so both
+//! transports behave identically.
+
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use super::catalog::CatalogCollector;
+use super::otlp_parser::{parse_logs_to_events, parse_metrics, parse_traces, Parsed};
+use super::queue::Batch;
+use crate::server::AppState;
+
+/// Records accepted and rejected from one export request
+#[derive(Debug, Default)]
+pub struct IngestOutcome {
+    pub accepted: usize,
+    pub rejected: usize,
+    /// Why records were rejected, if any were
+    pub error_message: Option<String>,
+}
+
+impl IngestOutcome {
+    fn from_parsed<T>(parsed: &Parsed<T>, kind: &str) -> Self {
+        Self {
+            accepted: parsed.rows.len(),
+            rejected: parsed.rejected,
+            error_message: parsed
+                .reason
+                .as_ref()
+                .map(|reason| format!("{} {} rejected: {}", parsed.rejected, kind, reason)),
+        }
+    }
+}
+
+/// Accepting a parsed export failed; every record in it was rejected
+#[derive(Debug, Error)]
+#[error("Failed to store {rejected} {kind}: {source}")]
+pub struct IngestError {
+    pub kind: &'static str,
+    pub rejected: usize,
+    #[source]
+    pub source: shared::Error,
+}
+
+/// Parse and queue an OTLP logs export
+pub async fn ingest_logs(
+    state: &AppState,
+    request: &ExportLogsServiceRequest,
+) -> Result<IngestOutcome, IngestError> {
+    let mut parsed = parse_logs_to_events(request, &state.mapping);
+    let outcome = IngestOutcome::from_parsed(&parsed, "log records");
+    state
+        .counters
+        .logs
+        .record(outcome.accepted, outcome.rejected);
+    if let Some(message) = &outcome.error_message {
+        warn!("{}", message);
+    }
+    if parsed.rows.is_empty() {
+        return Ok(outcome);
+    }
+
+    info!("Parsed {} events", outcome.accepted);
+    state.redactor.redact_events(&mut parsed.rows);
+    enqueue(state, Batch::Events(parsed.rows), "events").await?;
+    record_catalog(state, parsed.catalog).await;
+
+    Ok(outcome)
+}
+
+/// Parse and queue an OTLP metrics export
+///
+/// Cumulative Sum points are queued as reported; the queue writer converts
+/// them to deltas as it stores them.
+pub async fn ingest_metrics(
+    state: &AppState,
+    request: &ExportMetricsServiceRequest,
+) -> Result<IngestOutcome, IngestError> {
+    let mut parsed = parse_metrics(request, &state.mapping);
+    let outcome = IngestOutcome::from_parsed(&parsed, "data points");
+    state
+        .counters
+        .metrics
+        .record(outcome.accepted, outcome.rejected);
+    if let Some(message) = &outcome.error_message {
+        warn!("{}", message);
+    }
+    if parsed.rows.is_empty() {
+        return Ok(outcome);
+    }
+
+    info!("Parsed {} metrics", outcome.accepted);
+    state.redactor.redact_metrics(&mut parsed.rows);
+    enqueue(state, Batch::Metrics(parsed.rows), "metrics").await?;
+    record_catalog(state, parsed.catalog).await;
+
+    Ok(outcome)
+}
+
+/// Parse and queue an OTLP traces export
+pub async fn ingest_traces(
+    state: &AppState,
+    request: &ExportTraceServiceRequest,
+) -> Result<IngestOutcome, IngestError> {
+    let mut parsed = parse_traces(request);
+    let outcome = IngestOutcome::from_parsed(&parsed, "spans");
+    state
+        .counters
+        .traces
+        .record(outcome.accepted, outcome.rejected);
+    if let Some(message) = &outcome.error_message {
+        warn!("{}", message);
+    }
+    if parsed.rows.is_empty() {
+        return Ok(outcome);
+    }
+
+    info!("Parsed {} spans", outcome.accepted);
+    state.redactor.redact_spans(&mut parsed.rows);
+    enqueue(state, Batch::Spans(parsed.rows), "spans").await?;
+
+    Ok(outcome)
+}
+
+async fn enqueue(state: &AppState, batch: Batch, kind: &'static str) -> Result<(), IngestError> {
+    let count = batch.len();
+    state
+        .queue
+        .enqueue(batch)
+        .await
+        .map_err(|source| IngestError {
+            kind,
+            rejected: count,
+            source,
+        })?;
+    info!("Queued {} {}", count, kind);
+
+    Ok(())
+}
+
+/// Queue the attribute keys seen in an export
+///
+/// The export itself is already queued, so a failure here only costs catalog
+/// coverage and is not reported to the exporter.
+async fn record_catalog(state: &AppState, catalog: CatalogCollector) {
+    if catalog.is_empty() {
+        return;
+    }
+    if let Err(e) = state
+        .queue
+        .enqueue(Batch::Catalog(catalog.into_entries()))
+        .await
+    {
+        warn!("Failed to queue attribute catalog entries: {}", e);
+    }
+}
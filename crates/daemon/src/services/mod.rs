@@ -1,5 +1,25 @@
 //! Business logic services
 
+mod attribute_mapping;
+mod catalog;
+mod counters;
+mod ingest;
 mod otlp_parser;
+mod prometheus;
+mod queue;
+mod redaction;
+mod retention;
+mod sources;
+mod status;
+mod temporality;
+mod time;
 
-pub use otlp_parser::{parse_logs_to_events, parse_metrics};
+pub use attribute_mapping::AttributeMapping;
+pub use counters::IngestCounters;
+pub use ingest::{ingest_logs, ingest_metrics, ingest_traces};
+pub use prometheus::{render_metrics, CONTENT_TYPE as PROMETHEUS_CONTENT_TYPE};
+pub use queue::IngestQueue;
+pub use redaction::Redactor;
+pub use retention::Retention;
+pub use status::collect_status;
+pub use time::parse_time;
@@ -1,16 +1,61 @@
 //! OTLP data parser
 //!
-//! Converts OTLP metrics and logs into our database entities.
+//! Converts OTLP metrics, logs and traces into our database entities.
 
 use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
 use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
 use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue, KeyValue};
-use shared::{NewEvent, NewMetric};
+use opentelemetry_proto::tonic::metrics::v1::{AggregationTemporality, Metric};
+use opentelemetry_proto::tonic::trace::v1::{span, status};
+use serde_json::json;
+use shared::{MetricBucket, MetricQuantile, NewEvent, NewMetric, NewSpan};
 use uuid::Uuid;
 
+use super::attribute_mapping::{AttributeMapping, SignalMapping};
+use super::catalog::CatalogCollector;
+use super::sources::Source;
+
+/// Rows parsed from an export request, plus the records that were rejected
+#[derive(Debug)]
+pub struct Parsed<T> {
+    pub rows: Vec<T>,
+    /// Number of records that could not be converted
+    pub rejected: usize,
+    /// Why the first rejected record was rejected
+    pub reason: Option<String>,
+    /// Attribute keys seen in the accepted records
+    pub catalog: CatalogCollector,
+}
+
+impl<T> Parsed<T> {
+    fn new() -> Self {
+        Self {
+            rows: Vec::new(),
+            rejected: 0,
+            reason: None,
+            catalog: CatalogCollector::default(),
+        }
+    }
+
+    fn reject(&mut self, reason: &str) {
+        self.rejected += 1;
+        if self.reason.is_none() {
+            self.reason = Some(reason.to_string());
+        }
+    }
+}
+
 /// Parse OTLP metrics request into NewMetric entities
-pub fn parse_metrics(request: &ExportMetricsServiceRequest) -> Vec<NewMetric> {
-    let mut metrics = Vec::new();
+///
+/// Metric names and attributes are mapped onto the common schema for the
+/// exporting agent (see [`Source`]). Number data points without a value are
+/// rejected.
+pub fn parse_metrics(
+    request: &ExportMetricsServiceRequest,
+    mapping: &AttributeMapping,
+) -> Parsed<NewMetric> {
+    let mut metrics = Parsed::new();
 
     for resource_metrics in &request.resource_metrics {
         // Extract resource attributes
@@ -19,135 +64,437 @@ pub fn parse_metrics(request: &ExportMetricsServiceRequest) -> Vec<NewMetric> {
             .as_ref()
             .map(|r| extract_attributes(&r.attributes));
 
-        let resource_json = resource_attrs
+        let resource_json = resource_metrics
+            .resource
             .as_ref()
-            .and_then(|attrs| serde_json::to_string(attrs).ok());
+            .and_then(|r| attributes_json(&r.attributes));
+
+        let source = Source::from_service_name(
+            resource_attrs
+                .as_ref()
+                .and_then(|attrs| attrs.get("service.name"))
+                .map(String::as_str),
+        );
+        let point_attributes = |attrs: &[KeyValue]| {
+            let mut attrs = extract_attributes(attrs);
+            source.normalize_attributes(&mut attrs);
+            attrs
+        };
+        let first_row = metrics.rows.len();
 
         for scope_metrics in &resource_metrics.scope_metrics {
             for metric in &scope_metrics.metrics {
-                let metric_name = &metric.name;
-                let metric_unit = if metric.unit.is_empty() {
-                    None
-                } else {
-                    Some(metric.unit.clone())
-                };
-                let metric_description = if metric.description.is_empty() {
-                    None
-                } else {
-                    Some(metric.description.clone())
-                };
+                let metric_name = source.metric_name(&metric.name);
 
                 // Handle different metric data types
                 if let Some(data) = &metric.data {
                     match data {
                         opentelemetry_proto::tonic::metrics::v1::metric::Data::Sum(sum) => {
                             for data_point in &sum.data_points {
-                                let attrs = extract_attributes(&data_point.attributes);
-                                let value = extract_number_value(data_point);
+                                let attrs = point_attributes(&data_point.attributes);
+                                let Some(value) = extract_number_value(data_point) else {
+                                    metrics.reject("number data point has no value");
+                                    continue;
+                                };
                                 let timestamp = data_point.time_unix_nano as i64 / 1_000_000; // ns to ms
 
-                                metrics.push(create_metric(
-                                    metric_name,
+                                let mut new_metric = create_metric(
+                                    &mapping.metrics,
+                                    &metric_name,
+                                    metric,
                                     timestamp,
                                     value,
                                     &attrs,
                                     resource_json.as_deref(),
-                                    metric_unit.as_deref(),
-                                    metric_description.as_deref(),
+                                );
+                                new_metric.data_type = Some("sum".to_string());
+                                new_metric.attributes = attributes_json(&data_point.attributes);
+                                new_metric.temporality =
+                                    temporality_name(sum.aggregation_temporality);
+                                new_metric.start_timestamp =
+                                    Some(data_point.start_time_unix_nano as i64 / 1_000_000);
+                                new_metric.is_monotonic = Some(sum.is_monotonic);
+                                new_metric.raw_value = Some(value);
+                                new_metric.series_key = Some(series_key(
+                                    &metric_name,
+                                    &attrs,
+                                    resource_attrs.as_ref(),
                                 ));
+                                metrics.catalog.observe(
+                                    "metric",
+                                    &metric_name,
+                                    new_metric.app_version.as_deref(),
+                                    timestamp,
+                                    &data_point.attributes,
+                                );
+                                metrics.rows.push(new_metric);
                             }
                         }
                         opentelemetry_proto::tonic::metrics::v1::metric::Data::Gauge(gauge) => {
                             for data_point in &gauge.data_points {
-                                let attrs = extract_attributes(&data_point.attributes);
-                                let value = extract_number_value(data_point);
+                                let attrs = point_attributes(&data_point.attributes);
+                                let Some(value) = extract_number_value(data_point) else {
+                                    metrics.reject("number data point has no value");
+                                    continue;
+                                };
                                 let timestamp = data_point.time_unix_nano as i64 / 1_000_000;
 
-                                metrics.push(create_metric(
-                                    metric_name,
+                                let mut new_metric = create_metric(
+                                    &mapping.metrics,
+                                    &metric_name,
+                                    metric,
                                     timestamp,
                                     value,
                                     &attrs,
                                     resource_json.as_deref(),
-                                    metric_unit.as_deref(),
-                                    metric_description.as_deref(),
-                                ));
+                                );
+                                new_metric.data_type = Some("gauge".to_string());
+                                new_metric.attributes = attributes_json(&data_point.attributes);
+                                metrics.catalog.observe(
+                                    "metric",
+                                    &metric_name,
+                                    new_metric.app_version.as_deref(),
+                                    timestamp,
+                                    &data_point.attributes,
+                                );
+                                metrics.rows.push(new_metric);
                             }
                         }
                         opentelemetry_proto::tonic::metrics::v1::metric::Data::Histogram(hist) => {
                             for data_point in &hist.data_points {
-                                let attrs = extract_attributes(&data_point.attributes);
+                                let attrs = point_attributes(&data_point.attributes);
+                                let value = data_point.sum.unwrap_or(0.0);
+                                let timestamp = data_point.time_unix_nano as i64 / 1_000_000;
+
+                                let mut new_metric = create_metric(
+                                    &mapping.metrics,
+                                    &metric_name,
+                                    metric,
+                                    timestamp,
+                                    value,
+                                    &attrs,
+                                    resource_json.as_deref(),
+                                );
+                                new_metric.data_type = Some("histogram".to_string());
+                                new_metric.attributes = attributes_json(&data_point.attributes);
+                                new_metric.temporality =
+                                    temporality_name(hist.aggregation_temporality);
+                                new_metric.start_timestamp =
+                                    Some(data_point.start_time_unix_nano as i64 / 1_000_000);
+                                new_metric.count = Some(data_point.count as i64);
+                                new_metric.min = data_point.min;
+                                new_metric.max = data_point.max;
+                                new_metric.series_key = Some(series_key(
+                                    &metric_name,
+                                    &attrs,
+                                    resource_attrs.as_ref(),
+                                ));
+                                new_metric.buckets = explicit_buckets(
+                                    &data_point.explicit_bounds,
+                                    &data_point.bucket_counts,
+                                );
+                                metrics.catalog.observe(
+                                    "metric",
+                                    &metric_name,
+                                    new_metric.app_version.as_deref(),
+                                    timestamp,
+                                    &data_point.attributes,
+                                );
+                                metrics.rows.push(new_metric);
+                            }
+                        }
+                        opentelemetry_proto::tonic::metrics::v1::metric::Data::ExponentialHistogram(
+                            hist,
+                        ) => {
+                            for data_point in &hist.data_points {
+                                let attrs = point_attributes(&data_point.attributes);
                                 let value = data_point.sum.unwrap_or(0.0);
                                 let timestamp = data_point.time_unix_nano as i64 / 1_000_000;
 
-                                metrics.push(create_metric(
-                                    metric_name,
+                                let mut new_metric = create_metric(
+                                    &mapping.metrics,
+                                    &metric_name,
+                                    metric,
                                     timestamp,
                                     value,
                                     &attrs,
                                     resource_json.as_deref(),
-                                    metric_unit.as_deref(),
-                                    metric_description.as_deref(),
+                                );
+                                new_metric.data_type = Some("exponential_histogram".to_string());
+                                new_metric.attributes = attributes_json(&data_point.attributes);
+                                new_metric.temporality =
+                                    temporality_name(hist.aggregation_temporality);
+                                new_metric.start_timestamp =
+                                    Some(data_point.start_time_unix_nano as i64 / 1_000_000);
+                                new_metric.count = Some(data_point.count as i64);
+                                new_metric.min = data_point.min;
+                                new_metric.max = data_point.max;
+                                new_metric.series_key = Some(series_key(
+                                    &metric_name,
+                                    &attrs,
+                                    resource_attrs.as_ref(),
                                 ));
+                                new_metric.buckets = exponential_buckets(data_point);
+                                metrics.catalog.observe(
+                                    "metric",
+                                    &metric_name,
+                                    new_metric.app_version.as_deref(),
+                                    timestamp,
+                                    &data_point.attributes,
+                                );
+                                metrics.rows.push(new_metric);
+                            }
+                        }
+                        opentelemetry_proto::tonic::metrics::v1::metric::Data::Summary(summary) => {
+                            for data_point in &summary.data_points {
+                                let attrs = point_attributes(&data_point.attributes);
+                                let timestamp = data_point.time_unix_nano as i64 / 1_000_000;
+
+                                let mut new_metric = create_metric(
+                                    &mapping.metrics,
+                                    &metric_name,
+                                    metric,
+                                    timestamp,
+                                    data_point.sum,
+                                    &attrs,
+                                    resource_json.as_deref(),
+                                );
+                                new_metric.data_type = Some("summary".to_string());
+                                new_metric.attributes = attributes_json(&data_point.attributes);
+                                new_metric.count = Some(data_point.count as i64);
+                                new_metric.quantiles = data_point
+                                    .quantile_values
+                                    .iter()
+                                    .map(|q| MetricQuantile {
+                                        quantile: q.quantile,
+                                        value: q.value,
+                                    })
+                                    .collect();
+                                // The 0 and 1 quantiles are the observed min and max
+                                new_metric.min = new_metric
+                                    .quantiles
+                                    .iter()
+                                    .find(|q| q.quantile == 0.0)
+                                    .map(|q| q.value);
+                                new_metric.max = new_metric
+                                    .quantiles
+                                    .iter()
+                                    .find(|q| q.quantile == 1.0)
+                                    .map(|q| q.value);
+                                metrics.catalog.observe(
+                                    "metric",
+                                    &metric_name,
+                                    new_metric.app_version.as_deref(),
+                                    timestamp,
+                                    &data_point.attributes,
+                                );
+                                metrics.rows.push(new_metric);
                             }
                         }
-                        _ => {}
                     }
                 }
             }
         }
+
+        for row in &mut metrics.rows[first_row..] {
+            row.source = Some(source.as_str().to_string());
+        }
     }
 
     metrics
 }
 
 /// Parse OTLP logs request into NewEvent entities
-pub fn parse_logs_to_events(request: &ExportLogsServiceRequest) -> Vec<NewEvent> {
-    let mut events = Vec::new();
+///
+/// Event names and attributes are mapped onto the common schema for the
+/// exporting agent (see [`Source`]). Log records with neither an
+/// `event.name` attribute nor a string body are stored as `<source>.unknown`
+/// (e.g. `claude_code.unknown`).
+pub fn parse_logs_to_events(
+    request: &ExportLogsServiceRequest,
+    mapping: &AttributeMapping,
+) -> Parsed<NewEvent> {
+    let mut events = Parsed::new();
 
     for resource_logs in &request.resource_logs {
         let resource_attrs = resource_logs
             .resource
             .as_ref()
-            .map(|r| extract_attributes(&r.attributes));
+            .map(|r| extract_attributes(&r.attributes))
+            .unwrap_or_default();
 
-        let resource_json = resource_attrs
+        let resource_json = resource_logs
+            .resource
             .as_ref()
-            .and_then(|attrs| serde_json::to_string(attrs).ok());
+            .and_then(|r| attributes_json(&r.attributes));
+
+        let source =
+            Source::from_service_name(resource_attrs.get("service.name").map(String::as_str));
 
         for scope_logs in &resource_logs.scope_logs {
             for log_record in &scope_logs.log_records {
-                let attrs = extract_attributes(&log_record.attributes);
+                let mut attrs = extract_attributes(&log_record.attributes);
+                source.normalize_attributes(&mut attrs);
                 let timestamp = log_record.time_unix_nano as i64 / 1_000_000; // ns to ms
 
                 // Extract event name from attributes or body
-                let event_name = attrs
-                    .get("event.name")
-                    .cloned()
+                let event_name = mapping
+                    .events
+                    .fields("", &attrs)
+                    .string("name")
                     .or_else(|| extract_body_string(&log_record.body))
+                    .map(|name| source.event_name(&name, &attrs))
+                    .unwrap_or_else(|| source.unknown_event_name());
+
+                let mut event = create_event(
+                    &mapping.events,
+                    &event_name,
+                    timestamp,
+                    &attrs,
+                    attributes_json(&log_record.attributes),
+                    resource_json.as_deref(),
+                );
+                event.source = Some(source.as_str().to_string());
+                events.catalog.observe(
+                    "log",
+                    &event.name,
+                    event.app_version.as_deref(),
+                    timestamp,
+                    &log_record.attributes,
+                );
+                events.rows.push(event);
+            }
+        }
+    }
+
+    events
+}
+
+/// Parse OTLP traces request into NewSpan entities
+///
+/// Spans without a valid 16-byte trace ID and 8-byte span ID are rejected.
+pub fn parse_traces(request: &ExportTraceServiceRequest) -> Parsed<NewSpan> {
+    let mut spans = Parsed::new();
+
+    for resource_spans in &request.resource_spans {
+        let resource_attrs = resource_spans
+            .resource
+            .as_ref()
+            .map(|r| extract_attributes(&r.attributes))
+            .unwrap_or_default();
+
+        let resource_json = resource_spans
+            .resource
+            .as_ref()
+            .and_then(|r| attributes_json(&r.attributes));
+
+        for scope_spans in &resource_spans.scope_spans {
+            let scope_name = scope_spans
+                .scope
+                .as_ref()
+                .map(|s| s.name.clone())
+                .filter(|name| !name.is_empty());
+
+            for span in &scope_spans.spans {
+                if span.trace_id.len() != 16 || span.span_id.len() != 8 {
+                    spans.reject("span has an invalid trace or span id");
+                    continue;
+                }
+
+                let attrs = extract_attributes(&span.attributes);
+
+                // Spans may carry the session on themselves or on the resource
+                let session_id = attrs
+                    .get("session.id")
+                    .or_else(|| resource_attrs.get("session.id"))
+                    .cloned()
                     .unwrap_or_else(|| "unknown".to_string());
 
-                // Prefix with claude_code if not already
-                let event_name = if event_name.starts_with("claude_code.") {
-                    event_name
-                } else {
-                    format!("claude_code.{}", event_name)
-                };
+                let start_ns = span.start_time_unix_nano;
+                let end_ns = span.end_time_unix_nano.max(start_ns);
+
+                let events: Vec<_> = span
+                    .events
+                    .iter()
+                    .map(|event| {
+                        json!({
+                            "name": event.name,
+                            "timestamp": event.time_unix_nano as i64 / 1_000_000,
+                            "attributes": attributes_value(&event.attributes),
+                        })
+                    })
+                    .collect();
 
-                events.push(create_event(&event_name, timestamp, &attrs, resource_json.as_deref()));
+                let status = span.status.as_ref();
+
+                spans.rows.push(NewSpan {
+                    trace_id: hex_id(&span.trace_id),
+                    span_id: hex_id(&span.span_id),
+                    parent_span_id: Some(hex_id(&span.parent_span_id)).filter(|id| !id.is_empty()),
+                    session_id,
+                    name: span.name.clone(),
+                    kind: span_kind_name(span.kind),
+                    start_time: start_ns as i64 / 1_000_000, // ns to ms
+                    end_time: end_ns as i64 / 1_000_000,
+                    duration_ms: (end_ns - start_ns) as f64 / 1_000_000.0,
+                    status_code: status.and_then(|s| status_code_name(s.code)),
+                    status_message: status.map(|s| s.message.clone()).filter(|m| !m.is_empty()),
+                    attributes: attributes_json(&span.attributes),
+                    events: if events.is_empty() {
+                        None
+                    } else {
+                        serde_json::to_string(&events).ok()
+                    },
+                    scope_name: scope_name.clone(),
+                    resource: resource_json.clone(),
+                });
             }
         }
     }
 
-    events
+    spans
+}
+
+/// Hex-encode an OTLP trace or span ID
+fn hex_id(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Lowercase span kind name, e.g. `SPAN_KIND_CLIENT` -> `client`
+fn span_kind_name(kind: i32) -> Option<String> {
+    match span::SpanKind::try_from(kind).ok()? {
+        span::SpanKind::Unspecified => None,
+        kind => Some(
+            kind.as_str_name()
+                .trim_start_matches("SPAN_KIND_")
+                .to_ascii_lowercase(),
+        ),
+    }
+}
+
+/// Lowercase status code name, e.g. `STATUS_CODE_ERROR` -> `error`
+fn status_code_name(code: i32) -> Option<String> {
+    status::StatusCode::try_from(code).ok().map(|code| {
+        code.as_str_name()
+            .trim_start_matches("STATUS_CODE_")
+            .to_ascii_lowercase()
+    })
 }
 
 /// Extract attributes from KeyValue list into a HashMap
+///
+/// Arrays and key-value lists are kept as their JSON text.
 fn extract_attributes(attrs: &[KeyValue]) -> std::collections::HashMap<String, String> {
     let mut map = std::collections::HashMap::new();
     for kv in attrs {
         if let Some(value) = &kv.value {
-            if let Some(s) = extract_any_value_string(value) {
+            let s = extract_any_value_string(value).or_else(|| {
+                value
+                    .value
+                    .as_ref()
+                    .map(|_| any_value_json(value).to_string())
+            });
+            if let Some(s) = s {
                 map.insert(kv.key.clone(), s);
             }
         }
@@ -155,6 +502,47 @@ fn extract_attributes(attrs: &[KeyValue]) -> std::collections::HashMap<String, S
     map
 }
 
+/// All attributes as a JSON object string, or `None` when there are none
+fn attributes_json(attrs: &[KeyValue]) -> Option<String> {
+    if attrs.is_empty() {
+        return None;
+    }
+    serde_json::to_string(&attributes_value(attrs)).ok()
+}
+
+/// All attributes as a JSON object, keeping each value's type
+fn attributes_value(attrs: &[KeyValue]) -> serde_json::Value {
+    serde_json::Value::Object(
+        attrs
+            .iter()
+            .map(|kv| {
+                let value = kv
+                    .value
+                    .as_ref()
+                    .map(any_value_json)
+                    .unwrap_or(serde_json::Value::Null);
+                (kv.key.clone(), value)
+            })
+            .collect(),
+    )
+}
+
+/// Convert an AnyValue to JSON; bytes are hex-encoded
+fn any_value_json(value: &AnyValue) -> serde_json::Value {
+    match &value.value {
+        Some(any_value::Value::StringValue(s)) => json!(s),
+        Some(any_value::Value::BoolValue(b)) => json!(b),
+        Some(any_value::Value::IntValue(i)) => json!(i),
+        Some(any_value::Value::DoubleValue(d)) => json!(d),
+        Some(any_value::Value::BytesValue(bytes)) => json!(hex_id(bytes)),
+        Some(any_value::Value::ArrayValue(array)) => {
+            serde_json::Value::Array(array.values.iter().map(any_value_json).collect())
+        }
+        Some(any_value::Value::KvlistValue(list)) => attributes_value(&list.values),
+        None => serde_json::Value::Null,
+    }
+}
+
 /// Extract string value from AnyValue
 fn extract_any_value_string(value: &AnyValue) -> Option<String> {
     value.value.as_ref().and_then(|v| match v {
@@ -174,92 +562,455 @@ fn extract_body_string(body: &Option<AnyValue>) -> Option<String> {
 /// Extract number value from a data point
 fn extract_number_value(
     data_point: &opentelemetry_proto::tonic::metrics::v1::NumberDataPoint,
-) -> f64 {
+) -> Option<f64> {
     match &data_point.value {
-        Some(opentelemetry_proto::tonic::metrics::v1::number_data_point::Value::AsDouble(d)) => *d,
+        Some(opentelemetry_proto::tonic::metrics::v1::number_data_point::Value::AsDouble(d)) => {
+            Some(*d)
+        }
         Some(opentelemetry_proto::tonic::metrics::v1::number_data_point::Value::AsInt(i)) => {
-            *i as f64
+            Some(*i as f64)
+        }
+        None => None,
+    }
+}
+
+/// Lowercase temporality name, e.g. `AGGREGATION_TEMPORALITY_DELTA` -> `delta`
+fn temporality_name(temporality: i32) -> Option<String> {
+    match AggregationTemporality::try_from(temporality).ok()? {
+        AggregationTemporality::Unspecified => None,
+        temporality => Some(
+            temporality
+                .as_str_name()
+                .trim_start_matches("AGGREGATION_TEMPORALITY_")
+                .to_ascii_lowercase(),
+        ),
+    }
+}
+
+/// Stable identity of a metric series
+///
+/// Attributes and resource attributes are sorted so the key does not depend
+/// on the order the exporter sent them in.
+fn series_key(
+    name: &str,
+    attrs: &std::collections::HashMap<String, String>,
+    resource: Option<&std::collections::HashMap<String, String>>,
+) -> String {
+    let attrs: std::collections::BTreeMap<_, _> = attrs.iter().collect();
+    let resource: std::collections::BTreeMap<_, _> = resource.into_iter().flatten().collect();
+    let identity = json!([name, attrs, resource]).to_string();
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, identity.as_bytes()).to_string()
+}
+
+/// Convert explicit histogram bounds and counts into buckets
+///
+/// There is one more count than bounds: bucket `i` covers
+/// `(bounds[i - 1], bounds[i]]`, with the first and last buckets open-ended.
+/// Empty buckets are skipped.
+fn explicit_buckets(bounds: &[f64], counts: &[u64]) -> Vec<MetricBucket> {
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(i, &count)| MetricBucket {
+            lower_bound: i.checked_sub(1).and_then(|j| bounds.get(j).copied()),
+            upper_bound: bounds.get(i).copied(),
+            count: count as i64,
+        })
+        .collect()
+}
+
+/// Convert an exponential histogram data point into buckets with explicit bounds
+///
+/// With `base = 2^(2^-scale)`, positive bucket `index` covers
+/// `(base^index, base^(index + 1)]` and negative buckets mirror it below zero.
+/// Buckets are returned in ascending value order; empty ones are skipped.
+fn exponential_buckets(
+    data_point: &opentelemetry_proto::tonic::metrics::v1::ExponentialHistogramDataPoint,
+) -> Vec<MetricBucket> {
+    let base = 2f64.powf(2f64.powi(-data_point.scale));
+    let mut buckets = Vec::new();
+
+    if let Some(negative) = &data_point.negative {
+        for (i, &count) in negative.bucket_counts.iter().enumerate().rev() {
+            if count == 0 {
+                continue;
+            }
+            let index = negative.offset + i as i32;
+            buckets.push(MetricBucket {
+                lower_bound: Some(-base.powi(index + 1)),
+                upper_bound: Some(-base.powi(index)),
+                count: count as i64,
+            });
+        }
+    }
+
+    if data_point.zero_count > 0 {
+        buckets.push(MetricBucket {
+            lower_bound: Some(-data_point.zero_threshold),
+            upper_bound: Some(data_point.zero_threshold),
+            count: data_point.zero_count as i64,
+        });
+    }
+
+    if let Some(positive) = &data_point.positive {
+        for (i, &count) in positive.bucket_counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let index = positive.offset + i as i32;
+            buckets.push(MetricBucket {
+                lower_bound: Some(base.powi(index)),
+                upper_bound: Some(base.powi(index + 1)),
+                count: count as i64,
+            });
         }
-        None => 0.0,
     }
+
+    buckets
+}
+
+/// Deterministic ID for an event or metric data point
+///
+/// Derived from the session, name, timestamp, sorted attributes (which
+/// include `event.sequence` for events) and resource, so the same record
+/// re-sent by an exporter retry maps to the same ID while identical records
+/// from different processes or hosts stay apart.
+fn content_id(
+    kind: &str,
+    session_id: &str,
+    name: &str,
+    timestamp: i64,
+    attrs: &std::collections::HashMap<String, String>,
+    resource: Option<&str>,
+) -> String {
+    let attrs: std::collections::BTreeMap<_, _> = attrs.iter().collect();
+    let identity = json!([kind, session_id, name, timestamp, attrs, resource]).to_string();
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, identity.as_bytes()).to_string()
 }
 
 /// Create a NewMetric from parsed data
 fn create_metric(
+    mapping: &SignalMapping,
     name: &str,
+    metric: &Metric,
     timestamp: i64,
     value: f64,
     attrs: &std::collections::HashMap<String, String>,
     resource: Option<&str>,
-    unit: Option<&str>,
-    description: Option<&str>,
 ) -> NewMetric {
+    let fields = mapping.fields(name, attrs);
+    let session_id = fields
+        .string("session_id")
+        .unwrap_or_else(|| "unknown".to_string());
+
     NewMetric {
-        id: Uuid::new_v4().to_string(),
-        session_id: attrs
-            .get("session.id")
-            .cloned()
-            .unwrap_or_else(|| "unknown".to_string()),
+        id: content_id("metric", &session_id, name, timestamp, attrs, resource),
+        session_id,
         name: name.to_string(),
         timestamp,
         value,
-        metric_type: attrs.get("type").cloned(),
-        model: attrs.get("model").cloned(),
-        tool: attrs.get("tool").cloned(),
-        decision: attrs.get("decision").cloned(),
-        language: attrs.get("language").cloned(),
-        account_uuid: attrs.get("user.account_uuid").cloned(),
-        organization_id: attrs.get("organization.id").cloned(),
-        terminal_type: attrs.get("terminal.type").cloned(),
-        app_version: attrs.get("app.version").cloned(),
+        metric_type: fields.string("metric_type"),
+        model: fields.string("model"),
+        tool: fields.string("tool"),
+        decision: fields.string("decision"),
+        language: fields.string("language"),
+        account_uuid: fields.string("account_uuid"),
+        organization_id: fields.string("organization_id"),
+        terminal_type: fields.string("terminal_type"),
+        app_version: fields.string("app_version"),
         resource: resource.map(String::from),
-        user_id: attrs.get("user.id").cloned(),
-        user_email: attrs.get("user.email").cloned(),
-        unit: unit.map(String::from),
-        description: description.map(String::from),
+        user_id: fields.string("user_id"),
+        user_email: fields.string("user_email"),
+        unit: Some(metric.unit.clone()).filter(|u| !u.is_empty()),
+        description: Some(metric.description.clone()).filter(|d| !d.is_empty()),
+        data_type: None,
+        count: None,
+        min: None,
+        max: None,
+        temporality: None,
+        start_timestamp: None,
+        is_monotonic: None,
+        raw_value: None,
+        series_key: None,
+        attributes: None,
+        source: None,
+        buckets: Vec::new(),
+        quantiles: Vec::new(),
     }
 }
 
 /// Create a NewEvent from parsed data
 fn create_event(
+    mapping: &SignalMapping,
     name: &str,
     timestamp: i64,
     attrs: &std::collections::HashMap<String, String>,
+    attributes: Option<String>,
     resource: Option<&str>,
 ) -> NewEvent {
+    let fields = mapping.fields(name, attrs);
+    let session_id = fields
+        .string("session_id")
+        .unwrap_or_else(|| "unknown".to_string());
+
     NewEvent {
-        id: Uuid::new_v4().to_string(),
-        session_id: attrs
-            .get("session.id")
-            .cloned()
-            .unwrap_or_else(|| "unknown".to_string()),
+        id: content_id("event", &session_id, name, timestamp, attrs, resource),
+        session_id,
         name: name.to_string(),
         timestamp,
-        duration_ms: attrs.get("duration_ms").and_then(|s| s.parse().ok()),
-        success: attrs.get("success").map(|s| s == "true"),
-        error: attrs.get("error").cloned(),
-        model: attrs.get("model").cloned(),
-        cost_usd: attrs.get("cost_usd").and_then(|s| s.parse().ok()),
-        input_tokens: attrs.get("input_tokens").and_then(|s| s.parse().ok()),
-        output_tokens: attrs.get("output_tokens").and_then(|s| s.parse().ok()),
-        cache_read_tokens: attrs.get("cache_read_tokens").and_then(|s| s.parse().ok()),
-        cache_creation_tokens: attrs.get("cache_creation_tokens").and_then(|s| s.parse().ok()),
-        status_code: attrs.get("status_code").and_then(|s| s.parse().ok()),
-        attempt: attrs.get("attempt").and_then(|s| s.parse().ok()),
-        tool_name: attrs.get("tool_name").cloned(),
-        tool_decision: attrs.get("decision").cloned(),
-        decision_source: attrs.get("source").cloned(),
-        tool_parameters: attrs.get("tool_parameters").cloned(),
-        prompt_length: attrs.get("prompt_length").and_then(|s| s.parse().ok()),
-        prompt: attrs.get("prompt").cloned(),
-        account_uuid: attrs.get("user.account_uuid").cloned(),
-        organization_id: attrs.get("organization.id").cloned(),
-        terminal_type: attrs.get("terminal.type").cloned(),
-        app_version: attrs.get("app.version").cloned(),
+        duration_ms: fields.integer("duration_ms"),
+        success: fields.bool("success"),
+        error: fields.string("error"),
+        model: fields.string("model"),
+        cost_usd: fields.float("cost_usd"),
+        input_tokens: fields.integer("input_tokens"),
+        output_tokens: fields.integer("output_tokens"),
+        cache_read_tokens: fields.integer("cache_read_tokens"),
+        cache_creation_tokens: fields.integer("cache_creation_tokens"),
+        status_code: fields
+            .integer("status_code")
+            .and_then(|v| v.try_into().ok()),
+        attempt: fields.integer("attempt").and_then(|v| v.try_into().ok()),
+        tool_name: fields.string("tool_name"),
+        tool_decision: fields.string("tool_decision"),
+        decision_source: fields.string("decision_source"),
+        tool_parameters: fields.string("tool_parameters"),
+        prompt_length: fields.integer("prompt_length"),
+        prompt: fields.string("prompt"),
+        account_uuid: fields.string("account_uuid"),
+        organization_id: fields.string("organization_id"),
+        terminal_type: fields.string("terminal_type"),
+        app_version: fields.string("app_version"),
         resource: resource.map(String::from),
-        user_id: attrs.get("user.id").cloned(),
-        user_email: attrs.get("user.email").cloned(),
-        event_sequence: attrs.get("event.sequence").and_then(|s| s.parse().ok()),
-        tool_result_size_bytes: attrs.get("tool_result_size_bytes").and_then(|s| s.parse().ok()),
+        user_id: fields.string("user_id"),
+        user_email: fields.string("user_email"),
+        event_sequence: fields.integer("event_sequence"),
+        tool_result_size_bytes: fields.integer("tool_result_size_bytes"),
+        attributes,
+        source: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_proto::tonic::common::v1::ArrayValue;
+    use opentelemetry_proto::tonic::logs::v1::{LogRecord, ResourceLogs, ScopeLogs};
+    use opentelemetry_proto::tonic::metrics::v1::{
+        exponential_histogram_data_point, ExponentialHistogramDataPoint,
+    };
+    use opentelemetry_proto::tonic::resource::v1::Resource;
+    use opentelemetry_proto::tonic::trace::v1::{ResourceSpans, ScopeSpans, Span, Status};
+
+    #[test]
+    fn test_parse_traces_keeps_tree_links() {
+        let request = ExportTraceServiceRequest {
+            resource_spans: vec![ResourceSpans {
+                scope_spans: vec![ScopeSpans {
+                    spans: vec![Span {
+                        trace_id: vec![0xab; 16],
+                        span_id: vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+                        parent_span_id: vec![],
+                        name: "tool_call".to_string(),
+                        kind: span::SpanKind::Client as i32,
+                        start_time_unix_nano: 1_700_000_000_000_000_000,
+                        end_time_unix_nano: 1_700_000_000_001_500_000,
+                        attributes: vec![KeyValue {
+                            key: "session.id".to_string(),
+                            value: Some(AnyValue {
+                                value: Some(any_value::Value::StringValue("s1".to_string())),
+                            }),
+                        }],
+                        status: Some(Status {
+                            code: status::StatusCode::Error as i32,
+                            message: "boom".to_string(),
+                        }),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+
+        let spans = parse_traces(&request);
+        assert_eq!(spans.rejected, 0);
+        assert_eq!(spans.rows.len(), 1);
+        let span = &spans.rows[0];
+        assert_eq!(span.trace_id, "ab".repeat(16));
+        assert_eq!(span.span_id, "0102030405060708");
+        assert_eq!(span.parent_span_id, None);
+        assert_eq!(span.session_id, "s1");
+        assert_eq!(span.kind.as_deref(), Some("client"));
+        assert_eq!(span.status_code.as_deref(), Some("error"));
+        assert_eq!(span.start_time, 1_700_000_000_000);
+        assert!((span.duration_ms - 1.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let buckets = explicit_buckets(&[10.0, 20.0], &[3, 0, 2]);
+        assert_eq!(
+            buckets,
+            vec![
+                MetricBucket {
+                    lower_bound: None,
+                    upper_bound: Some(10.0),
+                    count: 3,
+                },
+                MetricBucket {
+                    lower_bound: Some(20.0),
+                    upper_bound: None,
+                    count: 2,
+                },
+            ]
+        );
+
+        // scale 0 => base 2, so offset 1 starts at (2, 4]
+        let data_point = ExponentialHistogramDataPoint {
+            scale: 0,
+            zero_count: 1,
+            positive: Some(exponential_histogram_data_point::Buckets {
+                offset: 1,
+                bucket_counts: vec![4, 5],
+            }),
+            ..Default::default()
+        };
+        let buckets = exponential_buckets(&data_point);
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0].count, 1);
+        assert_eq!(
+            (buckets[1].lower_bound, buckets[1].upper_bound),
+            (Some(2.0), Some(4.0))
+        );
+        assert_eq!(
+            (buckets[2].lower_bound, buckets[2].upper_bound),
+            (Some(4.0), Some(8.0))
+        );
+    }
+
+    #[test]
+    fn test_attributes_keep_structured_values() {
+        let string = |s: &str| AnyValue {
+            value: Some(any_value::Value::StringValue(s.to_string())),
+        };
+        let attrs = vec![
+            KeyValue {
+                key: "tool.args".to_string(),
+                value: Some(AnyValue {
+                    value: Some(any_value::Value::ArrayValue(ArrayValue {
+                        values: vec![string("-la"), string("/tmp")],
+                    })),
+                }),
+            },
+            KeyValue {
+                key: "retries".to_string(),
+                value: Some(AnyValue {
+                    value: Some(any_value::Value::IntValue(2)),
+                }),
+            },
+        ];
+
+        let json: serde_json::Value =
+            serde_json::from_str(&attributes_json(&attrs).unwrap()).unwrap();
+        assert_eq!(json, json!({ "tool.args": ["-la", "/tmp"], "retries": 2 }));
+        assert_eq!(attributes_json(&[]), None);
+        assert_eq!(
+            extract_attributes(&attrs)
+                .get("tool.args")
+                .map(String::as_str),
+            Some(r#"["-la","/tmp"]"#)
+        );
+    }
+
+    #[test]
+    fn test_content_id_is_deterministic_and_distinct() {
+        let attrs = |pairs: &[(&str, &str)]| -> std::collections::HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let a = attrs(&[("event.sequence", "1"), ("model", "opus")]);
+        let host_a = Some(r#"{"host.name":"a"}"#);
+        let id = content_id("event", "s1", "claude_code.api_request", 1000, &a, host_a);
+
+        // Retries map to the same ID, whatever order the attributes come in
+        let reordered = attrs(&[("model", "opus"), ("event.sequence", "1")]);
+        assert_eq!(
+            content_id(
+                "event",
+                "s1",
+                "claude_code.api_request",
+                1000,
+                &reordered,
+                host_a
+            ),
+            id
+        );
+
+        // Any difference in identity gives a different ID
+        let others = [
+            content_id("metric", "s1", "claude_code.api_request", 1000, &a, host_a),
+            content_id("event", "s2", "claude_code.api_request", 1000, &a, host_a),
+            content_id("event", "s1", "claude_code.api_error", 1000, &a, host_a),
+            content_id("event", "s1", "claude_code.api_request", 1001, &a, host_a),
+            content_id(
+                "event",
+                "s1",
+                "claude_code.api_request",
+                1000,
+                &attrs(&[("event.sequence", "2"), ("model", "opus")]),
+                host_a,
+            ),
+            content_id(
+                "event",
+                "s1",
+                "claude_code.api_request",
+                1000,
+                &a,
+                Some(r#"{"host.name":"b"}"#),
+            ),
+            content_id("event", "s1", "claude_code.api_request", 1000, &a, None),
+        ];
+        for other in &others {
+            assert_ne!(other, &id);
+        }
+    }
+
+    #[test]
+    fn test_unnamed_log_record_is_stored_as_unknown() {
+        let unnamed = |service_name: Option<&str>| ExportLogsServiceRequest {
+            resource_logs: vec![ResourceLogs {
+                resource: service_name.map(|name| Resource {
+                    attributes: vec![KeyValue {
+                        key: "service.name".to_string(),
+                        value: Some(AnyValue {
+                            value: Some(any_value::Value::StringValue(name.to_string())),
+                        }),
+                    }],
+                    ..Default::default()
+                }),
+                scope_logs: vec![ScopeLogs {
+                    log_records: vec![LogRecord {
+                        time_unix_nano: 1_700_000_000_000_000_000,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+
+        let events = parse_logs_to_events(&unnamed(None), &AttributeMapping::default());
+        assert_eq!(events.rejected, 0);
+        assert_eq!(events.rows.len(), 1);
+        assert_eq!(events.rows[0].name, "claude_code.unknown");
+
+        // The fallback names the agent that sent the record
+        let events =
+            parse_logs_to_events(&unnamed(Some("codex_cli_rs")), &AttributeMapping::default());
+        assert_eq!(events.rows[0].name, "codex.unknown");
     }
 }
@@ -0,0 +1,254 @@
+//! Prometheus exposition
+//!
+//! Renders usage totals from the database and the ingestion counters in the
+//! Prometheus text format. Usage totals come from the rollups and the
+//! sessions table, so a scrape costs a few small aggregate queries and
+//! pruning raw telemetry does not reset the counters.
+
+use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use shared::{RollupRepository, SessionRepository};
+use sqlx::SqlitePool;
+
+use super::counters::IngestCounters;
+
+/// Content type of the text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+const API_REQUEST: &str = "claude_code.api_request";
+const API_ERROR: &str = "claude_code.api_error";
+
+/// Render all metrics
+pub async fn render_metrics(db: &SqlitePool, counters: &IngestCounters) -> shared::Result<String> {
+    let mut out = Exposition::default();
+    render_usage(&mut out, db).await?;
+    render_ingestion(&mut out, counters);
+    Ok(out.text)
+}
+
+async fn render_usage(out: &mut Exposition, db: &SqlitePool) -> shared::Result<()> {
+    let series = RollupRepository::sum_events_by_series(db, API_REQUEST).await?;
+    let labels = |s: &shared::SeriesTotals| {
+        [
+            ("model", s.model.clone().unwrap_or_default()),
+            ("source", s.source.clone().unwrap_or_default()),
+        ]
+    };
+
+    out.family(
+        "lumo_api_requests_total",
+        "counter",
+        "API requests by model and telemetry source",
+    );
+    for s in &series {
+        out.sample("lumo_api_requests_total", &labels(s), s.event_count);
+    }
+
+    out.family(
+        "lumo_cost_usd_total",
+        "counter",
+        "Cost of API requests in US dollars",
+    );
+    for s in &series {
+        out.sample("lumo_cost_usd_total", &labels(s), s.cost_usd);
+    }
+
+    out.family(
+        "lumo_tokens_total",
+        "counter",
+        "Tokens by model, source and type",
+    );
+    for s in &series {
+        let [model, source] = labels(s);
+        for (kind, count) in [
+            ("input", s.input_tokens),
+            ("output", s.output_tokens),
+            ("cache_read", s.cache_read_tokens),
+            ("cache_creation", s.cache_creation_tokens),
+        ] {
+            let labels = [model.clone(), source.clone(), ("type", kind.to_string())];
+            out.sample("lumo_tokens_total", &labels, count);
+        }
+    }
+
+    out.family(
+        "lumo_api_errors_total",
+        "counter",
+        "API errors by HTTP status code",
+    );
+    let errors = RollupRepository::count_events_by_status_code(db, API_ERROR).await?;
+    for (status_code, count) in errors {
+        let status_code = status_code.map(|c| c.to_string()).unwrap_or_default();
+        out.sample(
+            "lumo_api_errors_total",
+            &[("status_code", status_code)],
+            count,
+        );
+    }
+
+    let lines =
+        RollupRepository::sum_metrics(db, "claude_code.lines_of_code.count", 0, i64::MAX, None)
+            .await?;
+    out.family(
+        "lumo_lines_of_code_total",
+        "counter",
+        "Lines of code changed by type",
+    );
+    for t in &lines {
+        let kind = t.metric_type.clone().unwrap_or_default();
+        out.sample("lumo_lines_of_code_total", &[("type", kind)], t.value);
+    }
+
+    for (name, metric, help) in [
+        (
+            "lumo_commits_total",
+            "claude_code.commit.count",
+            "Git commits created",
+        ),
+        (
+            "lumo_pull_requests_total",
+            "claude_code.pull_request.count",
+            "Pull requests created",
+        ),
+    ] {
+        let totals = RollupRepository::sum_metrics(db, metric, 0, i64::MAX, None).await?;
+        out.family(name, "counter", help);
+        let total = totals.iter().fold(0.0, |sum, t| sum + t.value);
+        out.sample(name, &[], total);
+    }
+
+    out.family("lumo_sessions", "gauge", "Sessions by status");
+    for (status, count) in SessionRepository::count_by_status(db).await? {
+        out.sample("lumo_sessions", &[("status", status)], count);
+    }
+
+    Ok(())
+}
+
+fn render_ingestion(out: &mut Exposition, counters: &IngestCounters) {
+    let signals = [
+        ("logs", &counters.logs),
+        ("metrics", &counters.metrics),
+        ("traces", &counters.traces),
+    ];
+
+    out.family(
+        "lumo_ingest_requests_total",
+        "counter",
+        "OTLP export requests received since the daemon started",
+    );
+    for (signal, c) in signals {
+        out.sample(
+            "lumo_ingest_requests_total",
+            &[("signal", signal.to_string())],
+            load(&c.requests),
+        );
+    }
+
+    out.family(
+        "lumo_ingest_records_total",
+        "counter",
+        "OTLP records parsed since the daemon started, by outcome",
+    );
+    for (signal, c) in signals {
+        for (outcome, counter) in [("accepted", &c.accepted), ("rejected", &c.rejected)] {
+            let labels = [
+                ("signal", signal.to_string()),
+                ("outcome", outcome.to_string()),
+            ];
+            out.sample("lumo_ingest_records_total", &labels, load(counter));
+        }
+    }
+
+    for (name, counter, help) in [
+        (
+            "lumo_ingest_rows_stored_total",
+            &counters.stored,
+            "Rows written to the database since the daemon started",
+        ),
+        (
+            "lumo_ingest_rows_spooled_total",
+            &counters.spooled,
+            "Rows spooled to disk since the daemon started",
+        ),
+        (
+            "lumo_ingest_spool_quarantined_total",
+            &counters.quarantined,
+            "Spool entries moved to the quarantine file since the daemon started",
+        ),
+        (
+            "lumo_notifications_received_total",
+            &counters.notifications,
+            "Hook notifications received since the daemon started",
+        ),
+    ] {
+        out.family(name, "counter", help);
+        out.sample(name, &[], load(counter));
+    }
+}
+
+fn load(counter: &AtomicU64) -> u64 {
+    counter.load(Ordering::Relaxed)
+}
+
+/// Text format output being built
+#[derive(Debug, Default)]
+struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {} {}", name, help);
+        let _ = writeln!(self.text, "# TYPE {} {}", name, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, String)], value: impl Display) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<_> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape(value)))
+                .collect();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {}", value);
+    }
+}
+
+/// Escape a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exposition_format() {
+        let mut out = Exposition::default();
+        out.family("lumo_cost_usd_total", "counter", "Cost");
+        out.sample(
+            "lumo_cost_usd_total",
+            &[
+                ("model", "opus".to_string()),
+                ("source", "a\"b\\c\nd".to_string()),
+            ],
+            1.5,
+        );
+        out.sample("lumo_commits_total", &[], 3);
+
+        assert_eq!(
+            out.text,
+            "# HELP lumo_cost_usd_total Cost\n\
+             # TYPE lumo_cost_usd_total counter\n\
+             lumo_cost_usd_total{model=\"opus\",source=\"a\\\"b\\\\c\\nd\"} 1.5\n\
+             lumo_commits_total 3\n"
+        );
+    }
+}
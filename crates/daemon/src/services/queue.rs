@@ -0,0 +1,628 @@
+//! Ingestion queue
+//!
+//! Sits between the OTLP receivers and the repositories so exporters are
+//! acknowledged as soon as their data is parsed. Batches are written to SQLite
+//! by a single background task. When the queue is full, or a write fails
+//! (e.g. the desktop app holds the write lock), batches are appended to a
+//! JSON Lines spool file instead and replayed later, including on restart.
+//!
+//! Replays are safe to repeat: every row has a content-derived ID and is
+//! inserted with `INSERT OR IGNORE`. Attribute catalog entries are merged
+//! instead, so a repeated replay only inflates their counts.
+//!
+//! Spool lines that cannot be read, and batches that keep failing for reasons
+//! other than a busy or unavailable database, are moved to a quarantine file
+//! next to the spool (`spool.rejected.jsonl`) so they are kept for inspection
+//! without being retried forever.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use shared::{
+    AttributeCatalogRepository, EventRepository, NewAttributeCatalogEntry, NewEvent, NewMetric,
+    NewSpan, SpanRepository,
+};
+use sqlx::SqlitePool;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use super::counters::IngestCounters;
+use super::temporality::SeriesTracker;
+
+/// How often the drain task retries a non-empty spool
+const SPOOL_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Failed attempts after which a spooled batch is quarantined
+///
+/// Only failures that are not transient (see [`is_transient`]) count.
+const MAX_SPOOL_ATTEMPTS: u32 = 5;
+
+/// A parsed export waiting to be stored
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "rows", rename_all = "snake_case")]
+pub enum Batch {
+    Events(Vec<NewEvent>),
+    Metrics(Vec<NewMetric>),
+    Spans(Vec<NewSpan>),
+    Catalog(Vec<NewAttributeCatalogEntry>),
+}
+
+impl Batch {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Events(rows) => rows.len(),
+            Self::Metrics(rows) => rows.len(),
+            Self::Spans(rows) => rows.len(),
+            Self::Catalog(rows) => rows.len(),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Events(_) => "events",
+            Self::Metrics(_) => "metrics",
+            Self::Spans(_) => "spans",
+            Self::Catalog(_) => "catalog entries",
+        }
+    }
+
+    /// Store the batch; cumulative metric points are converted to deltas
+    /// against `series` as they are stored
+    async fn store(&self, db: &SqlitePool, series: &SeriesTracker) -> shared::Result<()> {
+        match self {
+            Self::Events(rows) => EventRepository::insert_batch(db, rows).await,
+            Self::Metrics(rows) => series.store(db, rows).await,
+            Self::Spans(rows) => SpanRepository::insert_batch(db, rows).await,
+            Self::Catalog(rows) => AttributeCatalogRepository::upsert_batch(db, rows).await,
+        }
+    }
+}
+
+/// One line of the spool
+#[derive(Debug, Serialize, Deserialize)]
+struct SpoolEntry<B> {
+    /// Failed attempts to store the batch; absent in older spool files
+    #[serde(default)]
+    attempts: u32,
+    #[serde(flatten)]
+    batch: B,
+}
+
+/// Handle for submitting batches to the background writer
+#[derive(Clone)]
+pub struct IngestQueue {
+    tx: mpsc::Sender<Batch>,
+    spool: Arc<Spool>,
+}
+
+impl IngestQueue {
+    /// Start the background writer
+    ///
+    /// The writer replays any existing spool first. Once `shutdown` resolves
+    /// it stops accepting batches, stores what is still queued and exits;
+    /// batches submitted after that go to the spool. Stored and spooled rows
+    /// are added to `counters`.
+    pub fn start<F>(
+        db: SqlitePool,
+        capacity: usize,
+        spool_path: PathBuf,
+        counters: Arc<IngestCounters>,
+        shutdown: F,
+    ) -> (Self, JoinHandle<()>)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(capacity);
+        let spool = Arc::new(Spool::new(spool_path, counters));
+        let task = tokio::spawn(drain(rx, db, spool.clone(), shutdown));
+
+        (Self { tx, spool }, task)
+    }
+
+    /// Batches waiting for the background writer
+    pub fn depth(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
+    }
+
+    /// Batches that fit in memory before exports are spooled
+    pub fn capacity(&self) -> usize {
+        self.tx.max_capacity()
+    }
+
+    /// Size of the spool in bytes, including a replay in progress
+    ///
+    /// The quarantine file is not included.
+    pub fn spool_size(&self) -> u64 {
+        [self.spool.path.clone(), self.spool.replay_path()]
+            .iter()
+            .filter_map(|path| std::fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    /// Queue a batch for storage, spilling to the spool if the queue is full
+    pub async fn enqueue(&self, batch: Batch) -> shared::Result<()> {
+        match self.tx.try_send(batch) {
+            Ok(()) => Ok(()),
+            Err(mpsc::error::TrySendError::Full(batch))
+            | Err(mpsc::error::TrySendError::Closed(batch)) => {
+                warn!(
+                    "Ingestion queue unavailable, spooling {} {}",
+                    batch.len(),
+                    batch.kind()
+                );
+                self.spool.append(&batch, 0).await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Background writer loop
+async fn drain<F>(mut rx: mpsc::Receiver<Batch>, db: SqlitePool, spool: Arc<Spool>, shutdown: F)
+where
+    F: Future<Output = ()> + Send,
+{
+    let series = SeriesTracker::new();
+    spool.replay(&db, &series).await;
+
+    let mut retry = tokio::time::interval(SPOOL_RETRY_INTERVAL);
+    retry.tick().await; // the first tick completes immediately
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            batch = rx.recv() => match batch {
+                Some(batch) => store_or_spool(&db, &series, &spool, batch).await,
+                None => break,
+            },
+            _ = retry.tick() => spool.replay(&db, &series).await,
+            _ = &mut shutdown => {
+                rx.close();
+                while let Some(batch) = rx.recv().await {
+                    store_or_spool(&db, &series, &spool, batch).await;
+                }
+                break;
+            }
+        }
+    }
+
+    info!("Ingestion queue drained");
+}
+
+/// Store a batch, falling back to the spool if the write fails
+async fn store_or_spool(db: &SqlitePool, series: &SeriesTracker, spool: &Spool, batch: Batch) {
+    match batch.store(db, series).await {
+        Ok(()) => {
+            spool
+                .counters
+                .stored
+                .fetch_add(batch.len() as u64, Ordering::Relaxed);
+            debug!("Stored {} {}", batch.len(), batch.kind());
+        }
+        Err(e) => {
+            warn!(
+                "Failed to store {} {}, spooling: {}",
+                batch.len(),
+                batch.kind(),
+                e
+            );
+            let attempts = if is_transient(&e) { 0 } else { 1 };
+            if let Err(e) = spool.append(&batch, attempts).await {
+                error!("Failed to spool {} {}: {}", batch.len(), batch.kind(), e);
+            }
+        }
+    }
+}
+
+/// Whether a store failure says nothing about the batch itself
+///
+/// A busy, locked or closed database and I/O errors are expected to clear up
+/// (e.g. once the desktop app releases its write lock), so they do not count
+/// towards [`MAX_SPOOL_ATTEMPTS`].
+fn is_transient(error: &shared::Error) -> bool {
+    match error {
+        shared::Error::Io(_) => true,
+        shared::Error::Database(e) => match e {
+            sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => true,
+            // SQLITE_BUSY and SQLITE_LOCKED, including their extended codes
+            sqlx::Error::Database(e) => e
+                .code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .is_some_and(|code| matches!(code & 0xff, 5 | 6)),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Append-only JSON Lines file of batches that could not be stored yet
+struct Spool {
+    path: PathBuf,
+    /// Serializes appends, quarantines and the rename that starts a replay
+    lock: Mutex<()>,
+    counters: Arc<IngestCounters>,
+}
+
+impl Spool {
+    fn new(path: PathBuf, counters: Arc<IngestCounters>) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+            counters,
+        }
+    }
+
+    /// File a replay in progress is read from
+    fn replay_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".replay");
+        PathBuf::from(path)
+    }
+
+    /// Quarantine file for entries that will not be retried
+    fn rejected_path(&self) -> PathBuf {
+        self.path.with_extension("rejected.jsonl")
+    }
+
+    async fn append(&self, batch: &Batch, attempts: u32) -> shared::Result<()> {
+        let line = serde_json::to_vec(&SpoolEntry { attempts, batch })?;
+
+        let _guard = self.lock.lock().await;
+        append_line(&self.path, &line).await?;
+        self.counters
+            .spooled
+            .fetch_add(batch.len() as u64, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Move a spool line to the quarantine file
+    async fn quarantine(&self, line: &[u8]) -> shared::Result<()> {
+        let _guard = self.lock.lock().await;
+        append_line(&self.rejected_path(), line).await?;
+        self.counters.quarantined.fetch_add(1, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Store every spooled batch; batches that still fail are spooled again
+    /// until they run out of attempts
+    async fn replay(&self, db: &SqlitePool, series: &SeriesTracker) {
+        let replay_path = self.replay_path();
+
+        // A leftover replay file means the daemon stopped mid-replay; finish
+        // it before taking the current spool
+        if replay_path.exists() && !self.replay_file(db, series, &replay_path).await {
+            return;
+        }
+
+        {
+            let _guard = self.lock.lock().await;
+            if !self.path.exists() {
+                return;
+            }
+            if let Err(e) = tokio::fs::rename(&self.path, &replay_path).await {
+                error!("Failed to start spool replay: {}", e);
+                return;
+            }
+        }
+        self.replay_file(db, series, &replay_path).await;
+    }
+
+    /// Replay one file and remove it, returning whether it was finished
+    async fn replay_file(
+        &self,
+        db: &SqlitePool,
+        series: &SeriesTracker,
+        replay_path: &Path,
+    ) -> bool {
+        let contents = match tokio::fs::read_to_string(replay_path).await {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("Failed to read spool {}: {}", replay_path.display(), e);
+                return false;
+            }
+        };
+
+        let (mut stored, mut respooled, mut quarantined) = (0, 0, 0);
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            let entry: SpoolEntry<Batch> = match serde_json::from_str(line) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!("Quarantining unreadable spool entry: {}", e);
+                    if let Err(e) = self.quarantine(line.as_bytes()).await {
+                        // Keep the replay file so nothing is lost
+                        error!("Failed to quarantine spool entry, will retry: {}", e);
+                        return false;
+                    }
+                    quarantined += 1;
+                    continue;
+                }
+            };
+            let SpoolEntry { attempts, batch } = entry;
+
+            let result = match batch.store(db, series).await {
+                Ok(()) => {
+                    stored += batch.len();
+                    self.counters
+                        .stored
+                        .fetch_add(batch.len() as u64, Ordering::Relaxed);
+                    continue;
+                }
+                Err(e) if is_transient(&e) => {
+                    respooled += batch.len();
+                    self.append(&batch, attempts).await
+                }
+                Err(e) if attempts + 1 < MAX_SPOOL_ATTEMPTS => {
+                    debug!("Spooled {} failed again: {}", batch.kind(), e);
+                    respooled += batch.len();
+                    self.append(&batch, attempts + 1).await
+                }
+                Err(e) => {
+                    error!(
+                        "Quarantining {} {} after {} failed attempts: {}",
+                        batch.len(),
+                        batch.kind(),
+                        attempts + 1,
+                        e
+                    );
+                    quarantined += 1;
+                    let entry = SpoolEntry {
+                        attempts: attempts + 1,
+                        batch: &batch,
+                    };
+                    match serde_json::to_vec(&entry) {
+                        Ok(line) => self.quarantine(&line).await,
+                        Err(e) => Err(e.into()),
+                    }
+                }
+            };
+            if let Err(e) = result {
+                // Keep the replay file so nothing is lost
+                error!("Failed to re-spool batch, will retry: {}", e);
+                return false;
+            }
+        }
+
+        if let Err(e) = remove_file(replay_path).await {
+            error!("Failed to remove {}: {}", replay_path.display(), e);
+            return false;
+        }
+        info!(
+            "Replayed spool: {} rows stored, {} rows re-spooled, {} entries quarantined",
+            stored, respooled, quarantined
+        );
+        true
+    }
+}
+
+/// Append one line to a JSON Lines file, creating it if needed
+async fn append_line(path: &Path, line: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    let mut line = line.to_vec();
+    line.push(b'\n');
+    file.write_all(&line).await?;
+    file.sync_data().await
+}
+
+async fn remove_file(path: &Path) -> std::io::Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixture {
+        db: SqlitePool,
+        db_path: PathBuf,
+        spool_path: PathBuf,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let db_path =
+                std::env::temp_dir().join(format!("lumo-queue-{}.db", uuid::Uuid::new_v4()));
+            let db = shared::create_pool(&db_path).await.unwrap();
+            shared::run_migrations(&db).await.unwrap();
+            let spool_path = db_path.with_extension("spool.jsonl");
+            Self {
+                db,
+                db_path,
+                spool_path,
+            }
+        }
+
+        fn spool(&self) -> Spool {
+            Spool::new(self.spool_path.clone(), Arc::default())
+        }
+
+        async fn stored(&self) -> usize {
+            EventRepository::find_by_session(&self.db, "queue-session")
+                .await
+                .unwrap()
+                .len()
+        }
+
+        fn spooled_lines(&self, path: &Path) -> usize {
+            std::fs::read_to_string(path)
+                .map(|contents| contents.lines().count())
+                .unwrap_or_default()
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.db_path);
+            let _ = std::fs::remove_file(&self.spool_path);
+            let _ = std::fs::remove_file(self.spool_path.with_extension("rejected.jsonl"));
+        }
+    }
+
+    fn events(ids: &[&str]) -> Batch {
+        Batch::Events(
+            ids.iter()
+                .map(|id| {
+                    serde_json::from_value(serde_json::json!({
+                        "id": id,
+                        "session_id": "queue-session",
+                        "name": "claude_code.user_prompt",
+                        "timestamp": 1_700_000_000_000i64,
+                    }))
+                    .unwrap()
+                })
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_spools() {
+        let fixture = Fixture::new().await;
+        let (tx, _rx) = mpsc::channel(1);
+        let queue = IngestQueue {
+            tx,
+            spool: Arc::new(fixture.spool()),
+        };
+
+        queue.enqueue(events(&["a"])).await.unwrap();
+        queue.enqueue(events(&["b", "c"])).await.unwrap();
+
+        assert_eq!(queue.depth(), 1);
+        assert_eq!(fixture.spooled_lines(&fixture.spool_path), 1);
+        assert_eq!(queue.spool.counters.spooled.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_store_is_spooled() {
+        let fixture = Fixture::new().await;
+        let spool = fixture.spool();
+        fixture.db.close().await;
+
+        store_or_spool(&fixture.db, &SeriesTracker::new(), &spool, events(&["a"])).await;
+
+        assert_eq!(fixture.spooled_lines(&fixture.spool_path), 1);
+        assert_eq!(spool.counters.stored.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn test_start_replays_spool_and_leftover_replay() {
+        let fixture = Fixture::new().await;
+        let spool = fixture.spool();
+        spool.append(&events(&["a"]), 0).await.unwrap();
+        std::fs::rename(&fixture.spool_path, spool.replay_path()).unwrap();
+        spool.append(&events(&["b", "c"]), 0).await.unwrap();
+
+        let (_, task) = IngestQueue::start(
+            fixture.db.clone(),
+            4,
+            fixture.spool_path.clone(),
+            Arc::default(),
+            std::future::ready(()),
+        );
+        task.await.unwrap();
+
+        assert_eq!(fixture.stored().await, 3);
+        assert!(!fixture.spool_path.exists());
+        assert!(!spool.replay_path().exists());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stores_queued_batches() {
+        let fixture = Fixture::new().await;
+        let counters = Arc::new(IngestCounters::default());
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let (queue, task) = IngestQueue::start(
+            fixture.db.clone(),
+            4,
+            fixture.spool_path.clone(),
+            counters.clone(),
+            async {
+                let _ = stopped.await;
+            },
+        );
+
+        // Queued before the writer gets to run
+        queue.enqueue(events(&["a"])).await.unwrap();
+        queue.enqueue(events(&["b", "c"])).await.unwrap();
+        stop.send(()).unwrap();
+        task.await.unwrap();
+
+        assert_eq!(fixture.stored().await, 3);
+        assert_eq!(counters.stored.load(Ordering::Relaxed), 3);
+        assert!(!fixture.spool_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_replay_quarantines_unreadable_lines() {
+        let fixture = Fixture::new().await;
+        let spool = fixture.spool();
+        // A line written before spool entries carried an attempt count
+        let legacy = serde_json::to_string(&events(&["a"])).unwrap();
+        std::fs::write(&fixture.spool_path, format!("{legacy}\nnot json\n")).unwrap();
+
+        spool.replay(&fixture.db, &SeriesTracker::new()).await;
+
+        assert_eq!(fixture.stored().await, 1);
+        assert!(!fixture.spool_path.exists());
+        assert_eq!(
+            std::fs::read_to_string(spool.rejected_path()).unwrap(),
+            "not json\n"
+        );
+        assert_eq!(spool.counters.quarantined.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_quarantines_batches_that_keep_failing() {
+        let fixture = Fixture::new().await;
+        let spool = fixture.spool();
+        let series = SeriesTracker::new();
+        sqlx::query("DROP TABLE events")
+            .execute(&fixture.db)
+            .await
+            .unwrap();
+        spool.append(&events(&["a"]), 0).await.unwrap();
+
+        for _ in 1..MAX_SPOOL_ATTEMPTS {
+            spool.replay(&fixture.db, &series).await;
+            assert_eq!(fixture.spooled_lines(&fixture.spool_path), 1);
+        }
+        spool.replay(&fixture.db, &series).await;
+
+        assert!(!fixture.spool_path.exists());
+        assert_eq!(fixture.spooled_lines(&spool.rejected_path()), 1);
+        assert_eq!(spool.counters.quarantined.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_database_does_not_use_up_attempts() {
+        let fixture = Fixture::new().await;
+        let spool = fixture.spool();
+        let series = SeriesTracker::new();
+        fixture.db.close().await;
+        spool.append(&events(&["a"]), 0).await.unwrap();
+
+        for _ in 0..MAX_SPOOL_ATTEMPTS {
+            spool.replay(&fixture.db, &series).await;
+        }
+
+        assert_eq!(fixture.spooled_lines(&fixture.spool_path), 1);
+        assert!(!spool.rejected_path().exists());
+    }
+}
@@ -0,0 +1,480 @@
+//! Redaction
+//!
+//! Scrubs personal data and secrets from parsed rows before they are queued,
+//! so they reach neither the spool file nor the database. Each field can be
+//! kept, dropped, hashed or masked with regular expressions, and built-in
+//! detectors mask API keys, tokens and (when enabled) email addresses in
+//! every free-text field. The policy is read from a JSON file:
+//!
+//! ```json
+//! {
+//!   "fields": {
+//!     "prompt": "drop",
+//!     "user_email": "hash",
+//!     "tool_parameters": { "mask": ["--password[= ]\\S+"] }
+//!   },
+//!   "attributes": { "host.name": "hash" },
+//!   "detectors": ["api_key", "jwt", "bearer_token", "secret_assignment", "email"]
+//! }
+//! ```
+//!
+//! A field policy also applies to the attribute keys that fill that column
+//! (see [`AttributeMapping`]), so dropping `prompt` removes it from the
+//! stored `attributes` JSON as well. `attributes` sets policies for any other
+//! attribute key. Without a file, only the default detectors run.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use shared::{NewEvent, NewMetric, NewNotification, NewSpan};
+
+use super::attribute_mapping::AttributeMapping;
+
+/// Columns a field policy can be set for
+const FIELDS: &[&str] = &[
+    "prompt",
+    "tool_parameters",
+    "error",
+    "user_email",
+    "user_id",
+    "account_uuid",
+    "organization_id",
+    "status_message",
+    "message",
+    "cwd",
+    "transcript_path",
+];
+
+/// Built-in detectors: name and pattern
+const DETECTORS: &[(&str, &str)] = &[
+    (
+        "jwt",
+        r"\beyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}",
+    ),
+    (
+        "api_key",
+        r"\b(?:sk-(?:ant-)?[A-Za-z0-9_-]{20,}|AKIA[0-9A-Z]{16}|AIza[0-9A-Za-z_-]{35}|gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{22,}|xox[abprs]-[A-Za-z0-9-]{10,})",
+    ),
+    ("bearer_token", r"(?i)\bbearer\s+[A-Za-z0-9._~+/=-]{16,}"),
+    (
+        "secret_assignment",
+        r#"(?i)\b(?:password|passwd|secret|token|api[_-]?key)\s*[=:]\s*["']?[^\s"']+"#,
+    ),
+    (
+        "email",
+        r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b",
+    ),
+];
+
+/// Detectors that run when the file does not list any
+///
+/// Emails are left out because `user.email` identifies the local user and is
+/// shown in the app.
+const DEFAULT_DETECTORS: &[&str] = &["jwt", "api_key", "bearer_token", "secret_assignment"];
+
+/// Policy as written in the redaction file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PolicyConfig {
+    Keep,
+    Drop,
+    Hash,
+    /// Regular expressions whose matches are replaced
+    Mask(Vec<String>),
+}
+
+/// What happens to a field or attribute value
+#[derive(Debug, Clone)]
+enum Policy {
+    Keep,
+    Drop,
+    /// Replace with `sha256:<hex>`, so equal values can still be grouped
+    Hash,
+    Mask(Vec<Regex>),
+}
+
+impl TryFrom<PolicyConfig> for Policy {
+    type Error = anyhow::Error;
+
+    fn try_from(config: PolicyConfig) -> Result<Self> {
+        Ok(match config {
+            PolicyConfig::Keep => Self::Keep,
+            PolicyConfig::Drop => Self::Drop,
+            PolicyConfig::Hash => Self::Hash,
+            PolicyConfig::Mask(patterns) => Self::Mask(
+                patterns
+                    .iter()
+                    .map(|pattern| {
+                        Regex::new(pattern)
+                            .with_context(|| format!("Invalid mask pattern: {}", pattern))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+        })
+    }
+}
+
+/// Layout of the redaction file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RedactionFile {
+    #[serde(default)]
+    fields: HashMap<String, PolicyConfig>,
+    #[serde(default)]
+    attributes: HashMap<String, PolicyConfig>,
+    detectors: Option<Vec<String>>,
+}
+
+/// Values redacted since the daemon started
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RedactionReport {
+    /// Values changed by a policy, by field or attribute key
+    pub fields: BTreeMap<String, u64>,
+    /// Matches masked by each detector
+    pub detectors: BTreeMap<String, u64>,
+}
+
+impl RedactionReport {
+    fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.detectors.is_empty()
+    }
+
+    fn merge(&mut self, other: RedactionReport) {
+        for (key, count) in other.fields {
+            *self.fields.entry(key).or_default() += count;
+        }
+        for (key, count) in other.detectors {
+            *self.detectors.entry(key).or_default() += count;
+        }
+    }
+}
+
+/// Applies the redaction policy to parsed rows
+///
+/// The policy can be reloaded while the daemon runs; the report carries over.
+#[derive(Debug)]
+pub struct Redactor {
+    rules: RwLock<Arc<Rules>>,
+    report: Mutex<RedactionReport>,
+}
+
+impl Redactor {
+    /// Load the policy from `path`, or the defaults if it does not exist
+    pub fn load(path: &Path, mapping: &AttributeMapping) -> Result<Self> {
+        Ok(Self {
+            rules: RwLock::new(Arc::new(Rules::load(path, mapping)?)),
+            report: Mutex::new(RedactionReport::default()),
+        })
+    }
+
+    /// Replace the policy with the one in `path`
+    ///
+    /// On error the current policy stays in place.
+    pub fn reload(&self, path: &Path, mapping: &AttributeMapping) -> Result<()> {
+        let rules = Rules::load(path, mapping)?;
+        *self.rules.write().unwrap() = Arc::new(rules);
+        Ok(())
+    }
+
+    fn rules(&self) -> Arc<Rules> {
+        self.rules.read().unwrap().clone()
+    }
+
+    /// Counts of redacted values since the daemon started
+    pub fn report(&self) -> RedactionReport {
+        self.report.lock().unwrap().clone()
+    }
+
+    pub fn redact_events(&self, rows: &mut [NewEvent]) {
+        let rules = self.rules();
+        let mut report = RedactionReport::default();
+        for row in rows {
+            rules.field("prompt", &mut row.prompt, &mut report);
+            rules.field("tool_parameters", &mut row.tool_parameters, &mut report);
+            rules.field("error", &mut row.error, &mut report);
+            rules.field("user_email", &mut row.user_email, &mut report);
+            rules.field("user_id", &mut row.user_id, &mut report);
+            rules.field("account_uuid", &mut row.account_uuid, &mut report);
+            rules.field("organization_id", &mut row.organization_id, &mut report);
+            rules.json(&mut row.attributes, &mut report);
+            rules.json(&mut row.resource, &mut report);
+        }
+        self.record(report);
+    }
+
+    pub fn redact_metrics(&self, rows: &mut [NewMetric]) {
+        let rules = self.rules();
+        let mut report = RedactionReport::default();
+        for row in rows {
+            rules.field("user_email", &mut row.user_email, &mut report);
+            rules.field("user_id", &mut row.user_id, &mut report);
+            rules.field("account_uuid", &mut row.account_uuid, &mut report);
+            rules.field("organization_id", &mut row.organization_id, &mut report);
+            rules.json(&mut row.attributes, &mut report);
+            rules.json(&mut row.resource, &mut report);
+        }
+        self.record(report);
+    }
+
+    pub fn redact_spans(&self, rows: &mut [NewSpan]) {
+        let rules = self.rules();
+        let mut report = RedactionReport::default();
+        for row in rows {
+            rules.field("status_message", &mut row.status_message, &mut report);
+            rules.json(&mut row.attributes, &mut report);
+            rules.json(&mut row.events, &mut report);
+            rules.json(&mut row.resource, &mut report);
+        }
+        self.record(report);
+    }
+
+    pub fn redact_notification(&self, notification: &mut NewNotification) {
+        let rules = self.rules();
+        let mut report = RedactionReport::default();
+        let mut message = Some(std::mem::take(&mut notification.message));
+        rules.field("message", &mut message, &mut report);
+        notification.message = message.unwrap_or_default();
+        rules.field("cwd", &mut notification.cwd, &mut report);
+        rules.field(
+            "transcript_path",
+            &mut notification.transcript_path,
+            &mut report,
+        );
+        self.record(report);
+    }
+
+    fn record(&self, report: RedactionReport) {
+        if !report.is_empty() {
+            self.report.lock().unwrap().merge(report);
+        }
+    }
+}
+
+/// A loaded redaction policy
+#[derive(Debug)]
+struct Rules {
+    fields: HashMap<String, Policy>,
+    attributes: HashMap<String, Policy>,
+    detectors: Vec<(&'static str, Regex)>,
+}
+
+impl Rules {
+    fn load(path: &Path, mapping: &AttributeMapping) -> Result<Self> {
+        let file = match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("Invalid redaction config {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => RedactionFile::default(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read redaction config {}", path.display()))
+            }
+        };
+
+        Self::from_file(file, mapping)
+    }
+
+    fn from_file(file: RedactionFile, mapping: &AttributeMapping) -> Result<Self> {
+        let mut fields = HashMap::new();
+        for (field, config) in file.fields {
+            if !FIELDS.contains(&field.as_str()) {
+                bail!("Unknown field in redaction config: {}", field);
+            }
+            fields.insert(field, Policy::try_from(config)?);
+        }
+
+        let mut attributes = HashMap::new();
+        for (key, config) in file.attributes {
+            attributes.insert(key, Policy::try_from(config)?);
+        }
+        // Apply field policies to the attributes the fields are read from
+        for (field, policy) in &fields {
+            for key in mapping
+                .events
+                .keys(field)
+                .chain(mapping.metrics.keys(field))
+            {
+                attributes
+                    .entry(key.to_string())
+                    .or_insert_with(|| policy.clone());
+            }
+        }
+
+        let names: Vec<String> = match file.detectors {
+            Some(names) => names,
+            None => DEFAULT_DETECTORS.iter().map(|n| n.to_string()).collect(),
+        };
+        let mut detectors = Vec::new();
+        for name in &names {
+            let Some(&(name, pattern)) = DETECTORS.iter().find(|(n, _)| n == name) else {
+                bail!("Unknown detector in redaction config: {}", name);
+            };
+            detectors.push((name, Regex::new(pattern)?));
+        }
+
+        Ok(Self {
+            fields,
+            attributes,
+            detectors,
+        })
+    }
+
+    /// Redact a column value
+    fn field(&self, name: &str, value: &mut Option<String>, report: &mut RedactionReport) {
+        if let Some(text) = value.take() {
+            *value = self.text(name, self.fields.get(name), text, report);
+        }
+    }
+
+    /// Apply `policy` and then the detectors to a value; `None` drops it
+    fn text(
+        &self,
+        key: &str,
+        policy: Option<&Policy>,
+        text: String,
+        report: &mut RedactionReport,
+    ) -> Option<String> {
+        let text = match policy {
+            Some(Policy::Drop) => {
+                *report.fields.entry(key.to_string()).or_default() += 1;
+                return None;
+            }
+            Some(Policy::Hash) => {
+                *report.fields.entry(key.to_string()).or_default() += 1;
+                return Some(hash(&text));
+            }
+            Some(Policy::Mask(patterns)) => {
+                let mut masked = text.clone();
+                for pattern in patterns {
+                    masked = pattern.replace_all(&masked, "[REDACTED]").into_owned();
+                }
+                if masked != text {
+                    *report.fields.entry(key.to_string()).or_default() += 1;
+                }
+                masked
+            }
+            Some(Policy::Keep) | None => text,
+        };
+
+        Some(self.detect(text, report))
+    }
+
+    /// Mask everything the detectors match
+    fn detect(&self, mut text: String, report: &mut RedactionReport) -> String {
+        for (name, pattern) in &self.detectors {
+            let matches = pattern.find_iter(&text).count() as u64;
+            if matches > 0 {
+                text = pattern
+                    .replace_all(&text, format!("[REDACTED:{}]", name))
+                    .into_owned();
+                *report.detectors.entry(name.to_string()).or_default() += matches;
+            }
+        }
+        text
+    }
+
+    /// Redact a JSON blob: attribute policies on top-level keys, then the
+    /// detectors on every string in it
+    fn json(&self, value: &mut Option<String>, report: &mut RedactionReport) {
+        let Some(text) = value.take() else {
+            return;
+        };
+        let Ok(mut json) = serde_json::from_str::<Value>(&text) else {
+            *value = Some(self.detect(text, report));
+            return;
+        };
+
+        if let Value::Object(map) = &mut json {
+            let keys: Vec<String> = map.keys().cloned().collect();
+            for key in keys {
+                let Some(policy) = self.attributes.get(&key) else {
+                    continue;
+                };
+                let entry = map.remove(&key).unwrap_or(Value::Null);
+                let entry_text = match entry {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                if let Some(redacted) = self.text(&key, Some(policy), entry_text, report) {
+                    map.insert(key, Value::String(redacted));
+                }
+            }
+        }
+        self.detect_value(&mut json, report);
+
+        *value = serde_json::to_string(&json).ok();
+    }
+
+    fn detect_value(&self, value: &mut Value, report: &mut RedactionReport) {
+        match value {
+            Value::String(s) => *s = self.detect(std::mem::take(s), report),
+            Value::Array(values) => {
+                for value in values {
+                    self.detect_value(value, report);
+                }
+            }
+            Value::Object(map) => {
+                for value in map.values_mut() {
+                    self.detect_value(value, report);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn hash(text: &str) -> String {
+    format!("sha256:{:x}", Sha256::digest(text.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_policies_and_detectors() {
+        let file: RedactionFile = serde_json::from_str(
+            r#"{
+                "fields": {
+                    "prompt": "drop",
+                    "user_email": "hash",
+                    "tool_parameters": { "mask": ["--password \\S+"] }
+                }
+            }"#,
+        )
+        .unwrap();
+        let rules = Rules::from_file(file, &AttributeMapping::default()).unwrap();
+
+        let mut report = RedactionReport::default();
+        let mut prompt = Some("my secret plan".to_string());
+        rules.field("prompt", &mut prompt, &mut report);
+        assert_eq!(prompt, None);
+
+        let mut email = Some("dev@example.com".to_string());
+        rules.field("user_email", &mut email, &mut report);
+        assert!(email.unwrap().starts_with("sha256:"));
+
+        let mut params = Some(
+            r#"{"command":"deploy --password hunter2 --key sk-ant-REDACTED"}"#
+                .to_string(),
+        );
+        rules.field("tool_parameters", &mut params, &mut report);
+        assert_eq!(
+            params.as_deref(),
+            Some(r#"{"command":"deploy [REDACTED] --key [REDACTED:api_key]"}"#)
+        );
+
+        // The prompt attribute is dropped from the JSON blob too
+        let mut attributes = Some(r#"{"prompt":"hi","model":"opus"}"#.to_string());
+        rules.json(&mut attributes, &mut report);
+        assert_eq!(attributes.as_deref(), Some(r#"{"model":"opus"}"#));
+
+        assert_eq!(report.fields["prompt"], 2);
+        assert_eq!(report.detectors["api_key"], 1);
+    }
+}
@@ -0,0 +1,192 @@
+//! Scheduled retention
+//!
+//! Deletes raw telemetry older than the retention policy allows, then hands
+//! the freed pages back to the file system. Runs at startup, every hour and
+//! whenever the policy changes.
+
+use std::future::Future;
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use shared::{
+    EventRepository, MetricRepository, NotificationRepository, RetentionPolicy, SpanRepository,
+};
+use sqlx::SqlitePool;
+use tokio::sync::Notify;
+use tracing::{error, info};
+
+/// How often the policy is applied when it does not change
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// The retention policy in effect
+pub struct Retention {
+    policy: RwLock<RetentionPolicy>,
+    changed: Notify,
+}
+
+impl Retention {
+    /// Load the policy from `path`; a missing file keeps everything
+    pub fn load(path: &Path) -> Result<Self> {
+        let policy = RetentionPolicy::load(path)
+            .with_context(|| format!("Invalid retention policy {}", path.display()))?;
+
+        Ok(Self {
+            policy: RwLock::new(policy),
+            changed: Notify::new(),
+        })
+    }
+
+    /// Re-read the policy from `path`, pruning right away if it changed
+    ///
+    /// On error the current policy stays in effect.
+    pub fn reload(&self, path: &Path) -> Result<bool> {
+        let policy = RetentionPolicy::load(path)
+            .with_context(|| format!("Invalid retention policy {}", path.display()))?;
+
+        let mut current = self.policy.write().unwrap();
+        if *current == policy {
+            return Ok(false);
+        }
+        *current = policy;
+        self.changed.notify_one();
+
+        Ok(true)
+    }
+
+    /// The policy in effect
+    pub fn policy(&self) -> RetentionPolicy {
+        *self.policy.read().unwrap()
+    }
+
+    /// Apply the policy periodically until `shutdown` resolves
+    pub async fn run<F>(&self, pool: SqlitePool, shutdown: F)
+    where
+        F: Future<Output = ()>,
+    {
+        let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+        tokio::pin!(shutdown);
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = interval.tick() => {}
+                _ = self.changed.notified() => {}
+            }
+
+            if let Err(e) = prune(&pool, &self.policy()).await {
+                error!("Retention run failed: {:#}", e);
+            }
+        }
+    }
+}
+
+/// Delete rows older than the policy allows and reclaim their space
+async fn prune(pool: &SqlitePool, policy: &RetentionPolicy) -> Result<()> {
+    let cutoffs = Cutoffs::new(policy, chrono::Utc::now().timestamp_millis());
+
+    let mut deleted = Vec::new();
+    if let Some(before) = cutoffs.events {
+        deleted.push((
+            "events",
+            EventRepository::delete_before(pool, before).await?,
+        ));
+    }
+    if let Some(before) = cutoffs.metrics {
+        deleted.push((
+            "metrics",
+            MetricRepository::delete_before(pool, before).await?,
+        ));
+    }
+    if let Some(before) = cutoffs.spans {
+        deleted.push(("spans", SpanRepository::delete_before(pool, before).await?));
+    }
+    if let Some(before) = cutoffs.notifications {
+        deleted.push((
+            "notifications",
+            NotificationRepository::delete_before(pool, before).await?,
+        ));
+    }
+
+    if deleted.iter().all(|(_, count)| *count == 0) {
+        return Ok(());
+    }
+    let summary: Vec<String> = deleted
+        .iter()
+        .map(|(table, count)| format!("{} {}", count, table))
+        .collect();
+    info!("Retention deleted {}", summary.join(", "));
+
+    let db_path = shared::get_db_path()?;
+    let before = database_size(&db_path);
+    shared::reclaim_space(pool).await?;
+    let after = database_size(&db_path);
+    info!(
+        "Reclaimed {} KiB ({} KiB -> {} KiB)",
+        before.saturating_sub(after) / 1024,
+        before / 1024,
+        after / 1024
+    );
+
+    Ok(())
+}
+
+/// Unix millisecond timestamps rows must be newer than, per table
+#[derive(Debug, PartialEq)]
+struct Cutoffs {
+    events: Option<i64>,
+    metrics: Option<i64>,
+    spans: Option<i64>,
+    notifications: Option<i64>,
+}
+
+impl Cutoffs {
+    fn new(policy: &RetentionPolicy, now: i64) -> Self {
+        let cutoff = |days: Option<u32>| days.map(|days| now - i64::from(days) * DAY_MS);
+
+        Self {
+            events: cutoff(policy.events_days),
+            metrics: cutoff(policy.metrics_days),
+            spans: cutoff(policy.spans_days),
+            notifications: cutoff(policy.notifications_days),
+        }
+    }
+}
+
+/// Size of the database file and its write-ahead log in bytes
+fn database_size(db_path: &Path) -> u64 {
+    let wal_path = db_path.with_extension("db-wal");
+    [db_path, wal_path.as_path()]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cutoffs() {
+        let policy = RetentionPolicy {
+            events_days: Some(30),
+            notifications_days: Some(1),
+            ..Default::default()
+        };
+        let now = 100 * DAY_MS;
+
+        assert_eq!(
+            Cutoffs::new(&policy, now),
+            Cutoffs {
+                events: Some(70 * DAY_MS),
+                metrics: None,
+                spans: None,
+                notifications: Some(99 * DAY_MS),
+            }
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Telemetry sources
+//!
+//! Lumo stores every coding agent's telemetry in the Claude Code event and
+//! metric schema. The agent is identified by the `service.name` resource
+//! attribute, and each one has a normalizer that maps its event names,
+//! metric names and attribute keys onto that schema. Records without a
+//! counterpart keep their own names.
+
+use std::collections::HashMap;
+
+/// Coding agent that sent a batch of telemetry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    ClaudeCode,
+    Codex,
+    GeminiCli,
+    /// Any other agent, by its normalized `service.name`
+    Other(String),
+}
+
+impl Source {
+    /// Identify the source from the `service.name` resource attribute
+    ///
+    /// Exports without a service name are treated as Claude Code, which is
+    /// what the daemon assumed before it knew about other agents.
+    pub fn from_service_name(service_name: Option<&str>) -> Self {
+        let Some(name) = service_name
+            .map(|name| name.trim().to_ascii_lowercase().replace('-', "_"))
+            .filter(|name| !name.is_empty())
+        else {
+            return Self::ClaudeCode;
+        };
+
+        match name.as_str() {
+            "claude_code" => Self::ClaudeCode,
+            "gemini_cli" => Self::GeminiCli,
+            name if name.starts_with("codex") => Self::Codex,
+            _ => Self::Other(name),
+        }
+    }
+
+    /// Value stored in the `source` column
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::ClaudeCode => "claude_code",
+            Self::Codex => "codex",
+            Self::GeminiCli => "gemini_cli",
+            Self::Other(name) => name,
+        }
+    }
+
+    /// Name of log records carrying neither an event name nor a body
+    pub fn unknown_event_name(&self) -> String {
+        format!("{}.unknown", self.as_str())
+    }
+
+    /// Map an event name onto the common schema
+    ///
+    /// `attrs` must already be normalized.
+    pub fn event_name(&self, name: &str, attrs: &HashMap<String, String>) -> String {
+        let common = match self {
+            Self::ClaudeCode => {
+                // Claude Code names are already common; add the prefix if missing
+                return if name.starts_with("claude_code.") {
+                    name.to_string()
+                } else {
+                    format!("claude_code.{}", name)
+                };
+            }
+            Self::Codex => match name.trim_start_matches("codex.") {
+                // Token usage arrives on the completed response stream event
+                "sse_event"
+                    if attrs.get("event.kind").map(String::as_str)
+                        == Some("response.completed") =>
+                {
+                    Some("api_request")
+                }
+                "tool_result" => Some("tool_result"),
+                "tool_decision" => Some("tool_decision"),
+                "user_prompt" => Some("user_prompt"),
+                _ => None,
+            },
+            Self::GeminiCli => match name.trim_start_matches("gemini_cli.") {
+                "api_response" => Some("api_request"),
+                "api_error" => Some("api_error"),
+                "tool_call" => Some("tool_result"),
+                "user_prompt" => Some("user_prompt"),
+                _ => None,
+            },
+            Self::Other(_) => None,
+        };
+
+        match common {
+            Some(common) => format!("claude_code.{}", common),
+            None => name.to_string(),
+        }
+    }
+
+    /// Map a metric name onto the common schema
+    pub fn metric_name(&self, name: &str) -> String {
+        let common = match self {
+            Self::GeminiCli => match name.trim_start_matches("gemini_cli.") {
+                "token.usage" => Some("token.usage"),
+                "session.count" => Some("session.count"),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match common {
+            Some(common) => format!("claude_code.{}", common),
+            None => name.to_string(),
+        }
+    }
+
+    /// Copy source-specific attributes to the keys the common schema reads
+    ///
+    /// Keys the record already has are left alone.
+    pub fn normalize_attributes(&self, attrs: &mut HashMap<String, String>) {
+        let aliases: &[(&str, &str)] = match self {
+            Self::Codex => &[
+                ("conversation.id", "session.id"),
+                ("input_token_count", "input_tokens"),
+                ("output_token_count", "output_tokens"),
+                ("cached_token_count", "cache_read_tokens"),
+                ("http.response.status_code", "status_code"),
+            ],
+            Self::GeminiCli => &[
+                ("input_token_count", "input_tokens"),
+                ("output_token_count", "output_tokens"),
+                ("cached_content_token_count", "cache_read_tokens"),
+                ("function_name", "tool_name"),
+            ],
+            Self::ClaudeCode | Self::Other(_) => &[],
+        };
+
+        for &(from, to) in aliases {
+            if attrs.contains_key(to) {
+                continue;
+            }
+            if let Some(value) = attrs.get(from).cloned() {
+                attrs.insert(to.to_string(), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizes_other_agents() {
+        assert_eq!(Source::from_service_name(None), Source::ClaudeCode);
+        assert_eq!(
+            Source::from_service_name(Some("codex_cli_rs")),
+            Source::Codex
+        );
+        assert_eq!(
+            Source::from_service_name(Some("My-Agent")),
+            Source::Other("my_agent".to_string())
+        );
+
+        let source = Source::from_service_name(Some("gemini-cli"));
+        let mut attrs: HashMap<String, String> =
+            [("function_name", "read_file"), ("input_token_count", "120")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+        source.normalize_attributes(&mut attrs);
+
+        assert_eq!(attrs["tool_name"], "read_file");
+        assert_eq!(attrs["input_tokens"], "120");
+        assert_eq!(
+            source.event_name("gemini_cli.tool_call", &attrs),
+            "claude_code.tool_result"
+        );
+        assert_eq!(
+            source.event_name("gemini_cli.config", &attrs),
+            "gemini_cli.config"
+        );
+        assert_eq!(
+            Source::ClaudeCode.event_name("api_request", &attrs),
+            "claude_code.api_request"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Daemon status
+//!
+//! Answers "why is my dashboard empty": when data last arrived, how fast it
+//! is coming in and how much was rejected, how far the writer is behind, and
+//! the state of the database and the daemon itself.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+use chrono::Utc;
+use serde::Serialize;
+use shared::{EventRepository, MetricRepository};
+use tracing::warn;
+
+use crate::server::AppState;
+
+/// Status of the running daemon
+#[derive(Debug, Serialize)]
+pub struct DaemonStatus {
+    /// "healthy" or "unhealthy" (the database is unreachable)
+    pub status: &'static str,
+    pub version: &'static str,
+    /// Unix milliseconds
+    pub started_at: i64,
+    pub uptime_seconds: i64,
+    pub ingest: IngestStatus,
+    pub queue: QueueStatus,
+    pub database: DatabaseStatus,
+}
+
+/// Incoming data
+#[derive(Debug, Serialize)]
+pub struct IngestStatus {
+    /// When records were last accepted since the daemon started (Unix ms)
+    pub last_received_at: Option<i64>,
+    /// Timestamp of the newest stored event (Unix ms)
+    pub latest_event_at: Option<i64>,
+    /// Timestamp of the newest stored metric (Unix ms)
+    pub latest_metric_at: Option<i64>,
+    /// Records accepted in the last complete minute
+    pub events_per_minute: u64,
+    pub metrics_per_minute: u64,
+    pub spans_per_minute: u64,
+    /// Records rejected while parsing since the daemon started
+    pub parse_failures: u64,
+    /// Hook notifications received since the daemon started
+    pub notifications: u64,
+}
+
+/// Background writer
+#[derive(Debug, Serialize)]
+pub struct QueueStatus {
+    /// Batches waiting to be written
+    pub depth: usize,
+    pub capacity: usize,
+    /// Size of exports spooled to disk
+    pub spool_bytes: u64,
+    /// Rows written since the daemon started
+    pub stored_rows: u64,
+    /// Rows spooled since the daemon started
+    pub spooled_rows: u64,
+    /// Spool entries quarantined since the daemon started
+    pub quarantined_entries: u64,
+}
+
+/// SQLite database
+#[derive(Debug, Serialize)]
+pub struct DatabaseStatus {
+    pub connected: bool,
+    pub path: String,
+    pub size_bytes: u64,
+    pub wal_bytes: u64,
+    /// Version of the latest applied migration
+    pub migration_version: Option<i64>,
+    /// Bundled migrations not applied yet
+    pub pending_migrations: usize,
+}
+
+/// Collect the daemon status
+pub async fn collect_status(state: &AppState) -> DaemonStatus {
+    let now = Utc::now();
+    let now_ms = now.timestamp_millis();
+    let counters = &state.counters;
+    let signals = [&counters.logs, &counters.metrics, &counters.traces];
+
+    let database = database_status(state).await;
+    let latest = |result: shared::Result<Option<i64>>| {
+        result
+            .inspect_err(|e| warn!("Failed to read latest timestamp: {}", e))
+            .ok()
+            .flatten()
+    };
+
+    DaemonStatus {
+        status: if database.connected {
+            "healthy"
+        } else {
+            "unhealthy"
+        },
+        version: env!("CARGO_PKG_VERSION"),
+        started_at: state.started_at.timestamp_millis(),
+        uptime_seconds: (now - state.started_at).num_seconds(),
+        ingest: IngestStatus {
+            last_received_at: signals
+                .iter()
+                .filter_map(|signal| load_time(&signal.last_accepted_at))
+                .max(),
+            latest_event_at: latest(EventRepository::latest_timestamp(&state.db).await),
+            latest_metric_at: latest(MetricRepository::latest_timestamp(&state.db).await),
+            events_per_minute: counters.logs.per_minute.last_minute(now_ms),
+            metrics_per_minute: counters.metrics.per_minute.last_minute(now_ms),
+            spans_per_minute: counters.traces.per_minute.last_minute(now_ms),
+            parse_failures: signals.iter().map(|signal| load(&signal.rejected)).sum(),
+            notifications: load(&counters.notifications),
+        },
+        queue: QueueStatus {
+            depth: state.queue.depth(),
+            capacity: state.queue.capacity(),
+            spool_bytes: state.queue.spool_size(),
+            stored_rows: load(&counters.stored),
+            spooled_rows: load(&counters.spooled),
+            quarantined_entries: load(&counters.quarantined),
+        },
+        database,
+    }
+}
+
+async fn database_status(state: &AppState) -> DatabaseStatus {
+    let db_path = shared::get_db_path().unwrap_or_default();
+    let connected = sqlx::query("SELECT 1").execute(&state.db).await.is_ok();
+    let migration_version = shared::migration_version(&state.db)
+        .await
+        .inspect_err(|e| warn!("Failed to read migration version: {}", e))
+        .ok()
+        .flatten();
+    let pending_migrations = shared::pending_migrations(&state.db)
+        .await
+        .map(|pending| pending.len())
+        .unwrap_or_default();
+
+    DatabaseStatus {
+        connected,
+        path: db_path.display().to_string(),
+        size_bytes: file_size(&db_path),
+        wal_bytes: file_size(&db_path.with_extension("db-wal")),
+        migration_version,
+        pending_migrations,
+    }
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or_default()
+}
+
+fn load(counter: &AtomicU64) -> u64 {
+    counter.load(Ordering::Relaxed)
+}
+
+/// A Unix millisecond timestamp where 0 means never
+fn load_time(time: &AtomicI64) -> Option<i64> {
+    Some(time.load(Ordering::Relaxed)).filter(|&time| time > 0)
+}
@@ -0,0 +1,224 @@
+//! Aggregation temporality
+//!
+//! OTLP Sum points may be reported as deltas or as running (cumulative)
+//! totals. Everything downstream sums `metrics.value`, so cumulative points
+//! are converted to deltas here as they are stored.
+
+use std::collections::HashMap;
+
+use shared::{MetricRepository, NewMetric};
+use sqlx::SqlitePool;
+use tokio::sync::Mutex;
+
+/// Last point seen for a cumulative series
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SeriesState {
+    start_timestamp: Option<i64>,
+    timestamp: i64,
+    raw_value: f64,
+}
+
+/// Tracks cumulative series across export requests
+///
+/// Owned by the queue writer, so conversion happens as rows are stored rather
+/// than when they are received.
+#[derive(Default)]
+pub struct SeriesTracker {
+    series: Mutex<HashMap<String, SeriesState>>,
+}
+
+impl SeriesTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `metrics`, with the `value` of cumulative points rewritten to the
+    /// delta since the previous point of the same series
+    ///
+    /// The series state only advances once the rows are stored, so a batch
+    /// that fails and is spooled is converted again from the same state when
+    /// it is retried. Series not seen since startup are resumed from the
+    /// latest stored point, so a daemon restart does not re-count the running
+    /// total.
+    pub async fn store(&self, db: &SqlitePool, metrics: &[NewMetric]) -> shared::Result<()> {
+        // Held across the whole batch so batches touching the same series are
+        // applied one after the other
+        let mut series = self.series.lock().await;
+        let mut next = series.clone();
+        let mut metrics = metrics.to_vec();
+
+        for metric in metrics.iter_mut() {
+            if metric.temporality.as_deref() != Some("cumulative") {
+                continue;
+            }
+            let (Some(key), Some(raw_value)) = (metric.series_key.clone(), metric.raw_value) else {
+                continue;
+            };
+
+            let previous = match next.get(&key) {
+                Some(state) => Some(*state),
+                None => MetricRepository::find_latest_in_series(db, &key)
+                    .await?
+                    .and_then(|m| {
+                        Some(SeriesState {
+                            start_timestamp: m.start_timestamp,
+                            timestamp: m.timestamp,
+                            raw_value: m.raw_value?,
+                        })
+                    }),
+            };
+
+            let current = SeriesState {
+                start_timestamp: metric.start_timestamp,
+                timestamp: metric.timestamp,
+                raw_value,
+            };
+            // Only Sums are tracked; older spooled rows lack the flag
+            let monotonic = metric.is_monotonic.unwrap_or(true);
+            let (delta, state) = cumulative_delta(previous, current, monotonic);
+            metric.value = delta;
+            next.insert(key, state);
+        }
+
+        MetricRepository::insert_batch(db, &metrics).await?;
+        *series = next;
+
+        Ok(())
+    }
+}
+
+/// Delta between two cumulative points and the state to keep for the series
+///
+/// A changed start time, or a decreasing value of a monotonic series, means
+/// the counter was reset (e.g. the exporting process restarted), so the whole
+/// value is new. Non-monotonic series (up-down counters) may decrease, giving
+/// a negative delta. Points older than the last one seen contribute nothing.
+fn cumulative_delta(
+    previous: Option<SeriesState>,
+    current: SeriesState,
+    monotonic: bool,
+) -> (f64, SeriesState) {
+    let Some(previous) = previous else {
+        return (current.raw_value, current);
+    };
+
+    if current.timestamp <= previous.timestamp {
+        return (0.0, previous);
+    }
+
+    let reset = current.start_timestamp != previous.start_timestamp
+        || (monotonic && current.raw_value < previous.raw_value);
+    if reset {
+        return (current.raw_value, current);
+    }
+
+    (current.raw_value - previous.raw_value, current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(start: i64, timestamp: i64, raw_value: f64) -> SeriesState {
+        SeriesState {
+            start_timestamp: Some(start),
+            timestamp,
+            raw_value,
+        }
+    }
+
+    #[test]
+    fn test_cumulative_delta() {
+        // First point of a new series counts in full
+        let (delta, state) = cumulative_delta(None, point(0, 10, 5.0), true);
+        assert_eq!(delta, 5.0);
+
+        // Subsequent points count the increase only
+        let (delta, state) = cumulative_delta(Some(state), point(0, 20, 8.0), true);
+        assert_eq!(delta, 3.0);
+
+        // A retried (or older) point adds nothing
+        let (delta, state) = cumulative_delta(Some(state), point(0, 20, 8.0), true);
+        assert_eq!(delta, 0.0);
+        let (delta, state) = cumulative_delta(Some(state), point(0, 15, 6.0), true);
+        assert_eq!(delta, 0.0);
+        assert_eq!(state, point(0, 20, 8.0));
+
+        // A new start time is a reset
+        let (delta, state) = cumulative_delta(Some(state), point(30, 40, 2.0), true);
+        assert_eq!(delta, 2.0);
+
+        // So is a decreasing value with an unchanged start time
+        let (delta, _) = cumulative_delta(Some(state), point(30, 50, 1.0), true);
+        assert_eq!(delta, 1.0);
+    }
+
+    #[test]
+    fn test_non_monotonic_decrease_is_not_a_reset() {
+        let (delta, state) = cumulative_delta(None, point(0, 10, 5.0), false);
+        assert_eq!(delta, 5.0);
+
+        // An up-down counter going down reports the decrease
+        let (delta, state) = cumulative_delta(Some(state), point(0, 20, 3.0), false);
+        assert_eq!(delta, -2.0);
+        let (delta, state) = cumulative_delta(Some(state), point(0, 30, 4.0), false);
+        assert_eq!(delta, 1.0);
+
+        // A new start time is still a reset
+        let (delta, _) = cumulative_delta(Some(state), point(40, 50, 1.0), false);
+        assert_eq!(delta, 1.0);
+    }
+
+    fn cumulative(id: &str, timestamp: i64, raw_value: f64) -> NewMetric {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "session_id": "series-session",
+            "name": "claude_code.token.usage",
+            "timestamp": timestamp,
+            "value": raw_value,
+            "temporality": "cumulative",
+            "start_timestamp": 0,
+            "is_monotonic": true,
+            "raw_value": raw_value,
+            "series_key": "series",
+            "buckets": [],
+            "quantiles": [],
+        }))
+        .unwrap()
+    }
+
+    async fn temp_db() -> (SqlitePool, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("lumo-series-{}.db", uuid::Uuid::new_v4()));
+        let db = shared::create_pool(&path).await.unwrap();
+        shared::run_migrations(&db).await.unwrap();
+        (db, path)
+    }
+
+    #[tokio::test]
+    async fn test_failed_store_does_not_advance_series() {
+        let (db, path) = temp_db().await;
+        let (closed, closed_path) = temp_db().await;
+        closed.close().await;
+        let tracker = SeriesTracker::new();
+
+        tracker
+            .store(&db, &[cumulative("a", 10, 5.0)])
+            .await
+            .unwrap();
+        // The retried point is converted from the same state as the first try
+        let retried = [cumulative("b", 20, 8.0)];
+        assert!(tracker.store(&closed, &retried).await.is_err());
+        tracker.store(&db, &retried).await.unwrap();
+
+        let total: f64 = sqlx::query_scalar("SELECT SUM(value) FROM metrics")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(total, 8.0);
+
+        db.close().await;
+        for path in [path, closed_path] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
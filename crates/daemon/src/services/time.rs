@@ -0,0 +1,33 @@
+//! Time parsing
+//!
+//! Shared by the CLI (`--before`, `--since`, `--until`) and the query API
+//! (`since`, `until`), so both accept the same formats.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Parse a point in time into Unix milliseconds
+///
+/// Accepts a number of days ago (30d), Unix milliseconds, a UTC date
+/// (2025-01-31) or an RFC 3339 timestamp.
+pub fn parse_time(value: &str) -> Result<i64> {
+    if let Some(days) = value.strip_suffix('d') {
+        if let Ok(days) = days.parse::<i64>() {
+            return Ok(Utc::now().timestamp_millis() - days * 24 * 60 * 60 * 1000);
+        }
+    }
+    if let Ok(millis) = value.parse::<i64>() {
+        return Ok(millis);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date
+            .and_time(Default::default())
+            .and_utc()
+            .timestamp_millis());
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.timestamp_millis());
+    }
+
+    bail!("Invalid time: {}", value)
+}
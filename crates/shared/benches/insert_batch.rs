@@ -0,0 +1,163 @@
+//! Batch insert benchmark
+//!
+//! Compares one autocommit `INSERT` per row (the previous `insert_batch`
+//! behaviour, reproduced by calling `insert` in a loop) with the
+//! transactional multi-row `insert_batch`.
+//!
+//! Run with `cargo bench -p shared --bench insert_batch`.
+//! Set `LUMO_BENCH_ROWS` to change the number of rows per run.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use shared::{create_pool, run_migrations, EventRepository, MetricRepository, NewEvent, NewMetric};
+use sqlx::SqlitePool;
+
+const DEFAULT_ROWS: usize = 5_000;
+
+fn new_event(i: usize) -> NewEvent {
+    NewEvent {
+        id: format!("event-{i}"),
+        session_id: format!("session-{}", i % 20),
+        name: "claude_code.api_request".to_string(),
+        timestamp: 1_700_000_000_000 + i as i64,
+        duration_ms: Some(1_200),
+        success: Some(true),
+        error: None,
+        model: Some("claude-sonnet-4-5".to_string()),
+        cost_usd: Some(0.0123),
+        input_tokens: Some(1_000),
+        output_tokens: Some(250),
+        cache_read_tokens: Some(4_000),
+        cache_creation_tokens: Some(0),
+        status_code: None,
+        attempt: None,
+        tool_name: None,
+        tool_decision: None,
+        decision_source: None,
+        tool_parameters: None,
+        prompt_length: None,
+        prompt: None,
+        account_uuid: None,
+        organization_id: None,
+        terminal_type: Some("vscode".to_string()),
+        app_version: Some("2.0.0".to_string()),
+        resource: Some(r#"{"service.name":"claude-code"}"#.to_string()),
+        user_id: None,
+        user_email: None,
+        event_sequence: Some(i as i64),
+        tool_result_size_bytes: None,
+        attributes: Some(r#"{"model":"claude-sonnet-4-5"}"#.to_string()),
+        source: Some("claude_code".to_string()),
+    }
+}
+
+fn new_metric(i: usize) -> NewMetric {
+    NewMetric {
+        id: format!("metric-{i}"),
+        session_id: format!("session-{}", i % 20),
+        name: "claude_code.token.usage".to_string(),
+        timestamp: 1_700_000_000_000 + i as i64,
+        value: 42.0,
+        metric_type: Some("input".to_string()),
+        model: Some("claude-sonnet-4-5".to_string()),
+        tool: None,
+        decision: None,
+        language: None,
+        account_uuid: None,
+        organization_id: None,
+        terminal_type: Some("vscode".to_string()),
+        app_version: Some("2.0.0".to_string()),
+        resource: Some(r#"{"service.name":"claude-code"}"#.to_string()),
+        user_id: None,
+        user_email: None,
+        unit: Some("tokens".to_string()),
+        description: None,
+        data_type: Some("sum".to_string()),
+        count: None,
+        min: None,
+        max: None,
+        temporality: Some("delta".to_string()),
+        start_timestamp: None,
+        is_monotonic: Some(true),
+        raw_value: Some(42.0),
+        series_key: None,
+        attributes: Some(r#"{"type":"input"}"#.to_string()),
+        source: Some("claude_code".to_string()),
+        buckets: Vec::new(),
+        quantiles: Vec::new(),
+    }
+}
+
+async fn fresh_pool(label: &str) -> (SqlitePool, PathBuf) {
+    let path = std::env::temp_dir().join(format!("lumo-bench-{}-{}.db", label, std::process::id()));
+    remove_db(&path);
+    let pool = create_pool(&path).await.expect("create pool");
+    run_migrations(&pool).await.expect("run migrations");
+    (pool, path)
+}
+
+fn remove_db(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
+
+fn report(label: &str, rows: usize, elapsed: Duration) {
+    println!(
+        "{:<28} {:>7} rows in {:>9.2?}  ({:>10.0} rows/sec)",
+        label,
+        rows,
+        elapsed,
+        rows as f64 / elapsed.as_secs_f64()
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let rows = std::env::var("LUMO_BENCH_ROWS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_ROWS);
+
+    let events: Vec<NewEvent> = (0..rows).map(new_event).collect();
+    let metrics: Vec<NewMetric> = (0..rows).map(new_metric).collect();
+
+    let (pool, path) = fresh_pool("events-row").await;
+    let start = Instant::now();
+    for event in &events {
+        EventRepository::insert(&pool, event).await.expect("insert");
+    }
+    report("events: row by row", rows, start.elapsed());
+    pool.close().await;
+    remove_db(&path);
+
+    let (pool, path) = fresh_pool("events-batch").await;
+    let start = Instant::now();
+    EventRepository::insert_batch(&pool, &events)
+        .await
+        .expect("insert_batch");
+    report("events: insert_batch", rows, start.elapsed());
+    pool.close().await;
+    remove_db(&path);
+
+    let (pool, path) = fresh_pool("metrics-row").await;
+    let start = Instant::now();
+    for metric in &metrics {
+        MetricRepository::insert(&pool, metric)
+            .await
+            .expect("insert");
+    }
+    report("metrics: row by row", rows, start.elapsed());
+    pool.close().await;
+    remove_db(&path);
+
+    let (pool, path) = fresh_pool("metrics-batch").await;
+    let start = Instant::now();
+    MetricRepository::insert_batch(&pool, &metrics)
+        .await
+        .expect("insert_batch");
+    report("metrics: insert_batch", rows, start.elapsed());
+    pool.close().await;
+    remove_db(&path);
+}
@@ -0,0 +1,41 @@
+-- Spans table for OTLP traces
+-- Stores request/tool call trees sent by Claude Code or wrapping tools
+
+CREATE TABLE IF NOT EXISTS spans (
+    -- Identification (hex-encoded OTLP IDs)
+    trace_id TEXT NOT NULL,
+    span_id TEXT NOT NULL,
+    parent_span_id TEXT,                 -- NULL for root spans
+
+    -- Session identifier (from session.id span or resource attribute)
+    session_id TEXT NOT NULL,
+
+    name TEXT NOT NULL,
+    kind TEXT,                           -- "internal", "server", "client", "producer", "consumer"
+
+    -- Timing (Unix milliseconds; duration keeps sub-millisecond precision)
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    duration_ms REAL NOT NULL,
+
+    -- Status
+    status_code TEXT,                    -- "unset", "ok", "error"
+    status_message TEXT,
+
+    -- Span attributes and span events (JSON)
+    attributes TEXT,
+    events TEXT,
+
+    -- Instrumentation scope and resource attributes (JSON)
+    scope_name TEXT,
+    resource TEXT,
+
+    -- Metadata
+    received_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    PRIMARY KEY (trace_id, span_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_spans_session_id ON spans(session_id);
+CREATE INDEX IF NOT EXISTS idx_spans_start_time ON spans(start_time DESC);
+CREATE INDEX IF NOT EXISTS idx_spans_parent ON spans(trace_id, parent_span_id);
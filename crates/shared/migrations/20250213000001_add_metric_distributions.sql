@@ -0,0 +1,24 @@
+-- Metrics: keep the full distribution of histogram-like data points
+ALTER TABLE metrics ADD COLUMN data_type TEXT;  -- "sum", "gauge", "histogram", "exponential_histogram", "summary"
+ALTER TABLE metrics ADD COLUMN count INTEGER;   -- number of observations (histogram/summary)
+ALTER TABLE metrics ADD COLUMN min REAL;
+ALTER TABLE metrics ADD COLUMN max REAL;
+
+-- Histogram buckets, one row per non-empty bucket
+-- Exponential histogram buckets are stored with their computed boundaries
+CREATE TABLE IF NOT EXISTS metric_buckets (
+    metric_id TEXT NOT NULL REFERENCES metrics(id) ON DELETE CASCADE,
+    bucket_index INTEGER NOT NULL,       -- position in ascending value order
+    lower_bound REAL,                    -- NULL = -infinity
+    upper_bound REAL,                    -- NULL = +infinity
+    count INTEGER NOT NULL,
+    PRIMARY KEY (metric_id, bucket_index)
+);
+
+-- Summary quantiles as reported by the exporter
+CREATE TABLE IF NOT EXISTS metric_quantiles (
+    metric_id TEXT NOT NULL REFERENCES metrics(id) ON DELETE CASCADE,
+    quantile REAL NOT NULL,              -- 0.0 ..= 1.0
+    value REAL NOT NULL,
+    PRIMARY KEY (metric_id, quantile)
+);
@@ -0,0 +1,9 @@
+-- Metrics: track aggregation temporality of Sum data points
+-- `value` always holds the delta for the interval; cumulative points are
+-- converted by the daemon and the reported value is kept in `raw_value`.
+ALTER TABLE metrics ADD COLUMN temporality TEXT;        -- "delta", "cumulative"
+ALTER TABLE metrics ADD COLUMN start_timestamp INTEGER; -- series start (Unix ms)
+ALTER TABLE metrics ADD COLUMN raw_value REAL;          -- value as reported by the exporter
+ALTER TABLE metrics ADD COLUMN series_key TEXT;         -- identity of name + attributes + resource
+
+CREATE INDEX IF NOT EXISTS idx_metrics_series ON metrics(series_key, timestamp DESC);
@@ -0,0 +1,36 @@
+-- Collapse duplicate rows left behind by exporter retries
+--
+-- Rows inserted before IDs became content-derived received a random ID on
+-- every retry. Keep the first copy of each (identical) row and drop the rest.
+
+DELETE FROM events
+WHERE rowid NOT IN (
+    SELECT MIN(rowid)
+    FROM events
+    GROUP BY
+        session_id, name, timestamp, event_sequence,
+        duration_ms, success, error,
+        model, cost_usd, input_tokens, output_tokens,
+        cache_read_tokens, cache_creation_tokens, status_code, attempt,
+        tool_name, tool_decision, decision_source, tool_parameters,
+        prompt_length, prompt,
+        account_uuid, organization_id, terminal_type, app_version,
+        resource, user_id, user_email, tool_result_size_bytes
+);
+
+DELETE FROM metrics
+WHERE rowid NOT IN (
+    SELECT MIN(rowid)
+    FROM metrics
+    GROUP BY
+        session_id, name, timestamp, value,
+        metric_type, model, tool, decision, language,
+        account_uuid, organization_id, terminal_type, app_version,
+        resource, user_id, user_email, unit,
+        data_type, count, min, max,
+        temporality, start_timestamp, raw_value, series_key
+);
+
+-- Buckets and quantiles of removed metrics
+DELETE FROM metric_buckets WHERE metric_id NOT IN (SELECT id FROM metrics);
+DELETE FROM metric_quantiles WHERE metric_id NOT IN (SELECT id FROM metrics);
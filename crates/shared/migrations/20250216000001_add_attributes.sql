@@ -0,0 +1,5 @@
+-- Keep every OTLP attribute, including ones without a dedicated column
+-- JSON object of the log record / data point attributes; arrays and
+-- key-value lists are preserved as JSON arrays and objects.
+ALTER TABLE events ADD COLUMN attributes TEXT;
+ALTER TABLE metrics ADD COLUMN attributes TEXT;
@@ -0,0 +1,19 @@
+-- Catalog of attribute keys seen per event / metric name
+-- Lets schema drift across Claude Code versions be spotted
+
+CREATE TABLE IF NOT EXISTS attribute_catalog (
+    signal TEXT NOT NULL,                -- "log" or "metric"
+    name TEXT NOT NULL,                  -- event or metric name
+    attribute_key TEXT NOT NULL,
+    value_type TEXT NOT NULL,            -- "string", "int", "double", "bool", "bytes", "array", "kvlist", "empty"
+    app_version TEXT NOT NULL DEFAULT '', -- '' when the record has no app.version
+
+    -- Unix milliseconds of the earliest and latest record carrying the key
+    first_seen INTEGER NOT NULL,
+    last_seen INTEGER NOT NULL,
+    count INTEGER NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (signal, name, attribute_key, value_type, app_version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_attribute_catalog_first_seen ON attribute_catalog(first_seen DESC);
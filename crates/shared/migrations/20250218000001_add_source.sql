@@ -0,0 +1,56 @@
+-- Coding agent that sent each event and metric
+-- Derived from the service.name resource attribute: "claude_code", "codex",
+-- "gemini_cli", or the normalized service name for other agents.
+
+ALTER TABLE events ADD COLUMN source TEXT;
+ALTER TABLE metrics ADD COLUMN source TEXT;
+
+-- Existing rows were all parsed as Claude Code telemetry; only records whose
+-- resource names another agent are attributed to it
+UPDATE events SET source = CASE
+    WHEN json_extract(resource, '$."service.name"') LIKE 'codex%' THEN 'codex'
+    WHEN json_extract(resource, '$."service.name"') IN ('gemini-cli', 'gemini_cli') THEN 'gemini_cli'
+    ELSE 'claude_code'
+END;
+UPDATE metrics SET source = CASE
+    WHEN json_extract(resource, '$."service.name"') LIKE 'codex%' THEN 'codex'
+    WHEN json_extract(resource, '$."service.name"') IN ('gemini-cli', 'gemini_cli') THEN 'gemini_cli'
+    ELSE 'claude_code'
+END;
+
+CREATE INDEX IF NOT EXISTS idx_events_source_timestamp ON events(source, timestamp);
+CREATE INDEX IF NOT EXISTS idx_metrics_source_timestamp ON metrics(source, timestamp);
+
+-- Expose the source on sessions
+DROP VIEW IF EXISTS sessions;
+CREATE VIEW sessions AS
+SELECT
+    session_id AS id,
+
+    -- Time range
+    MIN(timestamp) AS start_time,
+    MAX(timestamp) AS end_time,
+    MAX(timestamp) - MIN(timestamp) AS duration_ms,
+
+    -- Counts
+    COUNT(*) AS event_count,
+    COUNT(CASE WHEN name = 'claude_code.api_request' THEN 1 END) AS api_request_count,
+    COUNT(CASE WHEN name = 'claude_code.api_error' THEN 1 END) AS error_count,
+    COUNT(CASE WHEN name = 'claude_code.tool_result' THEN 1 END) AS tool_use_count,
+    COUNT(CASE WHEN name = 'claude_code.user_prompt' THEN 1 END) AS prompt_count,
+
+    -- Totals from api_request events
+    COALESCE(SUM(CASE WHEN name = 'claude_code.api_request' THEN cost_usd ELSE 0 END), 0) AS total_cost_usd,
+    COALESCE(SUM(CASE WHEN name = 'claude_code.api_request' THEN input_tokens ELSE 0 END), 0) AS total_input_tokens,
+    COALESCE(SUM(CASE WHEN name = 'claude_code.api_request' THEN output_tokens ELSE 0 END), 0) AS total_output_tokens,
+    COALESCE(SUM(CASE WHEN name = 'claude_code.api_request' THEN cache_read_tokens ELSE 0 END), 0) AS total_cache_read_tokens,
+
+    -- Metadata (take the most recent non-null value)
+    MAX(account_uuid) AS account_uuid,
+    MAX(organization_id) AS organization_id,
+    MAX(terminal_type) AS terminal_type,
+    MAX(app_version) AS app_version,
+    MAX(source) AS source
+
+FROM events
+GROUP BY session_id;
@@ -0,0 +1,120 @@
+-- Quarter-hour rollups for dashboard queries
+--
+-- Triggers keep the rollups up to date as rows are inserted, so dashboards
+-- sum a few rows per quarter hour instead of scanning raw telemetry. Rows
+-- skipped by `INSERT OR IGNORE` do not fire the triggers, and deleting raw
+-- rows (e.g. by retention) leaves the rollups untouched.
+--
+-- `bucket` is the start of the UTC quarter hour in Unix milliseconds. Every
+-- time zone offset is a multiple of 15 minutes, so buckets group exactly by
+-- local hour and day, also in zones with half-hour offsets. Missing models,
+-- sources and metric attributes are stored as '' and missing HTTP status
+-- codes as 0 to keep them in the key.
+
+CREATE TABLE IF NOT EXISTS event_rollups (
+    bucket INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    model TEXT NOT NULL DEFAULT '',
+    source TEXT NOT NULL DEFAULT '',
+    status_code INTEGER NOT NULL DEFAULT 0,
+
+    event_count INTEGER NOT NULL DEFAULT 0,
+    cost_usd REAL NOT NULL DEFAULT 0,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (bucket, name, model, source, status_code)
+) WITHOUT ROWID;
+
+CREATE TABLE IF NOT EXISTS metric_rollups (
+    bucket INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    metric_type TEXT NOT NULL DEFAULT '',
+    decision TEXT NOT NULL DEFAULT '',
+    source TEXT NOT NULL DEFAULT '',
+
+    sample_count INTEGER NOT NULL DEFAULT 0,
+    value REAL NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (bucket, name, metric_type, decision, source)
+) WITHOUT ROWID;
+
+-- Sessions with at least one event in each quarter hour
+CREATE TABLE IF NOT EXISTS session_hours (
+    bucket INTEGER NOT NULL,
+    session_id TEXT NOT NULL,
+    source TEXT NOT NULL DEFAULT '',
+
+    PRIMARY KEY (bucket, session_id, source)
+) WITHOUT ROWID;
+
+CREATE TRIGGER IF NOT EXISTS events_rollup AFTER INSERT ON events
+BEGIN
+    INSERT INTO event_rollups (
+        bucket, name, model, source, status_code,
+        event_count, cost_usd, input_tokens, output_tokens,
+        cache_read_tokens, cache_creation_tokens
+    ) VALUES (
+        NEW.timestamp - NEW.timestamp % 900000, NEW.name,
+        COALESCE(NEW.model, ''), COALESCE(NEW.source, ''), COALESCE(NEW.status_code, 0),
+        1, COALESCE(NEW.cost_usd, 0), COALESCE(NEW.input_tokens, 0),
+        COALESCE(NEW.output_tokens, 0), COALESCE(NEW.cache_read_tokens, 0),
+        COALESCE(NEW.cache_creation_tokens, 0)
+    )
+    ON CONFLICT (bucket, name, model, source, status_code) DO UPDATE SET
+        event_count = event_count + 1,
+        cost_usd = cost_usd + excluded.cost_usd,
+        input_tokens = input_tokens + excluded.input_tokens,
+        output_tokens = output_tokens + excluded.output_tokens,
+        cache_read_tokens = cache_read_tokens + excluded.cache_read_tokens,
+        cache_creation_tokens = cache_creation_tokens + excluded.cache_creation_tokens;
+
+    INSERT OR IGNORE INTO session_hours (bucket, session_id, source)
+    VALUES (NEW.timestamp - NEW.timestamp % 900000, NEW.session_id, COALESCE(NEW.source, ''));
+END;
+
+CREATE TRIGGER IF NOT EXISTS metrics_rollup AFTER INSERT ON metrics
+BEGIN
+    INSERT INTO metric_rollups (
+        bucket, name, metric_type, decision, source, sample_count, value
+    ) VALUES (
+        NEW.timestamp - NEW.timestamp % 900000, NEW.name,
+        COALESCE(NEW.metric_type, ''), COALESCE(NEW.decision, ''),
+        COALESCE(NEW.source, ''), 1, COALESCE(NEW.value, 0)
+    )
+    ON CONFLICT (bucket, name, metric_type, decision, source) DO UPDATE SET
+        sample_count = sample_count + 1,
+        value = value + excluded.value;
+END;
+
+-- Backfill from existing rows
+INSERT INTO event_rollups (
+    bucket, name, model, source, status_code,
+    event_count, cost_usd, input_tokens, output_tokens,
+    cache_read_tokens, cache_creation_tokens
+)
+SELECT
+    timestamp - timestamp % 900000 AS bucket, name,
+    COALESCE(model, '') AS model, COALESCE(source, '') AS source,
+    COALESCE(status_code, 0) AS status_code,
+    COUNT(*), COALESCE(SUM(cost_usd), 0), COALESCE(SUM(input_tokens), 0),
+    COALESCE(SUM(output_tokens), 0), COALESCE(SUM(cache_read_tokens), 0),
+    COALESCE(SUM(cache_creation_tokens), 0)
+FROM events
+GROUP BY 1, 2, 3, 4, 5;
+
+INSERT INTO metric_rollups (
+    bucket, name, metric_type, decision, source, sample_count, value
+)
+SELECT
+    timestamp - timestamp % 900000 AS bucket, name,
+    COALESCE(metric_type, ''), COALESCE(decision, ''), COALESCE(source, ''),
+    COUNT(*), COALESCE(SUM(value), 0)
+FROM metrics
+GROUP BY 1, 2, 3, 4, 5;
+
+INSERT INTO session_hours (bucket, session_id, source)
+SELECT DISTINCT timestamp - timestamp % 900000, session_id, COALESCE(source, '')
+FROM events;
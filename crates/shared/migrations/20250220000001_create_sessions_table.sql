@@ -0,0 +1,122 @@
+-- Replace the sessions VIEW with a table
+--
+-- The view re-aggregated every event on each query. The daemon now
+-- recomputes the sessions touched by each event batch, and Claude Code hooks
+-- set the working directory and status:
+--   active  events are arriving
+--   idle    a Stop hook fired (Claude finished its turn)
+--   ended   a SessionEnd hook fired
+-- A session becomes active again when events newer than its status arrive.
+-- Deleting raw events (e.g. by retention) keeps the session rows.
+
+DROP VIEW IF EXISTS sessions;
+
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+
+    -- Time range
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+
+    -- Counts
+    event_count INTEGER NOT NULL DEFAULT 0,
+    api_request_count INTEGER NOT NULL DEFAULT 0,
+    error_count INTEGER NOT NULL DEFAULT 0,
+    tool_use_count INTEGER NOT NULL DEFAULT 0,
+    prompt_count INTEGER NOT NULL DEFAULT 0,
+
+    -- Totals from api_request events
+    total_cost_usd REAL NOT NULL DEFAULT 0,
+    total_input_tokens INTEGER NOT NULL DEFAULT 0,
+    total_output_tokens INTEGER NOT NULL DEFAULT 0,
+    total_cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+    total_cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+
+    -- JSON array of the distinct models used, sorted
+    models TEXT NOT NULL DEFAULT '[]',
+    last_event_name TEXT,
+
+    -- Metadata (the greatest non-null value, as the view reported)
+    account_uuid TEXT,
+    organization_id TEXT,
+    terminal_type TEXT,
+    app_version TEXT,
+    source TEXT,
+
+    -- From hooks
+    cwd TEXT,
+    status TEXT NOT NULL DEFAULT 'active',   -- "active", "idle" or "ended"
+    status_changed_at INTEGER                -- Unix ms
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_start_time ON sessions(start_time DESC);
+CREATE INDEX IF NOT EXISTS idx_sessions_end_time ON sessions(end_time);
+CREATE INDEX IF NOT EXISTS idx_events_session_timestamp ON events(session_id, timestamp);
+CREATE INDEX IF NOT EXISTS idx_notifications_session_id ON notifications(session_id);
+
+-- Backfill from existing events and hook notifications; sessions that did
+-- not report their end are considered idle
+INSERT INTO sessions (
+    id, start_time, end_time, duration_ms,
+    event_count, api_request_count, error_count, tool_use_count, prompt_count,
+    total_cost_usd, total_input_tokens, total_output_tokens,
+    total_cache_read_tokens, total_cache_creation_tokens,
+    models, last_event_name,
+    account_uuid, organization_id, terminal_type, app_version, source,
+    cwd, status, status_changed_at
+)
+SELECT
+    e.session_id,
+    MIN(e.timestamp),
+    MAX(e.timestamp),
+    MAX(e.timestamp) - MIN(e.timestamp),
+
+    COUNT(*),
+    COUNT(CASE WHEN e.name = 'claude_code.api_request' THEN 1 END),
+    COUNT(CASE WHEN e.name = 'claude_code.api_error' THEN 1 END),
+    COUNT(CASE WHEN e.name = 'claude_code.tool_result' THEN 1 END),
+    COUNT(CASE WHEN e.name = 'claude_code.user_prompt' THEN 1 END),
+
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.cost_usd END), 0),
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.input_tokens END), 0),
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.output_tokens END), 0),
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.cache_read_tokens END), 0),
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.cache_creation_tokens END), 0),
+
+    (
+        SELECT json_group_array(model) FROM (
+            SELECT DISTINCT m.model FROM events m
+            WHERE m.session_id = e.session_id AND m.model IS NOT NULL
+            ORDER BY m.model
+        )
+    ),
+    (
+        SELECT l.name FROM events l
+        WHERE l.session_id = e.session_id
+        ORDER BY l.timestamp DESC, l.event_sequence DESC
+        LIMIT 1
+    ),
+
+    MAX(e.account_uuid),
+    MAX(e.organization_id),
+    MAX(e.terminal_type),
+    MAX(e.app_version),
+    MAX(e.source),
+
+    (
+        SELECT n.cwd FROM notifications n
+        WHERE n.session_id = e.session_id AND n.cwd IS NOT NULL
+        ORDER BY n.created_at DESC
+        LIMIT 1
+    ),
+    CASE
+        WHEN EXISTS (
+            SELECT 1 FROM notifications n
+            WHERE n.session_id = e.session_id AND n.hook_event = 'SessionEnd'
+        ) THEN 'ended'
+        ELSE 'idle'
+    END,
+    MAX(e.timestamp)
+FROM events e
+GROUP BY e.session_id;
@@ -0,0 +1,65 @@
+//! Daemon authentication
+//!
+//! The daemon only accepts telemetry from clients that present the shared
+//! secret stored in `~/.lumo/auth-token`, or that connect over its Unix
+//! socket. Whichever of the daemon and the desktop app starts first
+//! generates the token; the app writes it into the Claude Code settings.
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::database::connection::get_db_path;
+use crate::error::Result;
+
+/// Get the authentication token file path (~/.lumo/auth-token)
+pub fn get_token_path() -> Result<PathBuf> {
+    Ok(get_db_path()?.with_file_name("auth-token"))
+}
+
+/// Get the daemon Unix socket path (~/.lumo/run/daemon.sock)
+///
+/// Connections over the socket skip token authentication; the socket file is
+/// only accessible to the current user.
+pub fn get_socket_path() -> Result<PathBuf> {
+    Ok(get_db_path()?.with_file_name("run").join("daemon.sock"))
+}
+
+/// Read the authentication token, generating it on first use
+///
+/// The file is created readable by the current user only.
+pub fn load_or_create_token() -> Result<String> {
+    let path = get_token_path()?;
+
+    match fs::read_to_string(&path) {
+        Ok(token) if !token.trim().is_empty() => return Ok(token.trim().to_string()),
+        Ok(_) => fs::remove_file(&path)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let token = uuid::Uuid::new_v4().simple().to_string();
+
+    // Write to a temporary file and link it into place, so a concurrent
+    // reader never sees a partially written token
+    let tmp_path = path.with_extension(format!("{}.tmp", token));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(&tmp_path)?.write_all(token.as_bytes())?;
+
+    let linked = fs::hard_link(&tmp_path, &path);
+    fs::remove_file(&tmp_path)?;
+    match linked {
+        Ok(()) => Ok(token),
+        // The other process created it first
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            Ok(fs::read_to_string(&path)?.trim().to_string())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
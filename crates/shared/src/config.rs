@@ -0,0 +1,356 @@
+//! Daemon configuration
+//!
+//! Settings are layered: built-in defaults, then `~/.lumo/daemon.toml`, then
+//! environment variables. Resolved here so the daemon and the desktop app,
+//! which writes the Claude Code settings pointing at the daemon, agree on
+//! them.
+
+use serde::Deserialize;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// Default HTTP listening address
+pub const DEFAULT_SERVER_ADDRESS: &str = "127.0.0.1:4318";
+
+/// Default maximum OTLP request body size (after decompression): 16 MiB
+pub const DEFAULT_MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default number of parsed exports buffered in memory before spooling to disk
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct DaemonConfig {
+    /// Config file the settings were read from (e.g. "~/.lumo/daemon.toml")
+    pub config_path: PathBuf,
+
+    /// Server listening address (e.g., "127.0.0.1:4318"); `None` serves the
+    /// Unix socket only
+    pub server_address: Option<String>,
+
+    /// OTLP/gRPC listening address (e.g., "127.0.0.1:4317"); `None` (the
+    /// default) disables gRPC, leaving the port to a local collector
+    pub grpc_address: Option<String>,
+
+    /// Log level (e.g., "info", "debug", "trace")
+    pub log_level: String,
+
+    /// Maximum OTLP request body size in bytes, measured after decompression
+    pub max_body_bytes: usize,
+
+    /// Number of parsed exports buffered in memory before spooling to disk
+    pub queue_capacity: usize,
+
+    /// Spool file for exports that could not be written yet (e.g. "~/.lumo/spool.jsonl")
+    pub spool_path: PathBuf,
+
+    /// Optional attribute mapping file (e.g. "~/.lumo/attribute-mapping.json")
+    pub attribute_mapping_path: PathBuf,
+
+    /// Optional redaction policy file (e.g. "~/.lumo/redaction.json")
+    pub redaction_path: PathBuf,
+
+    /// Optional retention policy file (e.g. "~/.lumo/retention.json")
+    pub retention_path: PathBuf,
+
+    /// Require the bearer token from `~/.lumo/auth-token` on all endpoints but
+    /// `/health`; off unless enabled
+    pub auth_enabled: bool,
+
+    /// Unix socket serving the same endpoints without token authentication
+    /// (e.g. "~/.lumo/run/daemon.sock"); `None` disables it
+    pub socket_path: Option<PathBuf>,
+}
+
+impl DaemonConfig {
+    /// Config file used unless `LUMO_CONFIG` names another one
+    pub fn default_path() -> Result<PathBuf> {
+        match env::var("LUMO_CONFIG") {
+            Ok(value) => Ok(PathBuf::from(value)),
+            Err(_) => Ok(crate::get_db_path()?.with_file_name("daemon.toml")),
+        }
+    }
+
+    /// Load configuration from `~/.lumo/daemon.toml` (or `LUMO_CONFIG`) and
+    /// environment variables
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load configuration from the given file and environment variables
+    ///
+    /// A missing file leaves every setting at its default.
+    pub fn load_from(config_path: &Path) -> Result<Self> {
+        let file = match std::fs::read_to_string(config_path) {
+            Ok(contents) => toml::from_str(&contents).map_err(|e| {
+                Error::Config(format!(
+                    "Invalid config file {}: {}",
+                    config_path.display(),
+                    e
+                ))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ConfigFile::default(),
+            Err(e) => {
+                return Err(Error::Config(format!(
+                    "Failed to read config file {}: {}",
+                    config_path.display(),
+                    e
+                )))
+            }
+        };
+
+        Self::from_layers(config_path, file, |key| env::var(key).ok())
+    }
+
+    /// Layer environment variables (read through `var`) over the file over
+    /// the defaults
+    fn from_layers(
+        config_path: &Path,
+        file: ConfigFile,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        // "off" leaves only the Unix socket listener
+        let server_address = match var("LUMO_SERVER_ADDRESS").or(file.server_address) {
+            Some(value) if is_off(&value) => None,
+            Some(value) => Some(value),
+            None => Some(DEFAULT_SERVER_ADDRESS.to_string()),
+        };
+
+        // The gRPC receiver is opt-in: 4317 is the standard port of an
+        // OpenTelemetry collector that may already run locally
+        let grpc_address = var("LUMO_GRPC_ADDRESS")
+            .or(file.grpc_address)
+            .filter(|value| !is_off(value));
+
+        let log_level = var("RUST_LOG")
+            .or(file.log_level)
+            .unwrap_or_else(|| "lumo_daemon=info,tower_http=info".to_string());
+
+        let max_body_bytes = match var("LUMO_MAX_BODY_BYTES") {
+            Some(value) => value.parse().map_err(|_| {
+                Error::Config("LUMO_MAX_BODY_BYTES must be a number of bytes".to_string())
+            })?,
+            None => file.max_body_bytes.unwrap_or(DEFAULT_MAX_BODY_BYTES),
+        };
+
+        let queue_capacity = match var("LUMO_QUEUE_CAPACITY") {
+            Some(value) => value.parse().map_err(|_| {
+                Error::Config("LUMO_QUEUE_CAPACITY must be a number of batches".to_string())
+            })?,
+            None => file.queue_capacity.unwrap_or(DEFAULT_QUEUE_CAPACITY),
+        };
+
+        let spool_path = match var("LUMO_SPOOL_PATH")
+            .map(PathBuf::from)
+            .or(file.spool_path)
+        {
+            Some(path) => path,
+            None => crate::get_db_path()?.with_file_name("spool.jsonl"),
+        };
+
+        let attribute_mapping_path = match var("LUMO_ATTRIBUTE_MAPPING")
+            .map(PathBuf::from)
+            .or(file.attribute_mapping_path)
+        {
+            Some(path) => path,
+            None => crate::get_db_path()?.with_file_name("attribute-mapping.json"),
+        };
+
+        let redaction_path = match var("LUMO_REDACTION_CONFIG")
+            .map(PathBuf::from)
+            .or(file.redaction_path)
+        {
+            Some(path) => path,
+            None => crate::get_db_path()?.with_file_name("redaction.json"),
+        };
+
+        let retention_path = match var("LUMO_RETENTION_CONFIG")
+            .map(PathBuf::from)
+            .or(file.retention_path)
+        {
+            Some(path) => path,
+            None => crate::get_retention_path()?,
+        };
+
+        // Authentication is opt-in, so exporters configured without the
+        // token keep working until it is turned on
+        let auth_enabled = match var("LUMO_AUTH") {
+            Some(value) => !matches!(value.to_ascii_lowercase().as_str(), "off" | "false" | "0"),
+            None => file.auth.unwrap_or(false),
+        };
+
+        // An empty value or "off" disables the Unix socket listener
+        let socket_path = match var("LUMO_SOCKET_PATH").or(file.socket_path) {
+            Some(value) if is_off(&value) => None,
+            Some(value) => Some(PathBuf::from(value)),
+            None if cfg!(unix) => Some(crate::get_socket_path()?),
+            None => None,
+        };
+
+        Ok(Self {
+            config_path: config_path.to_path_buf(),
+            server_address,
+            grpc_address,
+            log_level,
+            max_body_bytes,
+            queue_capacity,
+            spool_path,
+            attribute_mapping_path,
+            redaction_path,
+            retention_path,
+            auth_enabled,
+            socket_path,
+        })
+    }
+
+    /// Settings that differ from `other` and only take effect on restart
+    pub fn restart_required(&self, other: &DaemonConfig) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.server_address != other.server_address {
+            changed.push("server_address");
+        }
+        if self.grpc_address != other.grpc_address {
+            changed.push("grpc_address");
+        }
+        if self.max_body_bytes != other.max_body_bytes {
+            changed.push("max_body_bytes");
+        }
+        if self.queue_capacity != other.queue_capacity {
+            changed.push("queue_capacity");
+        }
+        if self.spool_path != other.spool_path {
+            changed.push("spool_path");
+        }
+        if self.attribute_mapping_path != other.attribute_mapping_path {
+            changed.push("attribute_mapping_path");
+        }
+        if self.auth_enabled != other.auth_enabled {
+            changed.push("auth");
+        }
+        if self.socket_path != other.socket_path {
+            changed.push("socket_path");
+        }
+        changed
+    }
+
+    /// Validate configuration
+    ///
+    /// The log level is not checked here; the daemon validates it against its
+    /// log filter syntax.
+    pub fn validate(&self) -> Result<()> {
+        // Parse address to ensure it's valid
+        if let Some(server_address) = &self.server_address {
+            server_address
+                .parse::<std::net::SocketAddr>()
+                .map_err(|e| Error::Config(format!("Invalid server address: {}", e)))?;
+        } else if self.socket_path.is_none() {
+            return Err(Error::Config(
+                "The server address and the Unix socket cannot both be off".to_string(),
+            ));
+        }
+
+        if let Some(grpc_address) = &self.grpc_address {
+            grpc_address
+                .parse::<std::net::SocketAddr>()
+                .map_err(|e| Error::Config(format!("Invalid gRPC address: {}", e)))?;
+        }
+
+        if !cfg!(unix) && self.socket_path.is_some() {
+            return Err(Error::Config(
+                "Unix socket listener is only supported on Unix".to_string(),
+            ));
+        }
+
+        if self.max_body_bytes == 0 {
+            return Err(Error::Config(
+                "Maximum body size must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.queue_capacity == 0 {
+            return Err(Error::Config(
+                "Queue capacity must be greater than zero".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Contents of `daemon.toml`; every setting is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    /// "off" serves the Unix socket only
+    server_address: Option<String>,
+    /// "off" disables the gRPC receiver
+    grpc_address: Option<String>,
+    log_level: Option<String>,
+    max_body_bytes: Option<usize>,
+    queue_capacity: Option<usize>,
+    spool_path: Option<PathBuf>,
+    attribute_mapping_path: Option<PathBuf>,
+    redaction_path: Option<PathBuf>,
+    retention_path: Option<PathBuf>,
+    auth: Option<bool>,
+    /// "off" disables the Unix socket listener
+    socket_path: Option<String>,
+}
+
+/// Whether an address setting turns its listener off
+fn is_off(value: &str) -> bool {
+    value.is_empty() || value.eq_ignore_ascii_case("off")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_overrides_file_overrides_defaults() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+                server_address = "127.0.0.1:5318"
+                grpc_address = "off"
+                log_level = "lumo_daemon=debug"
+                queue_capacity = 16
+            "#,
+        )
+        .unwrap();
+        let config = DaemonConfig::from_layers(Path::new("daemon.toml"), file, |key| {
+            (key == "LUMO_QUEUE_CAPACITY").then(|| "32".to_string())
+        })
+        .unwrap();
+
+        assert_eq!(config.server_address.as_deref(), Some("127.0.0.1:5318"));
+        assert_eq!(config.grpc_address, None);
+        assert_eq!(config.log_level, "lumo_daemon=debug");
+        assert_eq!(config.queue_capacity, 32);
+        assert_eq!(config.max_body_bytes, DEFAULT_MAX_BODY_BYTES);
+        assert!(!config.auth_enabled);
+        config.validate().unwrap();
+
+        assert!(toml::from_str::<ConfigFile>("queue_capcity = 16").is_err());
+    }
+
+    #[test]
+    fn test_server_address_off_needs_the_socket() {
+        let file: ConfigFile = toml::from_str(r#"socket_path = "/tmp/lumo.sock""#).unwrap();
+        let config = DaemonConfig::from_layers(Path::new("daemon.toml"), file, |key| {
+            (key == "LUMO_SERVER_ADDRESS").then(|| "off".to_string())
+        })
+        .unwrap();
+        assert_eq!(config.server_address, None);
+        assert_eq!(config.grpc_address, None);
+        if cfg!(unix) {
+            config.validate().unwrap();
+        }
+
+        let config = DaemonConfig {
+            socket_path: None,
+            ..config
+        };
+        assert!(config.validate().is_err());
+    }
+}
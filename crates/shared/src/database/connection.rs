@@ -1,6 +1,9 @@
 //! Database connection and migration utilities
 
-use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{
+    SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous,
+};
 use sqlx::SqlitePool;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -8,6 +11,9 @@ use tracing::info;
 
 use crate::error::Result;
 
+/// Migrations bundled with this build
+pub(crate) static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 /// Get the database path
 ///
 /// Returns `~/.lumo/lumo.db`
@@ -35,7 +41,9 @@ pub async fn create_pool(db_path: &std::path::Path) -> Result<SqlitePool> {
 
     info!("Connecting to database: {}", db_path.display());
 
+    // Only takes effect on new databases; `vacuum` converts existing ones
     let options = SqliteConnectOptions::from_str(&db_url)?
+        .auto_vacuum(SqliteAutoVacuum::Incremental)
         .journal_mode(SqliteJournalMode::Wal)
         .synchronous(SqliteSynchronous::Normal)
         .busy_timeout(std::time::Duration::from_secs(30))
@@ -57,7 +65,7 @@ pub async fn create_pool(db_path: &std::path::Path) -> Result<SqlitePool> {
 pub async fn run_migrations(pool: &SqlitePool) -> Result<()> {
     info!("Running database migrations...");
 
-    sqlx::migrate!("./migrations").run(pool).await?;
+    MIGRATOR.run(pool).await?;
 
     info!("Database migrations completed");
 
@@ -74,4 +82,79 @@ mod tests {
         assert!(path.to_string_lossy().contains(".lumo"));
         assert!(path.to_string_lossy().ends_with("lumo.db"));
     }
+
+    #[tokio::test]
+    async fn test_dedupe_migration_collapses_only_identical_rows() {
+        const DEDUPE_VERSION: i64 = 20250215000001;
+        const HOST_A: &str = r#"{"host.name":"a"}"#;
+        const HOST_B: &str = r#"{"host.name":"b"}"#;
+
+        let db_path = std::env::temp_dir().join(format!("lumo-dedupe-{}.db", uuid::Uuid::new_v4()));
+        let pool = create_pool(&db_path).await.unwrap();
+
+        // Stop just before the dedupe migration
+        let before = Migrator {
+            migrations: MIGRATOR
+                .iter()
+                .filter(|m| m.version < DEDUPE_VERSION)
+                .cloned()
+                .collect(),
+            ..Migrator::DEFAULT
+        };
+        before.run(&pool).await.unwrap();
+
+        // Two retried copies, then rows differing only in resource or prompt
+        for (id, resource, prompt) in [
+            ("e1", HOST_A, "hi"),
+            ("e2", HOST_A, "hi"),
+            ("e3", HOST_B, "hi"),
+            ("e4", HOST_A, "bye"),
+        ] {
+            sqlx::query(
+                "INSERT INTO events (id, session_id, name, timestamp, resource, prompt)
+                 VALUES (?, 's1', 'claude_code.user_prompt', 1000, ?, ?)",
+            )
+            .bind(id)
+            .bind(resource)
+            .bind(prompt)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        for (id, resource, value) in [
+            ("m1", HOST_A, 1.0),
+            ("m2", HOST_A, 1.0),
+            ("m3", HOST_B, 1.0),
+            ("m4", HOST_A, 2.0),
+        ] {
+            sqlx::query(
+                "INSERT INTO metrics (id, session_id, name, timestamp, value, resource)
+                 VALUES (?, 's1', 'claude_code.commit.count', 1000, ?, ?)",
+            )
+            .bind(id)
+            .bind(value)
+            .bind(resource)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        MIGRATOR.run(&pool).await.unwrap();
+
+        let ids = |table: &'static str| {
+            let sql = format!("SELECT id FROM {} ORDER BY id", table);
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, String>(&sql)
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(ids("events").await, ["e1", "e3", "e4"]);
+        assert_eq!(ids("metrics").await, ["m1", "m3", "m4"]);
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&db_path);
+    }
 }
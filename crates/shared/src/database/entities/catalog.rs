@@ -0,0 +1,64 @@
+//! Attribute catalog entity
+//!
+//! Records which attribute keys, and value types, the daemon has seen for
+//! each event and metric name.
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Database row representation of a catalog entry
+#[derive(Debug, Clone, FromRow)]
+pub struct AttributeCatalogRow {
+    pub signal: String,
+    pub name: String,
+    pub attribute_key: String,
+    pub value_type: String,
+    pub app_version: String,
+    pub first_seen: i64,
+    pub last_seen: i64,
+    pub count: i64,
+}
+
+/// Attribute catalog entry for internal use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeCatalogEntry {
+    /// "log" or "metric"
+    pub signal: String,
+    pub name: String,
+    pub attribute_key: String,
+    pub value_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+    pub first_seen: i64,
+    pub last_seen: i64,
+    pub count: i64,
+}
+
+/// Observed attribute keys for insertion; merged into existing entries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewAttributeCatalogEntry {
+    pub signal: String,
+    pub name: String,
+    pub attribute_key: String,
+    pub value_type: String,
+    pub app_version: Option<String>,
+    pub first_seen: i64,
+    pub last_seen: i64,
+    pub count: i64,
+}
+
+impl From<AttributeCatalogRow> for AttributeCatalogEntry {
+    fn from(row: AttributeCatalogRow) -> Self {
+        Self {
+            signal: row.signal,
+            name: row.name,
+            attribute_key: row.attribute_key,
+            value_type: row.value_type,
+            app_version: Some(row.app_version).filter(|v| !v.is_empty()),
+            first_seen: row.first_seen,
+            last_seen: row.last_seen,
+            count: row.count,
+        }
+    }
+}
@@ -39,6 +39,8 @@ pub struct EventRow {
     pub user_email: Option<String>,
     pub event_sequence: Option<i64>,
     pub tool_result_size_bytes: Option<i64>,
+    pub attributes: Option<String>,
+    pub source: Option<String>,
 }
 
 /// Event entity for internal use
@@ -99,11 +101,17 @@ pub struct Event {
     pub event_sequence: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_result_size_bytes: Option<i64>,
+    /// All log record attributes as a JSON object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<String>,
+    /// Coding agent that sent the record, e.g. "claude_code", "codex", "gemini_cli"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
     pub received_at: String,
 }
 
 /// New event for insertion
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewEvent {
     pub id: String,
     pub session_id: String,
@@ -135,6 +143,8 @@ pub struct NewEvent {
     pub user_email: Option<String>,
     pub event_sequence: Option<i64>,
     pub tool_result_size_bytes: Option<i64>,
+    pub attributes: Option<String>,
+    pub source: Option<String>,
 }
 
 impl From<EventRow> for Event {
@@ -169,6 +179,8 @@ impl From<EventRow> for Event {
             user_email: row.user_email,
             event_sequence: row.event_sequence,
             tool_result_size_bytes: row.tool_result_size_bytes,
+            attributes: row.attributes,
+            source: row.source,
             received_at: row.received_at,
         }
     }
@@ -28,6 +28,16 @@ pub struct MetricRow {
     pub user_email: Option<String>,
     pub unit: Option<String>,
     pub description: Option<String>,
+    pub data_type: Option<String>,
+    pub count: Option<i64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub temporality: Option<String>,
+    pub start_timestamp: Option<i64>,
+    pub raw_value: Option<f64>,
+    pub series_key: Option<String>,
+    pub attributes: Option<String>,
+    pub source: Option<String>,
 }
 
 /// Metric entity for internal use
@@ -65,11 +75,31 @@ pub struct Metric {
     pub unit: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temporality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_timestamp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_value: Option<f64>,
+    /// All data point attributes as a JSON object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<String>,
+    /// Coding agent that sent the record, e.g. "claude_code", "codex", "gemini_cli"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
     pub received_at: String,
 }
 
 /// New metric for insertion (without received_at)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewMetric {
     pub id: String,
     pub session_id: String,
@@ -90,6 +120,46 @@ pub struct NewMetric {
     pub user_email: Option<String>,
     pub unit: Option<String>,
     pub description: Option<String>,
+    pub data_type: Option<String>,
+    pub count: Option<i64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// "delta" or "cumulative" as reported by the exporter
+    pub temporality: Option<String>,
+    pub start_timestamp: Option<i64>,
+    /// Whether a Sum only ever increases (not stored); only a decrease of a
+    /// monotonic series is taken as a counter reset
+    pub is_monotonic: Option<bool>,
+    /// Value as reported; `value` holds the delta once cumulative points are converted
+    pub raw_value: Option<f64>,
+    /// Stable identity of the series (name + attributes + resource)
+    pub series_key: Option<String>,
+    /// All data point attributes as a JSON object
+    pub attributes: Option<String>,
+    pub source: Option<String>,
+    /// Histogram buckets (empty for non-histogram metrics)
+    pub buckets: Vec<MetricBucket>,
+    /// Summary quantiles (empty for non-summary metrics)
+    pub quantiles: Vec<MetricQuantile>,
+}
+
+/// A single histogram bucket
+///
+/// `None` bounds are open-ended (-infinity / +infinity).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricBucket {
+    pub lower_bound: Option<f64>,
+    pub upper_bound: Option<f64>,
+    pub count: i64,
+}
+
+/// A single summary quantile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricQuantile {
+    pub quantile: f64,
+    pub value: f64,
 }
 
 impl From<MetricRow> for Metric {
@@ -113,6 +183,15 @@ impl From<MetricRow> for Metric {
             user_email: row.user_email,
             unit: row.unit,
             description: row.description,
+            data_type: row.data_type,
+            count: row.count,
+            min: row.min,
+            max: row.max,
+            temporality: row.temporality,
+            start_timestamp: row.start_timestamp,
+            raw_value: row.raw_value,
+            attributes: row.attributes,
+            source: row.source,
             received_at: row.received_at,
         }
     }
@@ -2,12 +2,16 @@
 //!
 //! These structs represent the data stored in the database.
 
+mod catalog;
 mod event;
 mod metric;
 mod notification;
 mod session;
+mod span;
 
+pub use catalog::{AttributeCatalogEntry, AttributeCatalogRow, NewAttributeCatalogEntry};
 pub use event::{Event, EventRow, NewEvent};
-pub use metric::{Metric, MetricRow, NewMetric};
+pub use metric::{Metric, MetricBucket, MetricQuantile, MetricRow, NewMetric};
 pub use notification::{NewNotification, Notification, NotificationRow};
 pub use session::Session;
+pub use span::{NewSpan, Span, SpanRow};
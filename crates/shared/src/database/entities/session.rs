@@ -1,12 +1,12 @@
 //! Session entity
 //!
-//! Represents an aggregated session from events.
-//! This corresponds to the `sessions` VIEW in the database.
+//! Represents a session aggregated from its events and hooks.
+//! This corresponds to the `sessions` table in the database.
 
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
-/// Session entity (from the sessions view)
+/// Session entity (from the sessions table)
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 #[serde(rename_all = "camelCase")]
 pub struct Session {
@@ -23,6 +23,12 @@ pub struct Session {
     pub total_input_tokens: i64,
     pub total_output_tokens: i64,
     pub total_cache_read_tokens: i64,
+    pub total_cache_creation_tokens: i64,
+    /// Distinct models used, sorted
+    #[sqlx(json)]
+    pub models: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_event_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub account_uuid: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -31,4 +37,11 @@ pub struct Session {
     pub terminal_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub app_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Working directory reported by hooks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// "active", "idle" (after a Stop hook) or "ended" (after a SessionEnd hook)
+    pub status: String,
 }
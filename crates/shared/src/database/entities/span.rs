@@ -0,0 +1,100 @@
+//! Span entity
+//!
+//! Represents OTLP trace spans from Claude Code or wrapping tools.
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Database row representation of a span
+#[derive(Debug, Clone, FromRow)]
+pub struct SpanRow {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub session_id: String,
+    pub name: String,
+    pub kind: Option<String>,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub duration_ms: f64,
+    pub status_code: Option<String>,
+    pub status_message: Option<String>,
+    pub attributes: Option<String>,
+    pub events: Option<String>,
+    pub scope_name: Option<String>,
+    pub resource: Option<String>,
+    pub received_at: String,
+}
+
+/// Span entity for internal use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    pub trace_id: String,
+    pub span_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_span_id: Option<String>,
+    pub session_id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub duration_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+    pub received_at: String,
+}
+
+/// New span for insertion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewSpan {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub session_id: String,
+    pub name: String,
+    pub kind: Option<String>,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub duration_ms: f64,
+    pub status_code: Option<String>,
+    pub status_message: Option<String>,
+    pub attributes: Option<String>,
+    pub events: Option<String>,
+    pub scope_name: Option<String>,
+    pub resource: Option<String>,
+}
+
+impl From<SpanRow> for Span {
+    fn from(row: SpanRow) -> Self {
+        Self {
+            trace_id: row.trace_id,
+            span_id: row.span_id,
+            parent_span_id: row.parent_span_id,
+            session_id: row.session_id,
+            name: row.name,
+            kind: row.kind,
+            start_time: row.start_time,
+            end_time: row.end_time,
+            duration_ms: row.duration_ms,
+            status_code: row.status_code,
+            status_message: row.status_message,
+            attributes: row.attributes,
+            events: row.events,
+            scope_name: row.scope_name,
+            resource: row.resource,
+            received_at: row.received_at,
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Database maintenance utilities
+//!
+//! Checks and housekeeping used by the daemon's operational commands.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection, SqlitePool};
+use tracing::debug;
+
+use super::connection::MIGRATOR;
+use crate::error::Result;
+
+/// `PRAGMA auto_vacuum` value of incremental mode
+const INCREMENTAL: i64 = 2;
+
+/// Versions of bundled migrations that have not been applied yet
+pub async fn pending_migrations(pool: &SqlitePool) -> Result<Vec<i64>> {
+    let applied: Vec<(i64,)> =
+        match sqlx::query_as("SELECT version FROM _sqlx_migrations WHERE success = 1")
+            .fetch_all(pool)
+            .await
+        {
+            Ok(rows) => rows,
+            // No migration has ever run against this database
+            Err(sqlx::Error::Database(e)) if e.message().contains("no such table") => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+    let applied: HashSet<i64> = applied.into_iter().map(|(version,)| version).collect();
+
+    Ok(MIGRATOR
+        .iter()
+        .map(|migration| migration.version)
+        .filter(|version| !applied.contains(version))
+        .collect())
+}
+
+/// Version of the latest migration applied, `None` if none has run
+pub async fn migration_version(pool: &SqlitePool) -> Result<Option<i64>> {
+    match sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+        .fetch_one(pool)
+        .await
+    {
+        Ok(version) => Ok(version),
+        Err(sqlx::Error::Database(e)) if e.message().contains("no such table") => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Run SQLite's integrity check
+///
+/// Returns `["ok"]` for a healthy database, otherwise the problems found.
+pub async fn integrity_check(pool: &SqlitePool) -> Result<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().map(|(message,)| message).collect())
+}
+
+/// Rebuild the database file to reclaim free pages and truncate the WAL
+pub async fn vacuum(pool: &SqlitePool) -> Result<()> {
+    sqlx::query("VACUUM").execute(pool).await?;
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Switch a database created before incremental auto-vacuum was enabled to
+/// it, so later prunes can release pages with [`reclaim_space`]
+///
+/// Rebuilds the whole file outside WAL mode, which needs the only open
+/// connection: fails with "database is locked" while the daemon runs.
+/// Returns `false` if the database already uses incremental auto-vacuum.
+pub async fn enable_incremental_vacuum(db_path: &Path) -> Result<bool> {
+    let mut conn = SqliteConnectOptions::new()
+        .filename(db_path)
+        .busy_timeout(Duration::from_secs(5))
+        .connect()
+        .await?;
+
+    let (auto_vacuum,): (i64,) = sqlx::query_as("PRAGMA auto_vacuum")
+        .fetch_one(&mut conn)
+        .await?;
+    if auto_vacuum == INCREMENTAL {
+        conn.close().await?;
+        return Ok(false);
+    }
+
+    // The mode only changes with a VACUUM on the same connection, which
+    // cannot apply it in WAL mode
+    sqlx::query("PRAGMA journal_mode = DELETE")
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("PRAGMA auto_vacuum = INCREMENTAL")
+        .execute(&mut conn)
+        .await?;
+    let vacuumed = sqlx::query("VACUUM").execute(&mut conn).await;
+    sqlx::query("PRAGMA journal_mode = WAL")
+        .execute(&mut conn)
+        .await?;
+    vacuumed?;
+    conn.close().await?;
+
+    Ok(true)
+}
+
+/// Return pages freed by deletes to the file system and truncate the WAL
+///
+/// Cheap enough to run after every prune. Databases that are not in
+/// incremental auto-vacuum mode keep their free pages for reuse until
+/// [`enable_incremental_vacuum`] converts them.
+pub async fn reclaim_space(pool: &SqlitePool) -> Result<()> {
+    // Both pragmas must run on the same connection
+    let mut conn = pool.acquire().await?;
+
+    let (auto_vacuum,): (i64,) = sqlx::query_as("PRAGMA auto_vacuum")
+        .fetch_one(&mut *conn)
+        .await?;
+    if auto_vacuum == INCREMENTAL {
+        sqlx::query("PRAGMA incremental_vacuum")
+            .execute(&mut *conn)
+            .await?;
+    } else {
+        debug!("Incremental auto-vacuum is off; `lumo-daemon vacuum` enables it");
+    }
+
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::connection::create_pool;
+
+    async fn auto_vacuum(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("PRAGMA auto_vacuum")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_enable_incremental_vacuum_converts_legacy_databases() {
+        let dir = std::env::temp_dir();
+        let fresh = create_pool(&dir.join(format!("lumo-fresh-{}.db", uuid::Uuid::new_v4())))
+            .await
+            .unwrap();
+        assert_eq!(auto_vacuum(&fresh).await, INCREMENTAL);
+
+        // A database created before incremental auto-vacuum was enabled
+        let db_path = dir.join(format!("lumo-legacy-{}.db", uuid::Uuid::new_v4()));
+        let options = sqlx::sqlite::SqliteConnectOptions::new()
+            .filename(&db_path)
+            .create_if_missing(true);
+        let legacy = SqlitePool::connect_with(options).await.unwrap();
+        sqlx::query("CREATE TABLE t (x INTEGER)")
+            .execute(&legacy)
+            .await
+            .unwrap();
+        legacy.close().await;
+
+        let pool = create_pool(&db_path).await.unwrap();
+        assert_eq!(auto_vacuum(&pool).await, 0);
+
+        // The background reclaim leaves the mode alone
+        reclaim_space(&pool).await.unwrap();
+        assert_eq!(auto_vacuum(&pool).await, 0);
+
+        // Not while another connection has the database open
+        assert!(enable_incremental_vacuum(&db_path).await.is_err());
+        pool.close().await;
+
+        assert!(enable_incremental_vacuum(&db_path).await.unwrap());
+        assert!(!enable_incremental_vacuum(&db_path).await.unwrap());
+
+        let pool = create_pool(&db_path).await.unwrap();
+        assert_eq!(auto_vacuum(&pool).await, INCREMENTAL);
+        let (journal_mode,): (String,) = sqlx::query_as("PRAGMA journal_mode")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+        reclaim_space(&pool).await.unwrap();
+    }
+}
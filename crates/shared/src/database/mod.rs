@@ -1,7 +1,8 @@
 //! Database module
 //!
-//! Provides database connection, entities, and repositories.
+//! Provides database connection, entities, repositories, and maintenance.
 
 pub mod connection;
 pub mod entities;
+pub mod maintenance;
 pub mod repositories;
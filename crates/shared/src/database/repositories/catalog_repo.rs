@@ -0,0 +1,168 @@
+//! Attribute catalog repository
+//!
+//! Provides upserts and queries for the attribute catalog.
+
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use super::INSERT_CHUNK_SIZE;
+use crate::database::entities::{
+    AttributeCatalogEntry, AttributeCatalogRow, NewAttributeCatalogEntry,
+};
+use crate::error::Result;
+
+/// Repository for attribute catalog operations
+pub struct AttributeCatalogRepository;
+
+impl AttributeCatalogRepository {
+    /// Merge observed entries into the catalog in a single transaction
+    ///
+    /// Existing entries keep the earliest `first_seen` and latest `last_seen`,
+    /// and their counts are added up.
+    pub async fn upsert_batch(
+        pool: &SqlitePool,
+        entries: &[NewAttributeCatalogEntry],
+    ) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = pool.begin().await?;
+
+        for chunk in entries.chunks(INSERT_CHUNK_SIZE) {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                r#"
+                INSERT INTO attribute_catalog (
+                    signal, name, attribute_key, value_type, app_version,
+                    first_seen, last_seen, count
+                )
+                "#,
+            );
+            query.push_values(chunk, |mut row, entry| {
+                row.push_bind(&entry.signal)
+                    .push_bind(&entry.name)
+                    .push_bind(&entry.attribute_key)
+                    .push_bind(&entry.value_type)
+                    .push_bind(entry.app_version.as_deref().unwrap_or_default())
+                    .push_bind(entry.first_seen)
+                    .push_bind(entry.last_seen)
+                    .push_bind(entry.count);
+            });
+            query.push(
+                r#"
+                ON CONFLICT (signal, name, attribute_key, value_type, app_version) DO UPDATE SET
+                    first_seen = MIN(first_seen, excluded.first_seen),
+                    last_seen = MAX(last_seen, excluded.last_seen),
+                    count = count + excluded.count
+                "#,
+            );
+
+            query.build().execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Find catalog entries, optionally for one signal or first seen since a time
+    pub async fn find_all(
+        pool: &SqlitePool,
+        signal: Option<&str>,
+        first_seen_since: Option<i64>,
+    ) -> Result<Vec<AttributeCatalogEntry>> {
+        let rows: Vec<AttributeCatalogRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM attribute_catalog
+            WHERE (?1 IS NULL OR signal = ?1)
+              AND (?2 IS NULL OR first_seen >= ?2)
+            ORDER BY signal, name, attribute_key, app_version
+            "#,
+        )
+        .bind(signal)
+        .bind(first_seen_since)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(AttributeCatalogEntry::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(value_type: &str, app_version: Option<&str>, seen: i64) -> NewAttributeCatalogEntry {
+        NewAttributeCatalogEntry {
+            signal: "log".to_string(),
+            name: "claude_code.api_request".to_string(),
+            attribute_key: "model".to_string(),
+            value_type: value_type.to_string(),
+            app_version: app_version.map(String::from),
+            first_seen: seen,
+            last_seen: seen,
+            count: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upsert_merges_entries_with_the_same_key() {
+        let db_path =
+            std::env::temp_dir().join(format!("lumo-catalog-{}.db", uuid::Uuid::new_v4()));
+        let pool = crate::create_pool(&db_path).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+
+        // A missing app version merges with other missing ones
+        let batches = [
+            vec![
+                entry("string", None, 2000),
+                entry("string", Some("2.0.0"), 2000),
+            ],
+            vec![entry("string", None, 1000), entry("string", None, 3000)],
+            vec![entry("int", None, 1500)],
+        ];
+        for batch in &batches {
+            AttributeCatalogRepository::upsert_batch(&pool, batch)
+                .await
+                .unwrap();
+        }
+
+        let entries = AttributeCatalogRepository::find_all(&pool, Some("log"), None)
+            .await
+            .unwrap();
+        let mut summary: Vec<_> = entries
+            .iter()
+            .map(|e| {
+                (
+                    e.value_type.as_str(),
+                    e.app_version.as_deref(),
+                    e.first_seen,
+                    e.last_seen,
+                    e.count,
+                )
+            })
+            .collect();
+        summary.sort();
+        assert_eq!(
+            summary,
+            [
+                ("int", None, 1500, 1500, 1),
+                ("string", None, 1000, 3000, 3),
+                ("string", Some("2.0.0"), 2000, 2000, 1),
+            ]
+        );
+
+        // Filters
+        let since = AttributeCatalogRepository::find_all(&pool, None, Some(1500))
+            .await
+            .unwrap();
+        assert_eq!(since.len(), 2);
+        assert!(
+            AttributeCatalogRepository::find_all(&pool, Some("metric"), None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&db_path);
+    }
+}
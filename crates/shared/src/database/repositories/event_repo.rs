@@ -2,8 +2,9 @@
 //!
 //! Provides CRUD operations for events.
 
-use sqlx::SqlitePool;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
 
+use super::{attribute_path, AttributeGroup, SessionRepository, INSERT_CHUNK_SIZE};
 use crate::database::entities::{Event, EventRow, NewEvent};
 use crate::error::Result;
 
@@ -12,73 +13,90 @@ pub struct EventRepository;
 
 impl EventRepository {
     /// Insert a new event
+    ///
+    /// Event IDs are derived from their content, so an event re-sent by an
+    /// exporter retry is ignored rather than duplicated.
     pub async fn insert(pool: &SqlitePool, event: &NewEvent) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO events (
-                id, session_id, name, timestamp,
-                duration_ms, success, error,
-                model, cost_usd, input_tokens, output_tokens,
-                cache_read_tokens, cache_creation_tokens, status_code, attempt,
-                tool_name, tool_decision, decision_source, tool_parameters,
-                prompt_length, prompt,
-                account_uuid, organization_id, terminal_type, app_version,
-                resource,
-                user_id, user_email, event_sequence, tool_result_size_bytes
-            ) VALUES (
-                ?, ?, ?, ?,
-                ?, ?, ?,
-                ?, ?, ?, ?,
-                ?, ?, ?, ?,
-                ?, ?, ?, ?,
-                ?, ?,
-                ?, ?, ?, ?,
-                ?,
-                ?, ?, ?, ?
-            )
-            "#,
-        )
-        .bind(&event.id)
-        .bind(&event.session_id)
-        .bind(&event.name)
-        .bind(event.timestamp)
-        .bind(event.duration_ms)
-        .bind(event.success.map(|b| if b { 1 } else { 0 }))
-        .bind(&event.error)
-        .bind(&event.model)
-        .bind(event.cost_usd)
-        .bind(event.input_tokens)
-        .bind(event.output_tokens)
-        .bind(event.cache_read_tokens)
-        .bind(event.cache_creation_tokens)
-        .bind(event.status_code)
-        .bind(event.attempt)
-        .bind(&event.tool_name)
-        .bind(&event.tool_decision)
-        .bind(&event.decision_source)
-        .bind(&event.tool_parameters)
-        .bind(event.prompt_length)
-        .bind(&event.prompt)
-        .bind(&event.account_uuid)
-        .bind(&event.organization_id)
-        .bind(&event.terminal_type)
-        .bind(&event.app_version)
-        .bind(&event.resource)
-        .bind(&event.user_id)
-        .bind(&event.user_email)
-        .bind(event.event_sequence)
-        .bind(event.tool_result_size_bytes)
-        .execute(pool)
-        .await?;
-
-        Ok(())
+        Self::insert_batch(pool, std::slice::from_ref(event)).await
     }
 
     /// Insert multiple events in a batch
+    ///
+    /// All rows are written in a single transaction using multi-row
+    /// `INSERT`s, so a failure rejects the whole batch. The sessions the
+    /// events belong to are updated in the same transaction.
     pub async fn insert_batch(pool: &SqlitePool, events: &[NewEvent]) -> Result<()> {
-        for event in events {
-            Self::insert(pool, event).await?;
+        if events.is_empty() {
+            return Ok(());
         }
+
+        let mut tx = pool.begin().await?;
+
+        // Retried events are ignored and must not be added to sessions again
+        let mut inserted = Vec::new();
+        for chunk in events.chunks(INSERT_CHUNK_SIZE) {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                r#"
+                INSERT OR IGNORE INTO events (
+                    id, session_id, name, timestamp,
+                    duration_ms, success, error,
+                    model, cost_usd, input_tokens, output_tokens,
+                    cache_read_tokens, cache_creation_tokens, status_code, attempt,
+                    tool_name, tool_decision, decision_source, tool_parameters,
+                    prompt_length, prompt,
+                    account_uuid, organization_id, terminal_type, app_version,
+                    resource,
+                    user_id, user_email, event_sequence, tool_result_size_bytes,
+                    attributes, source
+                )
+                "#,
+            );
+
+            query.push_values(chunk, |mut row, event| {
+                row.push_bind(&event.id)
+                    .push_bind(&event.session_id)
+                    .push_bind(&event.name)
+                    .push_bind(event.timestamp)
+                    .push_bind(event.duration_ms)
+                    .push_bind(event.success.map(|b| if b { 1 } else { 0 }))
+                    .push_bind(&event.error)
+                    .push_bind(&event.model)
+                    .push_bind(event.cost_usd)
+                    .push_bind(event.input_tokens)
+                    .push_bind(event.output_tokens)
+                    .push_bind(event.cache_read_tokens)
+                    .push_bind(event.cache_creation_tokens)
+                    .push_bind(event.status_code)
+                    .push_bind(event.attempt)
+                    .push_bind(&event.tool_name)
+                    .push_bind(&event.tool_decision)
+                    .push_bind(&event.decision_source)
+                    .push_bind(&event.tool_parameters)
+                    .push_bind(event.prompt_length)
+                    .push_bind(&event.prompt)
+                    .push_bind(&event.account_uuid)
+                    .push_bind(&event.organization_id)
+                    .push_bind(&event.terminal_type)
+                    .push_bind(&event.app_version)
+                    .push_bind(&event.resource)
+                    .push_bind(&event.user_id)
+                    .push_bind(&event.user_email)
+                    .push_bind(event.event_sequence)
+                    .push_bind(event.tool_result_size_bytes)
+                    .push_bind(&event.attributes)
+                    .push_bind(&event.source);
+            });
+
+            query.push(" RETURNING id");
+
+            let ids: Vec<String> = query.build_query_scalar().fetch_all(&mut *tx).await?;
+            inserted.extend(ids);
+        }
+
+        SessionRepository::add_events(&mut tx, &inserted).await?;
+
+        tx.commit().await?;
+
         Ok(())
     }
 
@@ -98,6 +116,44 @@ impl EventRepository {
         Ok(rows.into_iter().map(Event::from).collect())
     }
 
+    /// Find events of a session within a time range, with pagination
+    /// (oldest first)
+    pub async fn find_by_session_paginated(
+        pool: &SqlitePool,
+        session_id: &str,
+        start_time: i64,
+        end_time: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Event>> {
+        let rows: Vec<EventRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM events
+            WHERE session_id = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp ASC, event_sequence ASC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(session_id)
+        .bind(start_time)
+        .bind(end_time)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Event::from).collect())
+    }
+
+    /// Timestamp of the newest event, `None` if there are none
+    pub async fn latest_timestamp(pool: &SqlitePool) -> Result<Option<i64>> {
+        let timestamp = sqlx::query_scalar("SELECT MAX(timestamp) FROM events")
+            .fetch_one(pool)
+            .await?;
+
+        Ok(timestamp)
+    }
+
     /// Find events by name
     pub async fn find_by_name(pool: &SqlitePool, name: &str) -> Result<Vec<Event>> {
         let rows: Vec<EventRow> = sqlx::query_as(
@@ -135,6 +191,72 @@ impl EventRepository {
         Ok(rows.into_iter().map(Event::from).collect())
     }
 
+    /// Find events whose attribute `key` equals `value` within a time range
+    pub async fn find_by_attribute(
+        pool: &SqlitePool,
+        key: &str,
+        value: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<Event>> {
+        let rows: Vec<EventRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM events
+            WHERE CAST(json_extract(attributes, ?) AS TEXT) = ?
+              AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp ASC
+            "#,
+        )
+        .bind(attribute_path(key))
+        .bind(value)
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Event::from).collect())
+    }
+
+    /// Group events by the value of attribute `key` within a time range
+    ///
+    /// Restricted to events called `name` when given.
+    pub async fn group_by_attribute(
+        pool: &SqlitePool,
+        name: Option<&str>,
+        key: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<AttributeGroup>> {
+        let rows: Vec<(Option<String>, i64, f64)> = sqlx::query_as(
+            r#"
+            SELECT
+                CAST(json_extract(attributes, ?1) AS TEXT) as value,
+                COUNT(*) as count,
+                COALESCE(SUM(cost_usd), 0.0) as total
+            FROM events
+            WHERE (?2 IS NULL OR name = ?2)
+              AND timestamp >= ?3 AND timestamp <= ?4
+            GROUP BY value
+            ORDER BY count DESC
+            "#,
+        )
+        .bind(attribute_path(key))
+        .bind(name)
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(value, count, total)| AttributeGroup {
+                value,
+                count,
+                total,
+            })
+            .collect())
+    }
+
     /// Find API request events for a session
     pub async fn find_api_requests(pool: &SqlitePool, session_id: &str) -> Result<Vec<Event>> {
         let rows: Vec<EventRow> = sqlx::query_as(
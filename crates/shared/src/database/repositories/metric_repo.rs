@@ -2,9 +2,10 @@
 //!
 //! Provides CRUD operations for metrics.
 
-use sqlx::SqlitePool;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
 
-use crate::database::entities::{Metric, MetricRow, NewMetric};
+use super::{attribute_path, AttributeGroup, INSERT_CHUNK_SIZE};
+use crate::database::entities::{Metric, MetricBucket, MetricQuantile, MetricRow, NewMetric};
 use crate::error::Result;
 
 /// Repository for metric operations
@@ -12,54 +13,114 @@ pub struct MetricRepository;
 
 impl MetricRepository {
     /// Insert a new metric
+    ///
+    /// Metric IDs are derived from their content, so a data point re-sent by
+    /// an exporter retry is ignored rather than duplicated.
     pub async fn insert(pool: &SqlitePool, metric: &NewMetric) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO metrics (
-                id, session_id, name, timestamp, value,
-                metric_type, model, tool, decision, language,
-                account_uuid, organization_id, terminal_type, app_version,
-                resource,
-                user_id, user_email, unit, description
-            ) VALUES (
-                ?, ?, ?, ?, ?,
-                ?, ?, ?, ?, ?,
-                ?, ?, ?, ?,
-                ?,
-                ?, ?, ?, ?
-            )
-            "#,
-        )
-        .bind(&metric.id)
-        .bind(&metric.session_id)
-        .bind(&metric.name)
-        .bind(metric.timestamp)
-        .bind(metric.value)
-        .bind(&metric.metric_type)
-        .bind(&metric.model)
-        .bind(&metric.tool)
-        .bind(&metric.decision)
-        .bind(&metric.language)
-        .bind(&metric.account_uuid)
-        .bind(&metric.organization_id)
-        .bind(&metric.terminal_type)
-        .bind(&metric.app_version)
-        .bind(&metric.resource)
-        .bind(&metric.user_id)
-        .bind(&metric.user_email)
-        .bind(&metric.unit)
-        .bind(&metric.description)
-        .execute(pool)
-        .await?;
-
-        Ok(())
+        Self::insert_batch(pool, std::slice::from_ref(metric)).await
     }
 
     /// Insert multiple metrics in a batch
+    ///
+    /// All rows, including histogram buckets and summary quantiles, are
+    /// written in a single transaction using multi-row `INSERT`s, so a
+    /// failure rejects the whole batch.
     pub async fn insert_batch(pool: &SqlitePool, metrics: &[NewMetric]) -> Result<()> {
-        for metric in metrics {
-            Self::insert(pool, metric).await?;
+        if metrics.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = pool.begin().await?;
+
+        for chunk in metrics.chunks(INSERT_CHUNK_SIZE) {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                r#"
+                INSERT OR IGNORE INTO metrics (
+                    id, session_id, name, timestamp, value,
+                    metric_type, model, tool, decision, language,
+                    account_uuid, organization_id, terminal_type, app_version,
+                    resource,
+                    user_id, user_email, unit, description,
+                    data_type, count, min, max,
+                    temporality, start_timestamp, raw_value, series_key,
+                    attributes, source
+                )
+                "#,
+            );
+
+            query.push_values(chunk, |mut row, metric| {
+                row.push_bind(&metric.id)
+                    .push_bind(&metric.session_id)
+                    .push_bind(&metric.name)
+                    .push_bind(metric.timestamp)
+                    .push_bind(metric.value)
+                    .push_bind(&metric.metric_type)
+                    .push_bind(&metric.model)
+                    .push_bind(&metric.tool)
+                    .push_bind(&metric.decision)
+                    .push_bind(&metric.language)
+                    .push_bind(&metric.account_uuid)
+                    .push_bind(&metric.organization_id)
+                    .push_bind(&metric.terminal_type)
+                    .push_bind(&metric.app_version)
+                    .push_bind(&metric.resource)
+                    .push_bind(&metric.user_id)
+                    .push_bind(&metric.user_email)
+                    .push_bind(&metric.unit)
+                    .push_bind(&metric.description)
+                    .push_bind(&metric.data_type)
+                    .push_bind(metric.count)
+                    .push_bind(metric.min)
+                    .push_bind(metric.max)
+                    .push_bind(&metric.temporality)
+                    .push_bind(metric.start_timestamp)
+                    .push_bind(metric.raw_value)
+                    .push_bind(&metric.series_key)
+                    .push_bind(&metric.attributes)
+                    .push_bind(&metric.source);
+            });
+
+            query.build().execute(&mut *tx).await?;
         }
+
+        // A retried data point keeps the buckets and quantiles already stored
+        // under its ID, which are identical, so conflicts are ignored too
+        let buckets: Vec<_> = metrics
+            .iter()
+            .flat_map(|m| m.buckets.iter().enumerate().map(move |(i, b)| (m, i, b)))
+            .collect();
+        for chunk in buckets.chunks(INSERT_CHUNK_SIZE) {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                "INSERT OR IGNORE INTO metric_buckets (metric_id, bucket_index, lower_bound, upper_bound, count) ",
+            );
+            query.push_values(chunk, |mut row, (metric, index, bucket)| {
+                row.push_bind(&metric.id)
+                    .push_bind(*index as i64)
+                    .push_bind(bucket.lower_bound)
+                    .push_bind(bucket.upper_bound)
+                    .push_bind(bucket.count);
+            });
+            query.build().execute(&mut *tx).await?;
+        }
+
+        let quantiles: Vec<_> = metrics
+            .iter()
+            .flat_map(|m| m.quantiles.iter().map(move |q| (m, q)))
+            .collect();
+        for chunk in quantiles.chunks(INSERT_CHUNK_SIZE) {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                "INSERT OR IGNORE INTO metric_quantiles (metric_id, quantile, value) ",
+            );
+            query.push_values(chunk, |mut row, (metric, quantile)| {
+                row.push_bind(&metric.id)
+                    .push_bind(quantile.quantile)
+                    .push_bind(quantile.value);
+            });
+            query.build().execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+
         Ok(())
     }
 
@@ -79,6 +140,15 @@ impl MetricRepository {
         Ok(rows.into_iter().map(Metric::from).collect())
     }
 
+    /// Timestamp of the newest metric, `None` if there are none
+    pub async fn latest_timestamp(pool: &SqlitePool) -> Result<Option<i64>> {
+        let timestamp = sqlx::query_scalar("SELECT MAX(timestamp) FROM metrics")
+            .fetch_one(pool)
+            .await?;
+
+        Ok(timestamp)
+    }
+
     /// Find metrics by name
     pub async fn find_by_name(pool: &SqlitePool, name: &str) -> Result<Vec<Metric>> {
         let rows: Vec<MetricRow> = sqlx::query_as(
@@ -116,6 +186,208 @@ impl MetricRepository {
         Ok(rows.into_iter().map(Metric::from).collect())
     }
 
+    /// Find metrics whose attribute `key` equals `value` within a time range
+    pub async fn find_by_attribute(
+        pool: &SqlitePool,
+        key: &str,
+        value: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<Metric>> {
+        let rows: Vec<MetricRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM metrics
+            WHERE CAST(json_extract(attributes, ?) AS TEXT) = ?
+              AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp ASC
+            "#,
+        )
+        .bind(attribute_path(key))
+        .bind(value)
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Metric::from).collect())
+    }
+
+    /// Group metrics by the value of attribute `key` within a time range
+    ///
+    /// Restricted to metrics called `name` when given.
+    pub async fn group_by_attribute(
+        pool: &SqlitePool,
+        name: Option<&str>,
+        key: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<AttributeGroup>> {
+        let rows: Vec<(Option<String>, i64, f64)> = sqlx::query_as(
+            r#"
+            SELECT
+                CAST(json_extract(attributes, ?1) AS TEXT) as value,
+                COUNT(*) as count,
+                COALESCE(SUM(value), 0.0) as total
+            FROM metrics
+            WHERE (?2 IS NULL OR name = ?2)
+              AND timestamp >= ?3 AND timestamp <= ?4
+            GROUP BY value
+            ORDER BY count DESC
+            "#,
+        )
+        .bind(attribute_path(key))
+        .bind(name)
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(value, count, total)| AttributeGroup {
+                value,
+                count,
+                total,
+            })
+            .collect())
+    }
+
+    /// Find the most recent point of a series
+    ///
+    /// Used to resume cumulative-to-delta conversion after a restart.
+    pub async fn find_latest_in_series(
+        pool: &SqlitePool,
+        series_key: &str,
+    ) -> Result<Option<Metric>> {
+        let row: Option<MetricRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM metrics
+            WHERE series_key = ?
+            ORDER BY timestamp DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(series_key)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(Metric::from))
+    }
+
+    /// Get the histogram buckets of a metric data point
+    pub async fn find_buckets(pool: &SqlitePool, metric_id: &str) -> Result<Vec<MetricBucket>> {
+        let rows: Vec<MetricBucketRow> = sqlx::query_as(
+            r#"
+            SELECT lower_bound, upper_bound, count
+            FROM metric_buckets
+            WHERE metric_id = ?
+            ORDER BY bucket_index ASC
+            "#,
+        )
+        .bind(metric_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(MetricBucket::from).collect())
+    }
+
+    /// Get the summary quantiles of a metric data point
+    pub async fn find_quantiles(pool: &SqlitePool, metric_id: &str) -> Result<Vec<MetricQuantile>> {
+        let rows: Vec<(f64, f64)> = sqlx::query_as(
+            r#"
+            SELECT quantile, value
+            FROM metric_quantiles
+            WHERE metric_id = ?
+            ORDER BY quantile ASC
+            "#,
+        )
+        .bind(metric_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(quantile, value)| MetricQuantile { quantile, value })
+            .collect())
+    }
+
+    /// Estimate percentiles of a histogram metric within a time range
+    ///
+    /// Buckets of the metric's data points are merged, then each requested
+    /// quantile (0.0 ..= 1.0) is located by linear interpolation inside its
+    /// bucket. Open-ended buckets are clamped to the observed min/max when the
+    /// exporter reported them.
+    ///
+    /// Cumulative points repeat every observation since their start time, so
+    /// only the latest one per series and start time is merged; delta points
+    /// are merged as they are. Rows stored before histograms had a series key
+    /// are told apart by their attributes and resource.
+    pub async fn get_percentiles(
+        pool: &SqlitePool,
+        name: &str,
+        start_time: i64,
+        end_time: i64,
+        quantiles: &[f64],
+    ) -> Result<Vec<MetricPercentile>> {
+        let rows: Vec<MetricBucketRow> = sqlx::query_as(
+            r#"
+            WITH points AS (
+                SELECT
+                    id,
+                    temporality,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY
+                            COALESCE(series_key, json_array(attributes, resource)),
+                            start_timestamp
+                        ORDER BY timestamp DESC
+                    ) as latest
+                FROM metrics
+                WHERE name = ? AND timestamp >= ? AND timestamp <= ?
+            )
+            SELECT b.lower_bound, b.upper_bound, SUM(b.count) as count
+            FROM metric_buckets b
+            JOIN points p ON p.id = b.metric_id
+            WHERE p.temporality IS NOT 'cumulative' OR p.latest = 1
+            GROUP BY b.lower_bound, b.upper_bound
+            "#,
+        )
+        .bind(name)
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_all(pool)
+        .await?;
+
+        let (min, max): (Option<f64>, Option<f64>) = sqlx::query_as(
+            r#"
+            SELECT MIN(min), MAX(max)
+            FROM metrics
+            WHERE name = ? AND timestamp >= ? AND timestamp <= ?
+            "#,
+        )
+        .bind(name)
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_one(pool)
+        .await?;
+
+        let mut buckets: Vec<MetricBucket> = rows.into_iter().map(MetricBucket::from).collect();
+        buckets.sort_by(|a, b| {
+            let upper = |bucket: &MetricBucket| bucket.upper_bound.unwrap_or(f64::INFINITY);
+            let lower = |bucket: &MetricBucket| bucket.lower_bound.unwrap_or(f64::NEG_INFINITY);
+            upper(a)
+                .total_cmp(&upper(b))
+                .then(lower(a).total_cmp(&lower(b)))
+        });
+
+        Ok(quantiles
+            .iter()
+            .map(|&quantile| MetricPercentile {
+                quantile,
+                value: percentile_from_buckets(&buckets, quantile, min, max),
+            })
+            .collect())
+    }
+
     /// Get aggregated token usage by model
     pub async fn get_token_usage_by_model(
         pool: &SqlitePool,
@@ -172,6 +444,69 @@ impl MetricRepository {
     }
 }
 
+/// A percentile estimated from histogram buckets
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricPercentile {
+    pub quantile: f64,
+    /// `None` when no observations fall in the range
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct MetricBucketRow {
+    lower_bound: Option<f64>,
+    upper_bound: Option<f64>,
+    count: i64,
+}
+
+impl From<MetricBucketRow> for MetricBucket {
+    fn from(row: MetricBucketRow) -> Self {
+        Self {
+            lower_bound: row.lower_bound,
+            upper_bound: row.upper_bound,
+            count: row.count,
+        }
+    }
+}
+
+/// Locate `quantile` in buckets sorted by ascending upper bound
+fn percentile_from_buckets(
+    buckets: &[MetricBucket],
+    quantile: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+) -> Option<f64> {
+    let total: i64 = buckets.iter().map(|b| b.count).sum();
+    if total <= 0 {
+        return None;
+    }
+
+    let rank = quantile.clamp(0.0, 1.0) * total as f64;
+    let mut cumulative = 0.0;
+
+    for bucket in buckets.iter().filter(|b| b.count > 0) {
+        let count = bucket.count as f64;
+        if cumulative + count >= rank {
+            // Fall back to the other bound when a side is open-ended
+            let lower = bucket.lower_bound.or(min).or(bucket.upper_bound)?;
+            let upper = bucket.upper_bound.or(max).unwrap_or(lower);
+            let fraction = (rank - cumulative) / count;
+            let mut value = lower + (upper - lower) * fraction;
+            if let Some(min) = min {
+                value = value.max(min);
+            }
+            if let Some(max) = max {
+                value = value.min(max);
+            }
+            return Some(value);
+        }
+        cumulative += count;
+    }
+
+    max
+}
+
 /// Token usage aggregated by model
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -197,3 +532,101 @@ impl From<TokenUsageByModelRow> for TokenUsageByModel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(lower: Option<f64>, upper: Option<f64>, count: i64) -> MetricBucket {
+        MetricBucket {
+            lower_bound: lower,
+            upper_bound: upper,
+            count,
+        }
+    }
+
+    #[test]
+    fn test_percentile_from_buckets() {
+        let buckets = vec![
+            bucket(None, Some(10.0), 50),
+            bucket(Some(10.0), Some(20.0), 40),
+            bucket(Some(20.0), None, 10),
+        ];
+
+        assert_eq!(
+            percentile_from_buckets(&buckets, 0.5, Some(0.0), Some(40.0)),
+            Some(10.0)
+        );
+        assert_eq!(
+            percentile_from_buckets(&buckets, 0.7, Some(0.0), Some(40.0)),
+            Some(15.0)
+        );
+        assert_eq!(
+            percentile_from_buckets(&buckets, 0.95, Some(0.0), Some(40.0)),
+            Some(30.0)
+        );
+        // Without min/max the open-ended buckets collapse onto their finite bound
+        assert_eq!(
+            percentile_from_buckets(&buckets, 0.99, None, None),
+            Some(20.0)
+        );
+        assert_eq!(percentile_from_buckets(&[], 0.5, None, None), None);
+    }
+
+    fn histogram(id: &str, timestamp: i64, buckets: serde_json::Value) -> NewMetric {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "session_id": "histogram-session",
+            "name": "claude_code.api_request.duration",
+            "timestamp": timestamp,
+            "value": 0.0,
+            "data_type": "histogram",
+            "min": 0.0,
+            "max": 20.0,
+            "temporality": "cumulative",
+            "start_timestamp": 0,
+            "series_key": "histogram-series",
+            "buckets": buckets,
+            "quantiles": [],
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_percentiles_use_latest_cumulative_point() {
+        let db_path =
+            std::env::temp_dir().join(format!("lumo-percentiles-{}.db", uuid::Uuid::new_v4()));
+        let pool = crate::create_pool(&db_path).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+
+        // The second snapshot repeats the four observations of the first
+        let first = serde_json::json!([{ "upperBound": 10.0, "count": 4 }]);
+        let second = serde_json::json!([
+            { "upperBound": 10.0, "count": 4 },
+            { "lowerBound": 10.0, "upperBound": 20.0, "count": 4 },
+        ]);
+        MetricRepository::insert_batch(
+            &pool,
+            &[
+                histogram("h1", 1_000, first),
+                histogram("h2", 2_000, second),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let percentiles = MetricRepository::get_percentiles(
+            &pool,
+            "claude_code.api_request.duration",
+            0,
+            3_000,
+            &[0.5],
+        )
+        .await
+        .unwrap();
+        assert_eq!(percentiles[0].value, Some(10.0));
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&db_path);
+    }
+}
@@ -2,12 +2,45 @@
 //!
 //! Provides CRUD operations for database entities.
 
+mod catalog_repo;
 mod event_repo;
 mod metric_repo;
 mod notification_repo;
+mod rollup_repo;
 mod session_repo;
+mod span_repo;
 
+pub use catalog_repo::AttributeCatalogRepository;
 pub use event_repo::EventRepository;
-pub use metric_repo::{MetricRepository, TokenUsageByModel};
+pub use metric_repo::{MetricPercentile, MetricRepository, TokenUsageByModel};
 pub use notification_repo::NotificationRepository;
+pub use rollup_repo::{
+    EventTotals, MetricTotals, PeriodCount, RollupGroup, RollupRepository, SeriesTotals,
+};
 pub use session_repo::{SessionRepository, SessionsSummary, TotalTokens};
+pub use span_repo::SpanRepository;
+
+/// Rows per multi-row `INSERT` statement
+///
+/// Keeps the number of bound parameters (rows x columns) well below SQLite's
+/// `SQLITE_MAX_VARIABLE_NUMBER` for the widest table.
+const INSERT_CHUNK_SIZE: usize = 500;
+
+/// Rows grouped by the value of one attribute
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeGroup {
+    /// Attribute value as text; `None` for rows without the attribute
+    pub value: Option<String>,
+    pub count: i64,
+    /// Sum of `value` for metrics, of `cost_usd` for events
+    pub total: f64,
+}
+
+/// JSON path selecting a top-level key of an `attributes` column
+///
+/// Attribute keys contain dots (e.g. `user.email`), so the key is quoted
+/// rather than treated as a nested path.
+fn attribute_path(key: &str) -> String {
+    format!("$.\"{}\"", key.replace('"', ""))
+}
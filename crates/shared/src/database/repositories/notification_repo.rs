@@ -118,6 +118,58 @@ impl NotificationRepository {
         Ok(rows.into_iter().map(Notification::from).collect())
     }
 
+    /// Find notifications created within a time range
+    pub async fn find_by_time_range(
+        pool: &SqlitePool,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<Notification>> {
+        let rows: Vec<NotificationRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM notifications
+            WHERE created_at >= ? AND created_at <= ?
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Notification::from).collect())
+    }
+
+    /// Find notifications created within a time range, optionally for one
+    /// session, with pagination (most recent first)
+    pub async fn find_filtered(
+        pool: &SqlitePool,
+        start_time: i64,
+        end_time: i64,
+        session_id: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Notification>> {
+        let rows: Vec<NotificationRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM notifications
+            WHERE created_at >= ? AND created_at <= ?
+              AND (? IS NULL OR session_id = ?)
+            ORDER BY created_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(start_time)
+        .bind(end_time)
+        .bind(session_id)
+        .bind(session_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Notification::from).collect())
+    }
+
     /// Get the count of unread notifications
     pub async fn unread_count(pool: &SqlitePool) -> Result<i64> {
         let row: (i64,) = sqlx::query_as(
@@ -130,4 +182,18 @@ impl NotificationRepository {
 
         Ok(row.0)
     }
+
+    /// Delete notifications created before a given timestamp
+    pub async fn delete_before(pool: &SqlitePool, timestamp: i64) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM notifications WHERE created_at < ?
+            "#,
+        )
+        .bind(timestamp)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
 }
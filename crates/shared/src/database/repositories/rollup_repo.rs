@@ -0,0 +1,443 @@
+//! Rollup repository
+//!
+//! Provides read operations for the rollups of events and metrics
+//! (maintained by triggers on insert, see the `create_rollups` migration).
+//!
+//! Rows are bucketed by UTC quarter hour and periods are formatted in local
+//! time, so grouping by local hour or day is exact in every time zone. Time
+//! bounds select the quarter hours starting within them.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::error::Result;
+
+/// Local time of a bucket formatted with the bound `strftime` format
+const PERIOD: &str = "strftime(?, datetime(bucket / 1000, 'unixepoch', 'localtime'))";
+
+/// How to group summed rollups
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RollupGroup<'a> {
+    /// `strftime` format of the local time to group by, e.g. "%Y-%m-%d"
+    pub period: Option<&'a str>,
+    /// Group by model as well
+    pub model: bool,
+}
+
+/// Summed event rollups of one group
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct EventTotals {
+    /// `None` unless grouped by period
+    pub period: Option<String>,
+    /// `None` unless grouped by model, or for events without a model
+    pub model: Option<String>,
+    pub event_count: i64,
+    pub cost_usd: f64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_creation_tokens: i64,
+}
+
+/// Summed metric rollups of one `metric_type` and `decision`
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricTotals {
+    pub metric_type: Option<String>,
+    pub decision: Option<String>,
+    pub sample_count: i64,
+    pub value: f64,
+}
+
+/// All-time event rollups of one model and telemetry source
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesTotals {
+    pub model: Option<String>,
+    pub source: Option<String>,
+    pub event_count: i64,
+    pub cost_usd: f64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_creation_tokens: i64,
+}
+
+/// Number of distinct sessions active in a period
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodCount {
+    pub period: String,
+    pub count: i64,
+}
+
+/// Repository for rollup operations
+pub struct RollupRepository;
+
+impl RollupRepository {
+    /// Sum events named `name` between two Unix millisecond timestamps,
+    /// optionally for one telemetry source
+    ///
+    /// Without grouping a single row is returned, even if nothing matched.
+    pub async fn sum_events(
+        pool: &SqlitePool,
+        name: &str,
+        start_time: i64,
+        end_time: i64,
+        source: Option<&str>,
+        group: RollupGroup<'_>,
+    ) -> Result<Vec<EventTotals>> {
+        let mut group_by = Vec::new();
+        if group.period.is_some() {
+            group_by.push("period");
+        }
+        if group.model {
+            group_by.push("model");
+        }
+        let group_by = if group_by.is_empty() {
+            String::new()
+        } else {
+            format!("GROUP BY {}", group_by.join(", "))
+        };
+
+        let query = format!(
+            r#"
+            SELECT
+                {} AS period,
+                {} AS model,
+                COALESCE(SUM(event_count), 0) AS event_count,
+                CAST(COALESCE(SUM(cost_usd), 0.0) AS REAL) AS cost_usd,
+                COALESCE(SUM(input_tokens), 0) AS input_tokens,
+                COALESCE(SUM(output_tokens), 0) AS output_tokens,
+                COALESCE(SUM(cache_read_tokens), 0) AS cache_read_tokens,
+                COALESCE(SUM(cache_creation_tokens), 0) AS cache_creation_tokens
+            FROM event_rollups
+            WHERE name = ? AND bucket >= ? AND bucket <= ?
+                AND (? IS NULL OR source = ?)
+            {}
+            ORDER BY MIN(bucket) ASC, model ASC
+            "#,
+            if group.period.is_some() {
+                PERIOD
+            } else {
+                "NULL"
+            },
+            if group.model {
+                "NULLIF(model, '')"
+            } else {
+                "NULL"
+            },
+            group_by
+        );
+
+        let mut query = sqlx::query_as(&query);
+        if let Some(period) = group.period {
+            query = query.bind(period);
+        }
+        let totals = query
+            .bind(name)
+            .bind(start_time)
+            .bind(end_time)
+            .bind(source)
+            .bind(source)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(totals)
+    }
+
+    /// Sum all events named `name` by model and telemetry source
+    pub async fn sum_events_by_series(pool: &SqlitePool, name: &str) -> Result<Vec<SeriesTotals>> {
+        let totals = sqlx::query_as(
+            r#"
+            SELECT
+                NULLIF(model, '') AS model,
+                NULLIF(source, '') AS source,
+                SUM(event_count) AS event_count,
+                CAST(SUM(cost_usd) AS REAL) AS cost_usd,
+                SUM(input_tokens) AS input_tokens,
+                SUM(output_tokens) AS output_tokens,
+                SUM(cache_read_tokens) AS cache_read_tokens,
+                SUM(cache_creation_tokens) AS cache_creation_tokens
+            FROM event_rollups
+            WHERE name = ?
+            GROUP BY model, source
+            ORDER BY model, source
+            "#,
+        )
+        .bind(name)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(totals)
+    }
+
+    /// Count all events named `name` by HTTP status code (`None` for events
+    /// without one)
+    pub async fn count_events_by_status_code(
+        pool: &SqlitePool,
+        name: &str,
+    ) -> Result<Vec<(Option<i64>, i64)>> {
+        let counts = sqlx::query_as(
+            r#"
+            SELECT NULLIF(status_code, 0), SUM(event_count)
+            FROM event_rollups
+            WHERE name = ?
+            GROUP BY status_code
+            ORDER BY status_code
+            "#,
+        )
+        .bind(name)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    /// Sum metrics named `name` between two Unix millisecond timestamps by
+    /// `metric_type` and `decision`, optionally for one telemetry source
+    pub async fn sum_metrics(
+        pool: &SqlitePool,
+        name: &str,
+        start_time: i64,
+        end_time: i64,
+        source: Option<&str>,
+    ) -> Result<Vec<MetricTotals>> {
+        let totals = sqlx::query_as(
+            r#"
+            SELECT
+                NULLIF(metric_type, '') AS metric_type,
+                NULLIF(decision, '') AS decision,
+                SUM(sample_count) AS sample_count,
+                CAST(SUM(value) AS REAL) AS value
+            FROM metric_rollups
+            WHERE name = ? AND bucket >= ? AND bucket <= ?
+                AND (? IS NULL OR source = ?)
+            GROUP BY metric_type, decision
+            "#,
+        )
+        .bind(name)
+        .bind(start_time)
+        .bind(end_time)
+        .bind(source)
+        .bind(source)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(totals)
+    }
+
+    /// Count distinct sessions with events in each period (a local time
+    /// `strftime` format) between two Unix millisecond timestamps
+    pub async fn count_sessions(
+        pool: &SqlitePool,
+        period: &str,
+        start_time: i64,
+        end_time: i64,
+        source: Option<&str>,
+    ) -> Result<Vec<PeriodCount>> {
+        let query = format!(
+            r#"
+            SELECT
+                {} AS period,
+                COUNT(DISTINCT session_id) AS count
+            FROM session_hours
+            WHERE bucket >= ? AND bucket <= ?
+                AND session_id != 'unknown'
+                AND (? IS NULL OR source = ?)
+            GROUP BY period
+            ORDER BY MIN(bucket) ASC
+            "#,
+            PERIOD
+        );
+
+        let counts = sqlx::query_as(&query)
+            .bind(period)
+            .bind(start_time)
+            .bind(end_time)
+            .bind(source)
+            .bind(source)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rollups_follow_inserts() {
+        let db_path = std::env::temp_dir().join(format!("lumo-rollup-{}.db", uuid::Uuid::new_v4()));
+        let pool = crate::create_pool(&db_path).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+
+        // Retried rows are ignored by the insert and must not be counted twice
+        for _ in 0..2 {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO events (id, session_id, name, timestamp, model, cost_usd, input_tokens, source)
+                VALUES
+                    ('a', 's1', 'claude_code.api_request', 3600000, 'opus', 1.5, 100, 'claude_code'),
+                    ('b', 's2', 'claude_code.api_request', 3600001, 'opus', 0.5, 10, 'codex'),
+                    ('c', 's1', 'claude_code.api_request', 7200000, NULL, 1.0, 1, 'claude_code')
+                "#,
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let by_model = RollupRepository::sum_events(
+            &pool,
+            "claude_code.api_request",
+            0,
+            i64::MAX,
+            None,
+            RollupGroup {
+                period: None,
+                model: true,
+            },
+        )
+        .await
+        .unwrap();
+        let summary: Vec<_> = by_model
+            .iter()
+            .map(|t| {
+                (
+                    t.model.as_deref(),
+                    t.event_count,
+                    t.cost_usd,
+                    t.input_tokens,
+                )
+            })
+            .collect();
+        assert_eq!(summary, [(Some("opus"), 2, 2.0, 110), (None, 1, 1.0, 1)]);
+
+        let codex = RollupRepository::sum_events(
+            &pool,
+            "claude_code.api_request",
+            0,
+            i64::MAX,
+            Some("codex"),
+            RollupGroup::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(codex[0].event_count, 1);
+
+        // Raw rows can be pruned without losing the totals
+        sqlx::query("DELETE FROM events")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let sessions = RollupRepository::count_sessions(&pool, "%Y", 0, i64::MAX, None)
+            .await
+            .unwrap();
+        assert_eq!(sessions[0].count, 2);
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[tokio::test]
+    async fn test_rollups_bucket_by_quarter_hour() {
+        const HOUR: i64 = 3_600_000;
+
+        let db_path =
+            std::env::temp_dir().join(format!("lumo-buckets-{}.db", uuid::Uuid::new_v4()));
+        let pool = crate::create_pool(&db_path).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+
+        // The half hours of one UTC hour fall into different local hours at
+        // half-hour offsets, so they must not share a bucket
+        sqlx::query(
+            r#"
+            INSERT INTO events (id, session_id, name, timestamp, model, cost_usd)
+            VALUES
+                ('a', 's1', 'claude_code.api_request', 3600000, 'opus', 1.0),
+                ('b', 's1', 'claude_code.api_request', 5400000, 'opus', 2.0),
+                ('c', 's2', 'claude_code.api_request', 6300000, 'opus', 4.0),
+                ('d', 's2', 'claude_code.api_request', 6400000, 'opus', 8.0)
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let buckets: Vec<(i64, i64, f64)> = sqlx::query_as(
+            "SELECT bucket, event_count, cost_usd FROM event_rollups ORDER BY bucket",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            buckets,
+            [
+                (HOUR, 1, 1.0),
+                (HOUR + 1_800_000, 1, 2.0),
+                (HOUR + 2_700_000, 2, 12.0)
+            ]
+        );
+
+        let sessions: Vec<(i64, String)> =
+            sqlx::query_as("SELECT bucket, session_id FROM session_hours ORDER BY bucket")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        let sessions: Vec<_> = sessions
+            .iter()
+            .map(|(bucket, id)| (*bucket, id.as_str()))
+            .collect();
+        assert_eq!(
+            sessions,
+            [
+                (HOUR, "s1"),
+                (HOUR + 1_800_000, "s1"),
+                (HOUR + 2_700_000, "s2")
+            ]
+        );
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[tokio::test]
+    async fn test_api_errors_by_status_code_survive_pruning() {
+        let db_path = std::env::temp_dir().join(format!("lumo-errors-{}.db", uuid::Uuid::new_v4()));
+        let pool = crate::create_pool(&db_path).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+
+        for (id, status_code) in [
+            ("a", Some(429)),
+            ("b", Some(500)),
+            ("c", Some(500)),
+            ("d", None),
+        ] {
+            sqlx::query(
+                "INSERT INTO events (id, session_id, name, timestamp, status_code)
+                 VALUES (?, 's1', 'claude_code.api_error', 1000, ?)",
+            )
+            .bind(id)
+            .bind(status_code)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        sqlx::query("DELETE FROM events")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let counts = RollupRepository::count_events_by_status_code(&pool, "claude_code.api_error")
+            .await
+            .unwrap();
+        assert_eq!(counts, [(None, 1), (Some(429), 1), (Some(500), 2)]);
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&db_path);
+    }
+}
@@ -1,22 +1,271 @@
 //! Session repository
 //!
-//! Provides read operations for sessions (from the sessions view).
+//! Provides operations for the sessions table, which the daemon keeps up to
+//! date from events and hooks.
 
-use sqlx::SqlitePool;
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
 
 use crate::database::entities::Session;
 use crate::error::{Error, Result};
 
+/// Aggregate the events of a preceding `batch` CTE into session rows
+///
+/// Sessions are inserted as active. Followed by the updates applied to
+/// existing rows: [`ADD_EVENTS`] or [`REPLACE_EVENTS`], then [`STATUS`].
+const UPSERT_FROM_BATCH: &str = r#"
+INSERT INTO sessions (
+    id, start_time, end_time, duration_ms,
+    event_count, api_request_count, error_count, tool_use_count, prompt_count,
+    total_cost_usd, total_input_tokens, total_output_tokens,
+    total_cache_read_tokens, total_cache_creation_tokens,
+    models, last_event_name,
+    account_uuid, organization_id, terminal_type, app_version, source,
+    status, status_changed_at
+)
+SELECT
+    e.session_id,
+    MIN(e.timestamp),
+    MAX(e.timestamp),
+    MAX(e.timestamp) - MIN(e.timestamp),
+
+    COUNT(*),
+    COUNT(CASE WHEN e.name = 'claude_code.api_request' THEN 1 END),
+    COUNT(CASE WHEN e.name = 'claude_code.api_error' THEN 1 END),
+    COUNT(CASE WHEN e.name = 'claude_code.tool_result' THEN 1 END),
+    COUNT(CASE WHEN e.name = 'claude_code.user_prompt' THEN 1 END),
+
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.cost_usd END), 0),
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.input_tokens END), 0),
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.output_tokens END), 0),
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.cache_read_tokens END), 0),
+    COALESCE(SUM(CASE WHEN e.name = 'claude_code.api_request' THEN e.cache_creation_tokens END), 0),
+
+    (
+        SELECT json_group_array(model) FROM (
+            SELECT DISTINCT m.model FROM batch m
+            WHERE m.session_id = e.session_id AND m.model IS NOT NULL
+            ORDER BY m.model
+        )
+    ),
+    (
+        SELECT l.name FROM batch l
+        WHERE l.session_id = e.session_id
+        ORDER BY l.timestamp DESC, l.event_sequence DESC
+        LIMIT 1
+    ),
+
+    MAX(e.account_uuid),
+    MAX(e.organization_id),
+    MAX(e.terminal_type),
+    MAX(e.app_version),
+    MAX(e.source),
+
+    'active',
+    MAX(e.timestamp)
+FROM batch e
+GROUP BY e.session_id
+ON CONFLICT (id) DO UPDATE SET
+"#;
+
+/// Add the aggregated events to existing sessions
+///
+/// Events already counted may have been pruned since, so sessions are only
+/// ever extended. Sessions created by a hook have no events to keep and
+/// take the time range of the new ones.
+const ADD_EVENTS: &str = r#"
+    start_time = CASE
+        WHEN sessions.event_count = 0 THEN excluded.start_time
+        ELSE MIN(sessions.start_time, excluded.start_time)
+    END,
+    end_time = CASE
+        WHEN sessions.event_count = 0 THEN excluded.end_time
+        ELSE MAX(sessions.end_time, excluded.end_time)
+    END,
+    duration_ms = CASE
+        WHEN sessions.event_count = 0 THEN excluded.duration_ms
+        ELSE MAX(sessions.end_time, excluded.end_time)
+            - MIN(sessions.start_time, excluded.start_time)
+    END,
+    event_count = sessions.event_count + excluded.event_count,
+    api_request_count = sessions.api_request_count + excluded.api_request_count,
+    error_count = sessions.error_count + excluded.error_count,
+    tool_use_count = sessions.tool_use_count + excluded.tool_use_count,
+    prompt_count = sessions.prompt_count + excluded.prompt_count,
+    total_cost_usd = sessions.total_cost_usd + excluded.total_cost_usd,
+    total_input_tokens = sessions.total_input_tokens + excluded.total_input_tokens,
+    total_output_tokens = sessions.total_output_tokens + excluded.total_output_tokens,
+    total_cache_read_tokens = sessions.total_cache_read_tokens + excluded.total_cache_read_tokens,
+    total_cache_creation_tokens =
+        sessions.total_cache_creation_tokens + excluded.total_cache_creation_tokens,
+    models = (
+        SELECT json_group_array(value) FROM (
+            SELECT value FROM json_each(sessions.models)
+            UNION
+            SELECT value FROM json_each(excluded.models)
+            ORDER BY value
+        )
+    ),
+    last_event_name = CASE
+        WHEN sessions.event_count = 0 OR excluded.end_time >= sessions.end_time
+        THEN excluded.last_event_name
+        ELSE sessions.last_event_name
+    END,
+    account_uuid = CASE
+        WHEN sessions.account_uuid IS NULL OR excluded.account_uuid > sessions.account_uuid
+        THEN excluded.account_uuid
+        ELSE sessions.account_uuid
+    END,
+    organization_id = CASE
+        WHEN sessions.organization_id IS NULL
+            OR excluded.organization_id > sessions.organization_id
+        THEN excluded.organization_id
+        ELSE sessions.organization_id
+    END,
+    terminal_type = CASE
+        WHEN sessions.terminal_type IS NULL OR excluded.terminal_type > sessions.terminal_type
+        THEN excluded.terminal_type
+        ELSE sessions.terminal_type
+    END,
+    app_version = CASE
+        WHEN sessions.app_version IS NULL OR excluded.app_version > sessions.app_version
+        THEN excluded.app_version
+        ELSE sessions.app_version
+    END,
+    source = CASE
+        WHEN sessions.source IS NULL OR excluded.source > sessions.source
+        THEN excluded.source
+        ELSE sessions.source
+    END,
+"#;
+
+/// Replace existing sessions with the aggregated events
+///
+/// Only applied to sessions none of whose events were pruned, see
+/// [`SessionRepository::rebuild`].
+const REPLACE_EVENTS: &str = r#"
+    start_time = excluded.start_time,
+    end_time = excluded.end_time,
+    duration_ms = excluded.duration_ms,
+    event_count = excluded.event_count,
+    api_request_count = excluded.api_request_count,
+    error_count = excluded.error_count,
+    tool_use_count = excluded.tool_use_count,
+    prompt_count = excluded.prompt_count,
+    total_cost_usd = excluded.total_cost_usd,
+    total_input_tokens = excluded.total_input_tokens,
+    total_output_tokens = excluded.total_output_tokens,
+    total_cache_read_tokens = excluded.total_cache_read_tokens,
+    total_cache_creation_tokens = excluded.total_cache_creation_tokens,
+    models = excluded.models,
+    last_event_name = excluded.last_event_name,
+    account_uuid = excluded.account_uuid,
+    organization_id = excluded.organization_id,
+    terminal_type = excluded.terminal_type,
+    app_version = excluded.app_version,
+    source = excluded.source,
+"#;
+
+/// Keep the hook-provided status unless events newer than it arrived, which
+/// make the session active again
+const STATUS: &str = r#"
+    status = CASE
+        WHEN excluded.end_time > COALESCE(sessions.status_changed_at, 0) THEN 'active'
+        ELSE sessions.status
+    END,
+    status_changed_at = CASE
+        WHEN sessions.status != 'active'
+            AND excluded.end_time > COALESCE(sessions.status_changed_at, 0)
+        THEN excluded.end_time
+        ELSE sessions.status_changed_at
+    END
+"#;
+
 /// Repository for session operations
 pub struct SessionRepository;
 
 impl SessionRepository {
+    /// Add newly inserted events to their sessions
+    ///
+    /// Called by [`EventRepository::insert_batch`](super::EventRepository::insert_batch)
+    /// within its transaction, with the ids of the rows it inserted.
+    pub(crate) async fn add_events(
+        conn: &mut SqliteConnection,
+        event_ids: &[String],
+    ) -> Result<()> {
+        if event_ids.is_empty() {
+            return Ok(());
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "WITH batch AS (SELECT * FROM events WHERE id IN (SELECT value FROM json_each(",
+        );
+        query.push_bind(serde_json::to_string(event_ids)?);
+        query.push(")))");
+        query.push(UPSERT_FROM_BATCH);
+        query.push(ADD_EVENTS);
+        query.push(STATUS);
+        query.build().execute(conn).await?;
+
+        Ok(())
+    }
+
+    /// Recompute every session from its events
+    ///
+    /// Returns the number of sessions written. Sessions whose events were
+    /// partly or all deleted keep their last state.
+    pub async fn rebuild(pool: &SqlitePool) -> Result<u64> {
+        let mut query = QueryBuilder::<Sqlite>::new("WITH batch AS (SELECT * FROM events)");
+        query.push(UPSERT_FROM_BATCH);
+        query.push(REPLACE_EVENTS);
+        query.push(STATUS);
+        query.push("WHERE sessions.event_count <= excluded.event_count");
+        let result = query.build().execute(pool).await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Record a Claude Code hook for a session
+    ///
+    /// Sets the working directory when the hook reports one, and the status
+    /// when it is `Some` ("active", "idle" or "ended"). A session unknown so
+    /// far is created without events; it is left out of listings and counts
+    /// until its first events arrive.
+    pub async fn record_hook(
+        pool: &SqlitePool,
+        session_id: &str,
+        status: Option<&str>,
+        cwd: Option<&str>,
+        timestamp: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (id, start_time, end_time, cwd, status, status_changed_at)
+            VALUES (?1, ?2, ?2, ?3, COALESCE(?4, 'active'), ?2)
+            ON CONFLICT (id) DO UPDATE SET
+                cwd = COALESCE(excluded.cwd, sessions.cwd),
+                status = COALESCE(?4, sessions.status),
+                status_changed_at = CASE
+                    WHEN ?4 IS NULL THEN sessions.status_changed_at
+                    ELSE excluded.status_changed_at
+                END
+            "#,
+        )
+        .bind(session_id)
+        .bind(timestamp)
+        .bind(cwd)
+        .bind(status)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Find all sessions ordered by start time (most recent first)
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Session>> {
         let sessions: Vec<Session> = sqlx::query_as(
             r#"
             SELECT * FROM sessions
-            WHERE id != 'unknown'
+            WHERE id != 'unknown' AND event_count > 0
             ORDER BY start_time DESC
             "#,
         )
@@ -35,7 +284,7 @@ impl SessionRepository {
         let sessions: Vec<Session> = sqlx::query_as(
             r#"
             SELECT * FROM sessions
-            WHERE id != 'unknown'
+            WHERE id != 'unknown' AND event_count > 0
             ORDER BY start_time DESC
             LIMIT ? OFFSET ?
             "#,
@@ -73,7 +322,7 @@ impl SessionRepository {
             r#"
             SELECT * FROM sessions
             WHERE start_time <= ? AND end_time >= ?
-              AND id != 'unknown'
+              AND id != 'unknown' AND event_count > 0
             ORDER BY start_time DESC
             "#,
         )
@@ -85,12 +334,90 @@ impl SessionRepository {
         Ok(sessions)
     }
 
+    /// Find sessions active within a time range, optionally for one telemetry
+    /// source and status, with pagination (most recent first)
+    pub async fn find_filtered(
+        pool: &SqlitePool,
+        start_time: i64,
+        end_time: i64,
+        source: Option<&str>,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Session>> {
+        let sessions: Vec<Session> = sqlx::query_as(
+            r#"
+            SELECT * FROM sessions
+            WHERE start_time <= ? AND end_time >= ?
+              AND id != 'unknown' AND event_count > 0
+              AND (? IS NULL OR source = ?)
+              AND (? IS NULL OR status = ?)
+            ORDER BY start_time DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(end_time)
+        .bind(start_time)
+        .bind(source)
+        .bind(source)
+        .bind(status)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    /// Count sessions active within a time range, optionally for one
+    /// telemetry source
+    pub async fn count_by_time_range(
+        pool: &SqlitePool,
+        start_time: i64,
+        end_time: i64,
+        source: Option<&str>,
+    ) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM sessions
+            WHERE start_time <= ? AND end_time >= ?
+              AND id != 'unknown' AND event_count > 0
+              AND (? IS NULL OR source = ?)
+            "#,
+        )
+        .bind(end_time)
+        .bind(start_time)
+        .bind(source)
+        .bind(source)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Count sessions by status
+    pub async fn count_by_status(pool: &SqlitePool) -> Result<Vec<(String, i64)>> {
+        let counts = sqlx::query_as(
+            r#"
+            SELECT status, COUNT(*) FROM sessions
+            WHERE id != 'unknown' AND event_count > 0
+            GROUP BY status
+            ORDER BY status
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(counts)
+    }
+
     /// Count total number of sessions
     pub async fn count(pool: &SqlitePool) -> Result<i64> {
         let (count,): (i64,) = sqlx::query_as(
             r#"
             SELECT COUNT(*) FROM sessions
-            WHERE id != 'unknown'
+            WHERE id != 'unknown' AND event_count > 0
             "#,
         )
         .fetch_one(pool)
@@ -104,7 +431,7 @@ impl SessionRepository {
         let (total,): (f64,) = sqlx::query_as(
             r#"
             SELECT COALESCE(SUM(total_cost_usd), 0) FROM sessions
-            WHERE id != 'unknown'
+            WHERE id != 'unknown' AND event_count > 0
             "#,
         )
         .fetch_one(pool)
@@ -122,7 +449,7 @@ impl SessionRepository {
                 COALESCE(SUM(total_output_tokens), 0) as output_tokens,
                 COALESCE(SUM(total_cache_read_tokens), 0) as cache_read_tokens
             FROM sessions
-            WHERE id != 'unknown'
+            WHERE id != 'unknown' AND event_count > 0
             "#,
         )
         .fetch_one(pool)
@@ -144,7 +471,7 @@ impl SessionRepository {
                 COALESCE(SUM(error_count), 0) as total_errors,
                 COALESCE(SUM(tool_use_count), 0) as total_tool_uses
             FROM sessions
-            WHERE id != 'unknown'
+            WHERE id != 'unknown' AND event_count > 0
             "#,
         )
         .fetch_one(pool)
@@ -217,3 +544,138 @@ impl From<SessionsSummaryRow> for SessionsSummary {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn insert_event(pool: &SqlitePool, id: &str, name: &str, timestamp: i64, model: &str) {
+        sqlx::query(
+            "INSERT INTO events (id, session_id, name, timestamp, model, cost_usd) VALUES (?, 's1', ?, ?, ?, 0.5)",
+        )
+        .bind(id)
+        .bind(name)
+        .bind(timestamp)
+        .bind(model)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sessions_follow_events_and_hooks() {
+        let db_path =
+            std::env::temp_dir().join(format!("lumo-sessions-{}.db", uuid::Uuid::new_v4()));
+        let pool = crate::create_pool(&db_path).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+
+        insert_event(&pool, "a", "claude_code.api_request", 1000, "sonnet").await;
+        insert_event(&pool, "b", "claude_code.api_request", 2000, "opus").await;
+        SessionRepository::rebuild(&pool).await.unwrap();
+        SessionRepository::record_hook(&pool, "s1", Some("idle"), Some("/work"), 3000)
+            .await
+            .unwrap();
+
+        let session = SessionRepository::find_by_id(&pool, "s1").await.unwrap();
+        assert_eq!(session.api_request_count, 2);
+        assert_eq!(session.total_cost_usd, 1.0);
+        assert_eq!(session.models, ["opus", "sonnet"]);
+        assert_eq!(session.cwd.as_deref(), Some("/work"));
+        assert_eq!(session.status, "idle");
+
+        // A hook alone does not make a session count
+        SessionRepository::record_hook(&pool, "s2", Some("active"), None, 3000)
+            .await
+            .unwrap();
+        assert_eq!(SessionRepository::count(&pool).await.unwrap(), 1);
+        assert_eq!(
+            SessionRepository::count_by_status(&pool).await.unwrap(),
+            [("idle".to_string(), 1)]
+        );
+        assert_eq!(
+            SessionRepository::count_by_time_range(&pool, 0, 5000, None)
+                .await
+                .unwrap(),
+            1
+        );
+
+        // Late events from before the hook keep the session idle; newer ones
+        // make it active again
+        insert_event(&pool, "c", "claude_code.tool_result", 2500, "opus").await;
+        SessionRepository::rebuild(&pool).await.unwrap();
+        let session = SessionRepository::find_by_id(&pool, "s1").await.unwrap();
+        assert_eq!(session.status, "idle");
+        assert_eq!(
+            session.last_event_name.as_deref(),
+            Some("claude_code.tool_result")
+        );
+
+        insert_event(&pool, "d", "claude_code.user_prompt", 4000, "opus").await;
+        SessionRepository::rebuild(&pool).await.unwrap();
+        let session = SessionRepository::find_by_id(&pool, "s1").await.unwrap();
+        assert_eq!(session.status, "active");
+        assert_eq!(session.event_count, 4);
+        assert_eq!(session.cwd.as_deref(), Some("/work"));
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_survive_pruning() {
+        let db_path =
+            std::env::temp_dir().join(format!("lumo-sessions-{}.db", uuid::Uuid::new_v4()));
+        let pool = crate::create_pool(&db_path).await.unwrap();
+        crate::run_migrations(&pool).await.unwrap();
+
+        let event = |id: &str, timestamp: i64, model: &str| -> crate::NewEvent {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "session_id": "s1",
+                "name": "claude_code.api_request",
+                "timestamp": timestamp,
+                "model": model,
+                "cost_usd": 0.5,
+                "input_tokens": 10,
+            }))
+            .unwrap()
+        };
+
+        crate::EventRepository::insert_batch(
+            &pool,
+            &[event("a", 1000, "sonnet"), event("b", 2000, "opus")],
+        )
+        .await
+        .unwrap();
+        crate::EventRepository::delete_before(&pool, 1500)
+            .await
+            .unwrap();
+
+        // A retried event is not counted twice
+        crate::EventRepository::insert_batch(
+            &pool,
+            &[event("b", 2000, "opus"), event("c", 3000, "opus")],
+        )
+        .await
+        .unwrap();
+
+        let session = SessionRepository::find_by_id(&pool, "s1").await.unwrap();
+        assert_eq!(session.start_time, 1000);
+        assert_eq!(session.end_time, 3000);
+        assert_eq!(session.duration_ms, 2000);
+        assert_eq!(session.event_count, 3);
+        assert_eq!(session.api_request_count, 3);
+        assert_eq!(session.total_cost_usd, 1.5);
+        assert_eq!(session.total_input_tokens, 30);
+        assert_eq!(session.models, ["opus", "sonnet"]);
+
+        // Rebuilding leaves the partly pruned session alone
+        assert_eq!(SessionRepository::rebuild(&pool).await.unwrap(), 0);
+        let rebuilt = SessionRepository::find_by_id(&pool, "s1").await.unwrap();
+        assert_eq!(rebuilt.event_count, 3);
+        assert_eq!(rebuilt.start_time, 1000);
+
+        pool.close().await;
+        let _ = std::fs::remove_file(&db_path);
+    }
+}
@@ -0,0 +1,161 @@
+//! Span repository
+//!
+//! Provides CRUD operations for trace spans.
+
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use super::INSERT_CHUNK_SIZE;
+use crate::database::entities::{NewSpan, Span, SpanRow};
+use crate::error::Result;
+
+/// Repository for span operations
+pub struct SpanRepository;
+
+impl SpanRepository {
+    /// Insert a new span
+    ///
+    /// Spans carry their own (trace_id, span_id) identity, so a re-exported
+    /// span is ignored rather than duplicated.
+    pub async fn insert(pool: &SqlitePool, span: &NewSpan) -> Result<()> {
+        Self::insert_batch(pool, std::slice::from_ref(span)).await
+    }
+
+    /// Insert multiple spans in a batch
+    ///
+    /// All rows are written in a single transaction using multi-row
+    /// `INSERT`s, so a failure rejects the whole batch.
+    pub async fn insert_batch(pool: &SqlitePool, spans: &[NewSpan]) -> Result<()> {
+        if spans.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = pool.begin().await?;
+
+        for chunk in spans.chunks(INSERT_CHUNK_SIZE) {
+            let mut query = QueryBuilder::<Sqlite>::new(
+                r#"
+                INSERT OR IGNORE INTO spans (
+                    trace_id, span_id, parent_span_id, session_id,
+                    name, kind,
+                    start_time, end_time, duration_ms,
+                    status_code, status_message,
+                    attributes, events,
+                    scope_name, resource
+                )
+                "#,
+            );
+
+            query.push_values(chunk, |mut row, span| {
+                row.push_bind(&span.trace_id)
+                    .push_bind(&span.span_id)
+                    .push_bind(&span.parent_span_id)
+                    .push_bind(&span.session_id)
+                    .push_bind(&span.name)
+                    .push_bind(&span.kind)
+                    .push_bind(span.start_time)
+                    .push_bind(span.end_time)
+                    .push_bind(span.duration_ms)
+                    .push_bind(&span.status_code)
+                    .push_bind(&span.status_message)
+                    .push_bind(&span.attributes)
+                    .push_bind(&span.events)
+                    .push_bind(&span.scope_name)
+                    .push_bind(&span.resource);
+            });
+
+            query.build().execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Find all spans for a session, ordered so parents precede children
+    pub async fn find_by_session(pool: &SqlitePool, session_id: &str) -> Result<Vec<Span>> {
+        let rows: Vec<SpanRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM spans
+            WHERE session_id = ?
+            ORDER BY start_time ASC, end_time DESC
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Span::from).collect())
+    }
+
+    /// Find all spans belonging to a trace
+    pub async fn find_by_trace(pool: &SqlitePool, trace_id: &str) -> Result<Vec<Span>> {
+        let rows: Vec<SpanRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM spans
+            WHERE trace_id = ?
+            ORDER BY start_time ASC, end_time DESC
+            "#,
+        )
+        .bind(trace_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Span::from).collect())
+    }
+
+    /// Find spans that started within a time range
+    pub async fn find_by_time_range(
+        pool: &SqlitePool,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<Span>> {
+        let rows: Vec<SpanRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM spans
+            WHERE start_time >= ? AND start_time <= ?
+            ORDER BY start_time ASC
+            "#,
+        )
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Span::from).collect())
+    }
+
+    /// Find the direct children of a span
+    pub async fn find_children(
+        pool: &SqlitePool,
+        trace_id: &str,
+        parent_span_id: &str,
+    ) -> Result<Vec<Span>> {
+        let rows: Vec<SpanRow> = sqlx::query_as(
+            r#"
+            SELECT * FROM spans
+            WHERE trace_id = ? AND parent_span_id = ?
+            ORDER BY start_time ASC
+            "#,
+        )
+        .bind(trace_id)
+        .bind(parent_span_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Span::from).collect())
+    }
+
+    /// Delete spans that started before a given timestamp
+    pub async fn delete_before(pool: &SqlitePool, timestamp: i64) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM spans WHERE start_time < ?
+            "#,
+        )
+        .bind(timestamp)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -25,4 +25,7 @@ pub enum Error {
 
     #[error("Invalid data: {0}")]
     InvalidData(String),
+
+    #[error("{0}")]
+    Config(String),
 }
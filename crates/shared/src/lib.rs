@@ -3,17 +3,30 @@
 //! Contains database entities, repositories, and utilities shared between
 //! the daemon and Tauri application.
 
+pub mod auth;
+pub mod config;
 pub mod database;
 pub mod error;
+pub mod retention;
 
 // Re-export commonly used types
+pub use auth::{get_socket_path, get_token_path, load_or_create_token};
+pub use config::DaemonConfig;
 pub use database::connection::{create_pool, get_db_path, run_migrations};
 pub use database::entities::{
-    Event, EventRow, Metric, MetricRow, NewEvent, NewMetric, NewNotification, Notification,
-    NotificationRow, Session,
+    AttributeCatalogEntry, AttributeCatalogRow, Event, EventRow, Metric, MetricBucket,
+    MetricQuantile, MetricRow, NewAttributeCatalogEntry, NewEvent, NewMetric, NewNotification,
+    NewSpan, Notification, NotificationRow, Session, Span, SpanRow,
+};
+pub use database::maintenance::{
+    enable_incremental_vacuum, integrity_check, migration_version, pending_migrations,
+    reclaim_space, vacuum,
 };
 pub use database::repositories::{
-    EventRepository, MetricRepository, NotificationRepository, SessionRepository, SessionsSummary,
+    AttributeCatalogRepository, AttributeGroup, EventRepository, EventTotals, MetricPercentile,
+    MetricRepository, MetricTotals, NotificationRepository, PeriodCount, RollupGroup,
+    RollupRepository, SeriesTotals, SessionRepository, SessionsSummary, SpanRepository,
     TokenUsageByModel, TotalTokens,
 };
 pub use error::{Error, Result};
+pub use retention::{get_retention_path, RetentionPolicy};
@@ -0,0 +1,79 @@
+//! Retention policy
+//!
+//! How long the daemon keeps raw telemetry, per table. The policy lives in
+//! `~/.lumo/retention.json`; the desktop app edits it and the daemon picks up
+//! changes without a restart.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::database::connection::get_db_path;
+use crate::error::{Error, Result};
+
+/// Get the retention policy file path (~/.lumo/retention.json)
+pub fn get_retention_path() -> Result<PathBuf> {
+    Ok(get_db_path()?.with_file_name("retention.json"))
+}
+
+/// Number of days to keep rows of each table; `None` keeps them forever
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionPolicy {
+    #[serde(default)]
+    pub events_days: Option<u32>,
+    #[serde(default)]
+    pub metrics_days: Option<u32>,
+    #[serde(default)]
+    pub spans_days: Option<u32>,
+    #[serde(default)]
+    pub notifications_days: Option<u32>,
+}
+
+impl RetentionPolicy {
+    /// Read the policy from `path`; a missing file keeps everything
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let policy: Self = serde_json::from_str(&contents)?;
+        policy.validate()?;
+
+        Ok(policy)
+    }
+
+    /// Write the policy to `path`
+    ///
+    /// The file is replaced atomically so the daemon never reads half of it.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.validate()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp_path, path)?;
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        let days = [
+            self.events_days,
+            self.metrics_days,
+            self.spans_days,
+            self.notifications_days,
+        ];
+        if days.contains(&Some(0)) {
+            return Err(Error::InvalidData(
+                "Retention must be at least one day".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
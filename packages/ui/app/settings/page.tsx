@@ -0,0 +1,5 @@
+import { Settings } from "@/modules/settings";
+
+export default function SettingsPage() {
+  return <Settings />;
+}
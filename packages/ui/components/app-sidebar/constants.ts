@@ -4,6 +4,7 @@ import {
   Wrench,
   BarChart3,
   Sparkles,
+  Settings,
 } from "lucide-react";
 import type { NavItem } from "./types";
 
@@ -33,4 +34,9 @@ export const NAV_ITEMS = [
     label: "Wrapped",
     icon: Sparkles,
   },
+  {
+    id: "settings",
+    label: "Settings",
+    icon: Settings,
+  },
 ] as const satisfies readonly NavItem[];
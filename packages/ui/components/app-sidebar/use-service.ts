@@ -10,6 +10,7 @@ const ROUTE_MAP: Record<string, string> = {
   tools: "/tools",
   analytics: "/analytics",
   wrapped: "/wrapped",
+  settings: "/settings",
 } as const;
 
 const NAV_ROUTES = [
@@ -17,6 +18,7 @@ const NAV_ROUTES = [
   { prefix: "/tools", id: "tools" },
   { prefix: "/analytics", id: "analytics" },
   { prefix: "/wrapped", id: "wrapped" },
+  { prefix: "/settings", id: "settings" },
 ] as const;
 
 function resolveActiveItem(pathname: string): string {
@@ -25,9 +25,9 @@ function shortenModel(model: string): string {
   return `${name} ${version}`;
 }
 
-export function CostChart({ timeRange }: CostChartProps) {
+export function CostChart({ timeRange, source }: CostChartProps) {
   const { dates, models, seriesMap, totalCost, isLoading, error, refetch } =
-    useService(timeRange);
+    useService(timeRange, source);
 
   if (isLoading) {
     return <CardLoading showTitle className="h-full" />;
@@ -2,4 +2,6 @@ import type { TimeRange } from "@/src/generated/typeshare-types";
 
 export interface CostChartProps {
   timeRange: TimeRange;
+  /** Telemetry source to show, or undefined for all sources */
+  source?: string;
 }
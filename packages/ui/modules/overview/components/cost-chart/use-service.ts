@@ -6,11 +6,11 @@ import { TrendsBridge } from "@/src/bridges/trends-bridge";
 import type { TimeRange } from "@/src/generated/typeshare-types";
 import { foregroundRefreshQueryOptions } from "@/src/lib/query-options";
 
-export function useService(timeRange: TimeRange) {
+export function useService(timeRange: TimeRange, source?: string) {
   const { data: raw, isLoading, error, refetch } = useQuery({
     ...foregroundRefreshQueryOptions,
-    queryKey: ["cost-by-model-trends", timeRange],
-    queryFn: () => TrendsBridge.getCostByModelTrends(timeRange),
+    queryKey: ["cost-by-model-trends", timeRange, source],
+    queryFn: () => TrendsBridge.getCostByModelTrends(timeRange, source),
   });
 
   const { dates, models, seriesMap, totalCost } = useMemo(() => {
@@ -1,4 +1,5 @@
 export { TimeRangeTabs } from "./time-range-tabs";
+export { SourceSelect } from "./source-select";
 export { StatCards } from "./stat-cards";
 export { CostChart } from "./cost-chart";
 export { ActivityHeatmap } from "./activity-heatmap";
@@ -0,0 +1,37 @@
+"use client";
+
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+
+/** Select value standing for every source */
+const ALL_SOURCES = "all";
+
+interface SourceSelectProps {
+  /** Telemetry source, or undefined for all sources */
+  value: string | undefined;
+  onChange: (value: string | undefined) => void;
+}
+
+export function SourceSelect({ value, onChange }: SourceSelectProps) {
+  return (
+    <Select
+      value={value ?? ALL_SOURCES}
+      onValueChange={(v) => onChange(v === ALL_SOURCES ? undefined : v)}
+    >
+      <SelectTrigger className="w-36 h-8 text-sm">
+        <SelectValue />
+      </SelectTrigger>
+      <SelectContent>
+        <SelectItem value={ALL_SOURCES}>All Agents</SelectItem>
+        <SelectItem value="claude_code">Claude Code</SelectItem>
+        <SelectItem value="codex">Codex</SelectItem>
+        <SelectItem value="gemini_cli">Gemini CLI</SelectItem>
+      </SelectContent>
+    </Select>
+  );
+}
@@ -8,8 +8,8 @@ import { formatValue } from "@/lib/format";
 import { useService } from "./use-service";
 import type { StatCardsProps } from "./types";
 
-export function StatCards({ timeRange }: StatCardsProps) {
-  const { stats, isLoading, error, refetch } = useService(timeRange);
+export function StatCards({ timeRange, source }: StatCardsProps) {
+  const { stats, isLoading, error, refetch } = useService(timeRange, source);
 
   if (isLoading) {
     return (
@@ -22,11 +22,11 @@ const DEFAULT_STATS: SummaryStats = {
   codeEditRejects: 0,
 };
 
-export function useService(timeRange: TimeRange) {
+export function useService(timeRange: TimeRange, source?: string) {
   const { data, isLoading, error, refetch } = useQuery({
     ...foregroundRefreshQueryOptions,
-    queryKey: ["summary-stats", timeRange],
-    queryFn: () => StatsBridge.getSummaryStats(timeRange),
+    queryKey: ["summary-stats", timeRange, source],
+    queryFn: () => StatsBridge.getSummaryStats(timeRange, source),
   });
 
   return {
@@ -3,6 +3,7 @@
 import { PageHeader } from "@/components/page-header";
 import {
   TimeRangeTabs,
+  SourceSelect,
   StatCards,
   CostChart,
   ActivityHeatmap,
@@ -10,18 +11,21 @@ import {
 import { useService } from "./use-service";
 
 export function Overview() {
-  const { timeRange, setTimeRange } = useService();
+  const { timeRange, setTimeRange, source, setSource } = useService();
 
   return (
     <div className="flex h-full flex-col overflow-hidden">
       <PageHeader title="Overview">
-        <TimeRangeTabs value={timeRange} onChange={setTimeRange} />
+        <div className="flex items-center gap-2">
+          <SourceSelect value={source} onChange={setSource} />
+          <TimeRangeTabs value={timeRange} onChange={setTimeRange} />
+        </div>
       </PageHeader>
 
       <div className="flex-1 overflow-y-auto bg-muted/40">
         <div className="mx-auto max-w-6xl space-y-6 p-6">
-          <StatCards timeRange={timeRange} />
-          <CostChart timeRange={timeRange} />
+          <StatCards timeRange={timeRange} source={source} />
+          <CostChart timeRange={timeRange} source={source} />
           <ActivityHeatmap />
         </div>
       </div>
@@ -5,9 +5,12 @@ import { TimeRange } from "@/src/generated/typeshare-types";
 
 export function useService() {
   const [timeRange, setTimeRange] = useState<TimeRange>(TimeRange.Today);
+  const [source, setSource] = useState<string | undefined>();
 
   return {
     timeRange,
     setTimeRange,
+    source,
+    setSource,
   };
 }
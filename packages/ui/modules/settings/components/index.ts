@@ -0,0 +1 @@
+export { RetentionPolicyCard } from "./retention-policy";
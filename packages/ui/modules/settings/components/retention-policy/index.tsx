@@ -0,0 +1,102 @@
+"use client";
+
+import {
+  Card,
+  CardContent,
+  CardHeader,
+  CardTitle,
+  CardDescription,
+} from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { CardLoading } from "@/components/card-loading";
+import { CardError } from "@/components/card-error";
+import { useService } from "./use-service";
+import type { RetentionField } from "./types";
+
+const FIELDS: { field: RetentionField; label: string }[] = [
+  { field: "eventsDays", label: "Events" },
+  { field: "metricsDays", label: "Metrics" },
+  { field: "spansDays", label: "Traces" },
+  { field: "notificationsDays", label: "Notifications" },
+];
+
+export function RetentionPolicyCard() {
+  const {
+    draft,
+    isLoading,
+    error,
+    isDirty,
+    isValid,
+    isFieldValid,
+    isSaving,
+    saveError,
+    setField,
+    handleSave,
+    handleReset,
+    refetch,
+  } = useService();
+
+  if (isLoading) return <CardLoading showTitle />;
+  if (error)
+    return (
+      <CardError
+        title="Data Retention"
+        message="Failed to load the retention policy"
+        onRetry={() => refetch()}
+      />
+    );
+
+  return (
+    <Card className="gap-3 py-4">
+      <CardHeader className="px-4">
+        <CardTitle>Data Retention</CardTitle>
+        <CardDescription>
+          Days of raw telemetry the daemon keeps; leave a field empty to keep
+          that data forever
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4 px-4">
+        <div className="grid gap-4 md:grid-cols-2">
+          {FIELDS.map(({ field, label }) => (
+            <label key={field} className="space-y-1.5 text-sm">
+              <span className="font-medium">{label}</span>
+              <Input
+                type="number"
+                min={1}
+                step={1}
+                placeholder="Forever"
+                value={draft[field]}
+                aria-invalid={!isFieldValid(field) || undefined}
+                onChange={(e) => setField(field, e.target.value)}
+              />
+            </label>
+          ))}
+        </div>
+        {!isValid && (
+          <p className="text-sm text-destructive">
+            Retention must be a whole number of days, at least one
+          </p>
+        )}
+        {saveError && <p className="text-sm text-destructive">{saveError}</p>}
+        <div className="flex justify-end gap-2">
+          <Button
+            variant="outline"
+            size="sm"
+            disabled={!isDirty || isSaving}
+            onClick={handleReset}
+          >
+            Reset
+          </Button>
+          <Button
+            size="sm"
+            disabled={!isDirty || !isValid || isSaving}
+            onClick={handleSave}
+          >
+            {isSaving ? "Saving..." : "Save"}
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
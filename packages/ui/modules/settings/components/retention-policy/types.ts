@@ -0,0 +1,21 @@
+import type { RetentionPolicy } from "@/src/generated/typeshare-types";
+
+export type RetentionField = keyof RetentionPolicy;
+
+/** Days typed into each field; an empty string keeps the data forever */
+export type RetentionDraft = Record<RetentionField, string>;
+
+export interface UseServiceReturn {
+  draft: RetentionDraft;
+  isLoading: boolean;
+  error: Error | null;
+  isDirty: boolean;
+  isValid: boolean;
+  isFieldValid: (field: RetentionField) => boolean;
+  isSaving: boolean;
+  saveError: string | null;
+  setField: (field: RetentionField, value: string) => void;
+  handleSave: () => void;
+  handleReset: () => void;
+  refetch: () => void;
+}
@@ -0,0 +1,100 @@
+"use client";
+
+import { useState, useEffect, useCallback } from "react";
+import { useQuery, useQueryClient } from "@tanstack/react-query";
+import { RetentionBridge } from "@/src/bridges/retention-bridge";
+import type { RetentionPolicy } from "@/src/generated/typeshare-types";
+import type { RetentionDraft, RetentionField, UseServiceReturn } from "./types";
+
+const RETENTION_POLICY_QUERY_KEY = ["retention-policy"] as const;
+
+function toDraft(policy: RetentionPolicy): RetentionDraft {
+  return {
+    eventsDays: policy.eventsDays?.toString() ?? "",
+    metricsDays: policy.metricsDays?.toString() ?? "",
+    spansDays: policy.spansDays?.toString() ?? "",
+    notificationsDays: policy.notificationsDays?.toString() ?? "",
+  };
+}
+
+/** Whole number of days of at least one, or `undefined` to keep forever */
+function parseDays(value: string): number | undefined {
+  return value.trim() === "" ? undefined : Number(value);
+}
+
+function isValidDays(value: string): boolean {
+  const days = parseDays(value);
+  return days === undefined || (Number.isInteger(days) && days >= 1);
+}
+
+export function useService(): UseServiceReturn {
+  const queryClient = useQueryClient();
+  const [draft, setDraft] = useState<RetentionDraft | null>(null);
+  const [isSaving, setIsSaving] = useState(false);
+  const [saveError, setSaveError] = useState<string | null>(null);
+
+  const { data, isLoading, error, refetch } = useQuery({
+    queryKey: [...RETENTION_POLICY_QUERY_KEY],
+    queryFn: () => RetentionBridge.getRetentionPolicy(),
+  });
+
+  const saved = data ? toDraft(data) : null;
+
+  useEffect(() => {
+    if (data) setDraft(toDraft(data));
+  }, [data]);
+
+  const current = draft ?? saved ?? toDraft({});
+  const isDirty =
+    saved !== null &&
+    (Object.keys(current) as RetentionField[]).some(
+      (field) => current[field].trim() !== saved[field]
+    );
+  const isValid = Object.values(current).every(isValidDays);
+
+  const setField = useCallback((field: RetentionField, value: string) => {
+    setDraft((prev) => ({ ...(prev ?? toDraft({})), [field]: value }));
+    setSaveError(null);
+  }, []);
+
+  const handleSave = useCallback(async () => {
+    if (!isValid) return;
+    setIsSaving(true);
+    setSaveError(null);
+    try {
+      await RetentionBridge.setRetentionPolicy({
+        eventsDays: parseDays(current.eventsDays),
+        metricsDays: parseDays(current.metricsDays),
+        spansDays: parseDays(current.spansDays),
+        notificationsDays: parseDays(current.notificationsDays),
+      });
+      await queryClient.invalidateQueries({
+        queryKey: [...RETENTION_POLICY_QUERY_KEY],
+      });
+    } catch (e) {
+      setSaveError(String(e));
+    } finally {
+      setIsSaving(false);
+    }
+  }, [current, isValid, queryClient]);
+
+  const handleReset = useCallback(() => {
+    if (data) setDraft(toDraft(data));
+    setSaveError(null);
+  }, [data]);
+
+  return {
+    draft: current,
+    isLoading,
+    error: error as Error | null,
+    isDirty,
+    isValid,
+    isFieldValid: (field) => isValidDays(current[field]),
+    isSaving,
+    saveError,
+    setField,
+    handleSave,
+    handleReset,
+    refetch,
+  };
+}
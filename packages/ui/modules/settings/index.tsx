@@ -0,0 +1,18 @@
+"use client";
+
+import { PageHeader } from "@/components/page-header";
+import { RetentionPolicyCard } from "./components";
+
+export function Settings() {
+  return (
+    <div className="flex h-full flex-col overflow-hidden">
+      <PageHeader title="Settings" />
+
+      <div className="flex-1 overflow-y-auto bg-muted/40">
+        <div className="mx-auto max-w-6xl space-y-6 p-6">
+          <RetentionPolicyCard />
+        </div>
+      </div>
+    </div>
+  );
+}
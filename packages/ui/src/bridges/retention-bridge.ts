@@ -0,0 +1,24 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { RetentionPolicy } from "../generated/typeshare-types";
+
+/**
+ * Retention Bridge - Frontend interface for the daemon's retention policy
+ *
+ * Each field is the number of days a kind of telemetry is kept; a missing
+ * value keeps it forever.
+ */
+export class RetentionBridge {
+  /**
+   * Get the current retention policy
+   */
+  static async getRetentionPolicy(): Promise<RetentionPolicy> {
+    return invoke<RetentionPolicy>("get_retention_policy");
+  }
+
+  /**
+   * Save the retention policy; the daemon applies it within a few seconds
+   */
+  static async setRetentionPolicy(policy: RetentionPolicy): Promise<void> {
+    return invoke<void>("set_retention_policy", { policy });
+  }
+}
@@ -8,26 +8,29 @@ import type {
 
 /**
  * Stats Bridge - Frontend interface for statistics operations
+ *
+ * `source` limits the results to one telemetry source (e.g. "codex");
+ * all sources are included when it is omitted.
  */
 export class StatsBridge {
   /**
    * Get summary statistics for a time range
    */
-  static async getSummaryStats(timeRange: TimeRange): Promise<SummaryStats> {
-    return invoke<SummaryStats>("get_summary_stats", { timeRange });
+  static async getSummaryStats(timeRange: TimeRange, source?: string): Promise<SummaryStats> {
+    return invoke<SummaryStats>("get_summary_stats", { timeRange, source });
   }
 
   /**
    * Get model usage statistics for a time range
    */
-  static async getModelStats(timeRange: TimeRange): Promise<ModelStats[]> {
-    return invoke<ModelStats[]>("get_model_stats", { timeRange });
+  static async getModelStats(timeRange: TimeRange, source?: string): Promise<ModelStats[]> {
+    return invoke<ModelStats[]>("get_model_stats", { timeRange, source });
   }
 
   /**
    * Get token statistics by model for a time range
    */
-  static async getTokenStats(timeRange: TimeRange): Promise<TokenStats[]> {
-    return invoke<TokenStats[]>("get_token_stats", { timeRange });
+  static async getTokenStats(timeRange: TimeRange, source?: string): Promise<TokenStats[]> {
+    return invoke<TokenStats[]>("get_token_stats", { timeRange, source });
   }
 }
@@ -8,20 +8,35 @@ import type {
 
 /**
  * Trends Bridge - Frontend interface for trends operations
+ *
+ * `source` limits the results to one telemetry source (e.g. "codex");
+ * all sources are included when it is omitted.
  */
 export class TrendsBridge {
   /**
    * Get usage trends for a time range
    */
-  static async getUsageTrends(timeRange: TimeRange): Promise<UsageTrend[]> {
-    return invoke<UsageTrend[]>("get_usage_trends", { timeRange });
+  static async getUsageTrends(timeRange: TimeRange, source?: string): Promise<UsageTrend[]> {
+    return invoke<UsageTrend[]>("get_usage_trends", { timeRange, source });
   }
 
-  static async getCostByModelTrends(timeRange: TimeRange): Promise<CostByModelTrend[]> {
-    return invoke<CostByModelTrend[]>("get_cost_by_model_trends", { timeRange });
+  /**
+   * Get cost by model trends for a time range
+   */
+  static async getCostByModelTrends(
+    timeRange: TimeRange,
+    source?: string,
+  ): Promise<CostByModelTrend[]> {
+    return invoke<CostByModelTrend[]>("get_cost_by_model_trends", { timeRange, source });
   }
 
-  static async getCostEfficiencyTrend(timeRange: TimeRange): Promise<CostEfficiencyTrend[]> {
-    return invoke<CostEfficiencyTrend[]>("get_cost_efficiency_trend", { timeRange });
+  /**
+   * Get cost efficiency trend for a time range
+   */
+  static async getCostEfficiencyTrend(
+    timeRange: TimeRange,
+    source?: string,
+  ): Promise<CostEfficiencyTrend[]> {
+    return invoke<CostEfficiencyTrend[]>("get_cost_efficiency_trend", { timeRange, source });
   }
 }
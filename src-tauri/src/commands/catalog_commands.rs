@@ -0,0 +1,21 @@
+//! Attribute catalog commands
+//!
+//! Tauri IPC commands for inspecting the attribute keys the daemon has seen.
+
+use shared::{AttributeCatalogEntry, AttributeCatalogRepository};
+use sqlx::SqlitePool;
+use tauri::{command, AppHandle, Manager};
+
+/// Get the attribute catalog, optionally for one signal ("log" or "metric")
+/// or only entries first seen since a Unix millisecond timestamp
+#[command]
+pub async fn get_attribute_catalog(
+    app_handle: AppHandle,
+    signal: Option<String>,
+    since: Option<i64>,
+) -> Result<Vec<AttributeCatalogEntry>, String> {
+    let pool = app_handle.state::<SqlitePool>();
+    AttributeCatalogRepository::find_all(&pool, signal.as_deref(), since)
+        .await
+        .map_err(|e| e.to_string())
+}
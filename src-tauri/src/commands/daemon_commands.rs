@@ -1,6 +1,6 @@
 use tauri::command;
 
-use crate::daemon::{check_daemon_health, daemon_http_endpoint};
+use crate::daemon::{check_daemon_health, daemon_http_endpoint, fetch_daemon_status};
 use crate::services::WslRuntimeService;
 
 #[derive(Debug, serde::Serialize)]
@@ -9,6 +9,9 @@ pub struct RuntimeEnvStatus {
     pub platform: String,
     pub daemon_healthy: bool,
     pub daemon_version: Option<String>,
+    /// The daemon's `/status` report: when data last arrived, ingestion
+    /// rates, parse failures, queue depth, database size and uptime
+    pub daemon_status: Option<serde_json::Value>,
     pub endpoint: String,
     pub wsl_detected: bool,
     pub default_distro: Option<String>,
@@ -26,12 +29,18 @@ pub async fn get_daemon_status() -> Result<bool, String> {
 #[command]
 pub async fn get_runtime_env_status() -> Result<RuntimeEnvStatus, String> {
     let health = check_daemon_health().await;
+    let daemon_status = if health.is_some() {
+        fetch_daemon_status().await
+    } else {
+        None
+    };
     let wsl = WslRuntimeService::inspect();
 
     Ok(RuntimeEnvStatus {
         platform: std::env::consts::OS.to_string(),
         daemon_healthy: health.is_some(),
         daemon_version: health.map(|h| h.version),
+        daemon_status,
         endpoint: daemon_http_endpoint(),
         wsl_detected: wsl.detected,
         default_distro: wsl.default_distro,
@@ -1,7 +1,9 @@
 pub mod analytics_commands;
+pub mod catalog_commands;
 pub mod claude_session_commands;
 pub mod daemon_commands;
 pub mod export_commands;
+pub mod retention_commands;
 
 pub mod session_commands;
 pub mod stats_commands;
@@ -12,9 +14,11 @@ pub mod user_commands;
 pub mod wrapped_commands;
 
 pub use analytics_commands::*;
+pub use catalog_commands::*;
 pub use claude_session_commands::*;
 pub use daemon_commands::*;
 pub use export_commands::*;
+pub use retention_commands::*;
 
 pub use session_commands::*;
 pub use stats_commands::*;
@@ -68,6 +72,11 @@ macro_rules! app_commands {
             // Daemon commands
             commands::get_daemon_status,
             commands::get_runtime_env_status,
+            // Catalog commands
+            commands::get_attribute_catalog,
+            // Retention commands
+            commands::get_retention_policy,
+            commands::set_retention_policy,
             // Usage commands
             commands::get_usage_limits,
             commands::save_api_key,
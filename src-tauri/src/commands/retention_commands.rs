@@ -0,0 +1,26 @@
+//! Retention commands
+//!
+//! Tauri IPC commands for showing and changing how long the daemon keeps raw
+//! telemetry. The daemon applies a saved policy within a few seconds.
+
+use tauri::command;
+
+use crate::types::RetentionPolicy;
+
+/// Get the retention policy
+#[command]
+pub async fn get_retention_policy() -> Result<RetentionPolicy, String> {
+    let path = shared::get_retention_path().map_err(|e| e.to_string())?;
+    shared::RetentionPolicy::load(&path)
+        .map(RetentionPolicy::from)
+        .map_err(|e| e.to_string())
+}
+
+/// Save the retention policy
+#[command]
+pub async fn set_retention_policy(policy: RetentionPolicy) -> Result<(), String> {
+    let path = shared::get_retention_path().map_err(|e| e.to_string())?;
+    shared::RetentionPolicy::from(policy)
+        .save(&path)
+        .map_err(|e| e.to_string())
+}
@@ -8,14 +8,15 @@ use tauri::{command, AppHandle, Manager};
 use crate::services::TrendsService;
 use crate::types::{CostByModelTrend, CostEfficiencyTrend, TimeRange, UsageTrend};
 
-/// Get usage trends for a time range
+/// Get usage trends for a time range, optionally for one telemetry source
 #[command]
 pub async fn get_usage_trends(
     app_handle: AppHandle,
     time_range: TimeRange,
+    source: Option<String>,
 ) -> Result<Vec<UsageTrend>, String> {
     let pool = app_handle.state::<SqlitePool>();
-    TrendsService::get_usage_trends(&pool, time_range)
+    TrendsService::get_usage_trends(&pool, time_range, source.as_deref())
         .await
         .map_err(|e| e.to_string())
 }
@@ -25,9 +26,10 @@ pub async fn get_usage_trends(
 pub async fn get_cost_efficiency_trend(
     app_handle: AppHandle,
     time_range: TimeRange,
+    source: Option<String>,
 ) -> Result<Vec<CostEfficiencyTrend>, String> {
     let pool = app_handle.state::<SqlitePool>();
-    TrendsService::get_cost_efficiency_trend(&pool, time_range)
+    TrendsService::get_cost_efficiency_trend(&pool, time_range, source.as_deref())
         .await
         .map_err(|e| e.to_string())
 }
@@ -37,9 +39,10 @@ pub async fn get_cost_efficiency_trend(
 pub async fn get_cost_by_model_trends(
     app_handle: AppHandle,
     time_range: TimeRange,
+    source: Option<String>,
 ) -> Result<Vec<CostByModelTrend>, String> {
     let pool = app_handle.state::<SqlitePool>();
-    TrendsService::get_cost_by_model_trends(&pool, time_range)
+    TrendsService::get_cost_by_model_trends(&pool, time_range, source.as_deref())
         .await
         .map_err(|e| e.to_string())
 }
@@ -1,18 +1,40 @@
+#[cfg(unix)]
+use std::path::PathBuf;
 use std::time::Duration;
 
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use shared::DaemonConfig;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
 
-const DEFAULT_DAEMON_ADDR: &str = "127.0.0.1:4318";
 const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
 
-pub fn daemon_addr() -> String {
-    std::env::var("LUMO_SERVER_ADDRESS").unwrap_or_else(|_| DEFAULT_DAEMON_ADDR.to_string())
+/// The daemon's configuration, resolved the same way the daemon does.
+/// None (after logging why) when `daemon.toml` is invalid.
+fn daemon_config() -> Option<DaemonConfig> {
+    DaemonConfig::load()
+        .inspect_err(|e| log::warn!("Failed to read the daemon configuration: {}", e))
+        .ok()
 }
 
+/// TCP address of the daemon; None when it serves the Unix socket only.
+pub fn daemon_addr() -> Option<String> {
+    daemon_config()?.server_address
+}
+
+/// Where the daemon is reached, for display.
 pub fn daemon_http_endpoint() -> String {
-    format!("http://{}", daemon_addr())
+    if let Some(addr) = daemon_addr() {
+        return format!("http://{}", addr);
+    }
+    #[cfg(unix)]
+    if let Some(socket_path) = daemon_socket_path() {
+        return format!("unix:{}", socket_path.display());
+    }
+    "off".to_string()
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,30 +44,76 @@ pub struct HealthResponse {
     pub version: String,
 }
 
+/// Unix socket the daemon also listens on, if any.
+#[cfg(unix)]
+fn daemon_socket_path() -> Option<PathBuf> {
+    daemon_config()?.socket_path
+}
+
 /// Send a GET /health request to the daemon and parse the response.
 /// Returns None if the daemon is not reachable.
 pub async fn check_daemon_health() -> Option<HealthResponse> {
-    tokio::time::timeout(HEALTH_TIMEOUT, async {
-        let addr = daemon_addr();
-        let mut stream = TcpStream::connect(&addr).await.ok()?;
-
-        let request = format!(
-            "GET /health HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
-            addr
-        );
-        stream.write_all(request.as_bytes()).await.ok()?;
-
-        let mut response = Vec::new();
-        stream.read_to_end(&mut response).await.ok()?;
+    get_json("/health", None).await
+}
 
-        let response_str = String::from_utf8_lossy(&response);
+/// Fetch the daemon's GET /status report (ingestion, queue and database).
+/// The Unix socket needs no token; over TCP the daemon's auth token is sent.
+/// Returns None if the daemon is not reachable or predates /status.
+pub async fn fetch_daemon_status() -> Option<serde_json::Value> {
+    let token = shared::get_token_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|token| token.trim().to_string());
+    get_json::<serde_json::Value>("/status", token.as_deref())
+        .await
+        // Error responses (e.g. a rejected token) are JSON too
+        .filter(|status| status.get("ingest").is_some())
+}
 
-        // Find JSON body after the blank line
-        let body = response_str.split("\r\n\r\n").nth(1)?;
+/// Send a GET request to the daemon and parse the JSON body.
+/// Tries the Unix socket first, then TCP unless the daemon serves the socket
+/// only.
+async fn get_json<T: DeserializeOwned>(path: &str, token: Option<&str>) -> Option<T> {
+    tokio::time::timeout(HEALTH_TIMEOUT, async {
+        #[cfg(unix)]
+        if let Some(socket_path) = daemon_socket_path() {
+            if let Ok(stream) = UnixStream::connect(&socket_path).await {
+                if let Some(body) = request_json(stream, "localhost", path, None).await {
+                    return Some(body);
+                }
+            }
+        }
 
-        serde_json::from_str::<HealthResponse>(body).ok()
+        let addr = daemon_addr()?;
+        let stream = TcpStream::connect(&addr).await.ok()?;
+        request_json(stream, &addr, path, token).await
     })
     .await
     .ok()
     .flatten()
 }
+
+async fn request_json<S, T>(mut stream: S, host: &str, path: &str, token: Option<&str>) -> Option<T>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: DeserializeOwned,
+{
+    let authorization = token
+        .map(|token| format!("Authorization: Bearer {}\r\n", token))
+        .unwrap_or_default();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\n{}Connection: close\r\n\r\n",
+        path, host, authorization
+    );
+    stream.write_all(request.as_bytes()).await.ok()?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.ok()?;
+
+    let response_str = String::from_utf8_lossy(&response);
+
+    // Find JSON body after the blank line
+    let body = response_str.split("\r\n\r\n").nth(1)?;
+
+    serde_json::from_str::<T>(body).ok()
+}
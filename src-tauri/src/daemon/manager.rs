@@ -5,7 +5,7 @@ use std::time::Duration;
 use anyhow::{Context, Result};
 use tauri::Manager;
 
-use super::health::check_daemon_health;
+use super::health::{check_daemon_health, daemon_http_endpoint};
 #[cfg(target_os = "macos")]
 use super::plist;
 #[cfg(not(target_os = "macos"))]
@@ -402,6 +402,9 @@ impl DaemonManager {
                 return Ok(());
             }
         }
-        anyhow::bail!("Daemon failed to start within 5 seconds")
+        anyhow::bail!(
+            "Daemon failed to start within 5 seconds (no answer at {})",
+            daemon_http_endpoint()
+        )
     }
 }
@@ -3,5 +3,5 @@ mod manager;
 #[cfg(target_os = "macos")]
 mod plist;
 
-pub use health::{check_daemon_health, daemon_http_endpoint};
+pub use health::{check_daemon_health, daemon_http_endpoint, fetch_daemon_status};
 pub use manager::DaemonManager;
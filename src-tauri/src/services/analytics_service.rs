@@ -6,9 +6,10 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 use chrono::{Local, TimeZone};
+use shared::{RollupGroup, RollupRepository};
 use sqlx::SqlitePool;
 
-use super::time_range::{generate_date_labels, get_time_range_bounds};
+use super::time_range::{generate_date_labels, get_time_range_bounds, period_format};
 use crate::types::{
     ActivityDay, CacheHitTrend, ErrorRateStats, HourlyActivity, SessionBucket, TimeRange,
 };
@@ -24,21 +25,17 @@ impl AnalyticsService {
     ) -> Result<Vec<HourlyActivity>> {
         let (start_time, end_time) = get_time_range_bounds(time_range);
 
-        let rows: Vec<HourlyRow> = sqlx::query_as(
-            r#"
-            SELECT
-                CAST(strftime('%H', datetime(timestamp / 1000, 'unixepoch', 'localtime')) AS INTEGER) as hour,
-                COUNT(*) as count
-            FROM events
-            WHERE timestamp >= ? AND timestamp <= ?
-                AND name = 'claude_code.api_request'
-            GROUP BY hour
-            ORDER BY hour ASC
-            "#,
+        let rows = RollupRepository::sum_events(
+            pool,
+            "claude_code.api_request",
+            start_time,
+            end_time,
+            None,
+            RollupGroup {
+                period: Some("%H"),
+                model: false,
+            },
         )
-        .bind(start_time)
-        .bind(end_time)
-        .fetch_all(pool)
         .await?;
 
         // Fill all 24 hours
@@ -47,8 +44,9 @@ impl AnalyticsService {
             item.hour = i as i32;
         }
         for r in rows {
-            if (r.hour as usize) < 24 {
-                result[r.hour as usize].count = r.count as i32;
+            let hour = r.period.and_then(|hour| hour.parse::<usize>().ok());
+            if let Some(item) = hour.and_then(|hour| result.get_mut(hour)) {
+                item.count = r.event_count as i32;
             }
         }
 
@@ -111,24 +109,10 @@ impl AnalyticsService {
     ) -> Result<ErrorRateStats> {
         let (start_time, end_time) = get_time_range_bounds(time_range);
 
-        let row: Option<ErrorRateRow> = sqlx::query_as(
-            r#"
-            SELECT
-                COALESCE(SUM(CASE WHEN name = 'claude_code.api_request' THEN 1 ELSE 0 END), 0) as total_requests,
-                COALESCE(SUM(CASE WHEN name = 'claude_code.api_error' THEN 1 ELSE 0 END), 0) as total_errors
-            FROM events
-            WHERE timestamp >= ? AND timestamp <= ?
-                AND name IN ('claude_code.api_request', 'claude_code.api_error')
-            "#,
-        )
-        .bind(start_time)
-        .bind(end_time)
-        .fetch_optional(pool)
-        .await?;
-
-        let (total_requests, total_errors) = row
-            .map(|r| (r.total_requests as i32, r.total_errors as i32))
-            .unwrap_or((0, 0));
+        let total_requests =
+            Self::count_events(pool, "claude_code.api_request", start_time, end_time).await?;
+        let total_errors =
+            Self::count_events(pool, "claude_code.api_error", start_time, end_time).await?;
 
         let error_rate = if total_requests > 0 {
             total_errors as f32 / total_requests as f32
@@ -150,43 +134,27 @@ impl AnalyticsService {
     ) -> Result<Vec<CacheHitTrend>> {
         let (start_time, end_time) = get_time_range_bounds(time_range);
 
-        let (format_str, group_expr) = match time_range {
-            TimeRange::Today => (
-                "%H:00",
-                "strftime('%Y-%m-%d %H', datetime(timestamp / 1000, 'unixepoch', 'localtime'))",
-            ),
-            TimeRange::Week | TimeRange::Month => (
-                "%Y-%m-%d",
-                "strftime('%Y-%m-%d', datetime(timestamp / 1000, 'unixepoch', 'localtime'))",
-            ),
-        };
-
-        let query = format!(
-            r#"
-            SELECT
-                strftime('{}', datetime(timestamp / 1000, 'unixepoch', 'localtime')) as date,
-                COALESCE(
-                    SUM(cache_read_tokens) * 100.0 / NULLIF(SUM(input_tokens + cache_read_tokens), 0),
-                    0.0
-                ) as rate
-            FROM events
-            WHERE timestamp >= ? AND timestamp <= ?
-                AND name = 'claude_code.api_request'
-            GROUP BY {}
-            ORDER BY MIN(timestamp) ASC
-            "#,
-            format_str, group_expr
-        );
-
-        let rows: Vec<CacheHitRow> = sqlx::query_as(&query)
-            .bind(start_time)
-            .bind(end_time)
-            .fetch_all(pool)
-            .await?;
+        let rows = RollupRepository::sum_events(
+            pool,
+            "claude_code.api_request",
+            start_time,
+            end_time,
+            None,
+            RollupGroup {
+                period: Some(period_format(time_range)),
+                model: false,
+            },
+        )
+        .await?;
 
+        // Cache hit rate = cache_read / (cache_read + input)
         let mut trend_map: HashMap<String, f32> = HashMap::new();
         for r in rows {
-            trend_map.insert(r.date, r.rate as f32);
+            let denominator = r.input_tokens + r.cache_read_tokens;
+            if denominator > 0 {
+                let rate = r.cache_read_tokens as f64 * 100.0 / denominator as f64;
+                trend_map.insert(r.period.unwrap_or_default(), rate as f32);
+            }
         }
 
         let all_labels = generate_date_labels(time_range);
@@ -212,38 +180,36 @@ impl AnalyticsService {
             .timestamp_millis();
         let end_time = now.timestamp_millis();
 
-        let rows: Vec<ActivityDayRow> = sqlx::query_as(
-            r#"
-            SELECT
-                strftime('%Y-%m-%d', datetime(timestamp / 1000, 'unixepoch', 'localtime')) as date,
-                COUNT(DISTINCT session_id) as count
-            FROM events
-            WHERE timestamp >= ? AND timestamp <= ?
-                AND session_id != 'unknown'
-            GROUP BY date
-            ORDER BY date ASC
-            "#,
-        )
-        .bind(start_time)
-        .bind(end_time)
-        .fetch_all(pool)
-        .await?;
+        let rows =
+            RollupRepository::count_sessions(pool, "%Y-%m-%d", start_time, end_time, None).await?;
 
         Ok(rows
             .into_iter()
             .map(|r| ActivityDay {
-                date: r.date,
+                date: r.period,
                 count: r.count as i32,
             })
             .collect())
     }
 
-}
-
-#[derive(Debug, sqlx::FromRow)]
-struct HourlyRow {
-    hour: i64,
-    count: i64,
+    /// Count events named `name` between two timestamps
+    async fn count_events(
+        pool: &SqlitePool,
+        name: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<i32> {
+        let totals = RollupRepository::sum_events(
+            pool,
+            name,
+            start_time,
+            end_time,
+            None,
+            RollupGroup::default(),
+        )
+        .await?;
+        Ok(totals.first().map_or(0, |t| t.event_count as i32))
+    }
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -251,21 +217,3 @@ struct BucketRow {
     bucket: String,
     count: i64,
 }
-
-#[derive(Debug, sqlx::FromRow)]
-struct ErrorRateRow {
-    total_requests: i64,
-    total_errors: i64,
-}
-
-#[derive(Debug, sqlx::FromRow)]
-struct ActivityDayRow {
-    date: String,
-    count: i64,
-}
-
-#[derive(Debug, sqlx::FromRow)]
-struct CacheHitRow {
-    date: String,
-    rate: f64,
-}
@@ -5,6 +5,7 @@
 
 use anyhow::{Context, Result};
 use serde_json::{json, Map, Value};
+use shared::DaemonConfig;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -19,16 +20,16 @@ const OTEL_STATIC_ENV_VARS: &[(&str, &str)] = &[
     ("OTEL_EXPORTER_OTLP_PROTOCOL", "http/json"),
 ];
 
-/// The command used by Lumo hooks — pipes hook stdin JSON to the daemon.
-/// Uses --noproxy to bypass any system proxy (for localhost delivery).
-const HOOK_COMMAND: &str =
-    "curl -s --noproxy localhost -X POST http://localhost:4318/notify -H 'Content-Type: application/json' -d \"$(cat)\"";
+/// OTEL env var carrying the exporter headers, including the daemon token
+/// when authentication is on.
+const OTEL_HEADERS_KEY: &str = "OTEL_EXPORTER_OTLP_HEADERS";
 
-/// Marker substring to detect if a Lumo hook is already present.
-const HOOK_MARKER: &str = "localhost:4318/notify";
+/// Marker substring to detect if a Lumo hook is already present, whichever
+/// daemon address it was written for.
+const HOOK_MARKER: &str = "/notify -H 'Content-Type: application/json'";
 
 /// Hook events that Lumo subscribes to.
-const HOOK_EVENTS: &[&str] = &["Notification", "Stop", "SubagentStop"];
+const HOOK_EVENTS: &[&str] = &["Notification", "Stop", "SubagentStop", "SessionEnd"];
 
 #[derive(Debug, Clone)]
 struct SettingsTarget {
@@ -104,18 +105,78 @@ impl ClaudeConfigService {
         Ok(())
     }
 
-    fn daemon_endpoint() -> String {
-        std::env::var("LUMO_SERVER_ADDRESS")
-            .map(|addr| format!("http://{}", addr))
-            .unwrap_or_else(|_| "http://localhost:4318".to_string())
+    /// Token the daemon requires on every endpoint but `/health`, if it has
+    /// authentication on. The token file is not created otherwise.
+    fn auth_token(config: &DaemonConfig) -> Result<Option<String>> {
+        if !config.auth_enabled {
+            return Ok(None);
+        }
+        shared::load_or_create_token()
+            .map(Some)
+            .context("Failed to load Lumo daemon auth token")
+    }
+
+    /// The command used by Lumo hooks — pipes hook stdin JSON to the daemon.
+    /// Uses --noproxy to bypass any system proxy (for local delivery).
+    fn hook_command(endpoint: &str, token: Option<&str>) -> String {
+        let auth_header = token
+            .map(|token| format!(" -H 'Authorization: Bearer {}'", token))
+            .unwrap_or_default();
+        format!(
+            "curl -s --noproxy '*' -X POST {}/notify -H 'Content-Type: application/json'{} -d \"$(cat)\"",
+            endpoint, auth_header
+        )
+    }
+
+    /// Set or drop the `Authorization` entry of an `OTEL_EXPORTER_OTLP_HEADERS`
+    /// value, keeping any other headers the user configured. `None` means no
+    /// headers are left.
+    fn otel_headers(current: Option<&str>, token: Option<&str>) -> Option<String> {
+        let mut headers: Vec<String> = current
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| {
+                !entry.is_empty()
+                    && !entry
+                        .split('=')
+                        .next()
+                        .unwrap_or_default()
+                        .trim()
+                        .eq_ignore_ascii_case("authorization")
+            })
+            .map(String::from)
+            .collect();
+        if let Some(token) = token {
+            headers.push(format!("Authorization=Bearer {}", token));
+        }
+        (!headers.is_empty()).then(|| headers.join(","))
+    }
+
+    /// The daemon settings, resolved the same way the daemon resolves them.
+    fn daemon_config() -> Result<DaemonConfig> {
+        DaemonConfig::load().context("Failed to load Lumo daemon config")
+    }
+
+    /// Base URL of the daemon's HTTP listener. Claude Code exports over HTTP
+    /// only, so a daemon serving just the Unix socket is rejected rather than
+    /// pointed at a port nobody listens on.
+    fn daemon_endpoint(config: &DaemonConfig) -> Result<String> {
+        let address = config.server_address.as_deref().context(
+            "The Lumo daemon's HTTP listener is off (server_address = \"off\"); Claude Code cannot send telemetry to the Unix socket",
+        )?;
+        Ok(format!("http://{}", address))
     }
 
     /// Ensure Claude settings have required OTEL env vars for all supported targets.
     pub fn ensure_otel_config() -> Result<()> {
+        let config = Self::daemon_config()?;
+        let endpoint = Self::daemon_endpoint(&config)?;
+        let token = Self::auth_token(&config)?;
         let mut updated_any = false;
 
         for target in Self::settings_targets()? {
-            match Self::ensure_otel_config_for_path(&target.path) {
+            match Self::ensure_otel_config_for_path(&target.path, &endpoint, token.as_deref()) {
                 Ok(updated) => {
                     if updated {
                         updated_any = true;
@@ -145,7 +206,11 @@ impl ClaudeConfigService {
         Ok(())
     }
 
-    fn ensure_otel_config_for_path(path: &Path) -> Result<bool> {
+    fn ensure_otel_config_for_path(
+        path: &Path,
+        endpoint: &str,
+        token: Option<&str>,
+    ) -> Result<bool> {
         let mut root = Self::read_settings(path)?;
 
         let env_obj = root
@@ -166,12 +231,23 @@ impl ClaudeConfigService {
         }
 
         let endpoint_key = "OTEL_EXPORTER_OTLP_ENDPOINT";
-        let endpoint_expected = Value::String(Self::daemon_endpoint());
+        let endpoint_expected = Value::String(endpoint.to_string());
         if env_map.get(endpoint_key) != Some(&endpoint_expected) {
             env_map.insert(endpoint_key.to_string(), endpoint_expected);
             changed = true;
         }
 
+        let headers_expected =
+            Self::otel_headers(env_map.get(OTEL_HEADERS_KEY).and_then(Value::as_str), token)
+                .map(Value::String);
+        if env_map.get(OTEL_HEADERS_KEY) != headers_expected.as_ref() {
+            match headers_expected {
+                Some(headers) => env_map.insert(OTEL_HEADERS_KEY.to_string(), headers),
+                None => env_map.remove(OTEL_HEADERS_KEY),
+            };
+            changed = true;
+        }
+
         if changed {
             Self::write_settings(path, &root)?;
         }
@@ -191,10 +267,13 @@ impl ClaudeConfigService {
 
     /// Ensure Claude settings have hooks that forward events to `/notify`.
     pub fn ensure_hooks_config() -> Result<()> {
+        let config = Self::daemon_config()?;
+        let endpoint = Self::daemon_endpoint(&config)?;
+        let token = Self::auth_token(&config)?;
         let mut updated_any = false;
 
         for target in Self::settings_targets()? {
-            match Self::ensure_hooks_config_for_path(&target.path) {
+            match Self::ensure_hooks_config_for_path(&target.path, &endpoint, token.as_deref()) {
                 Ok(updated) => {
                     if updated {
                         updated_any = true;
@@ -224,7 +303,11 @@ impl ClaudeConfigService {
         Ok(())
     }
 
-    fn ensure_hooks_config_for_path(path: &Path) -> Result<bool> {
+    fn ensure_hooks_config_for_path(
+        path: &Path,
+        endpoint: &str,
+        token: Option<&str>,
+    ) -> Result<bool> {
         let mut root = Self::read_settings(path)?;
 
         let hooks_obj = root
@@ -238,7 +321,7 @@ impl ClaudeConfigService {
             "hooks": [
                 {
                     "type": "command",
-                    "command": HOOK_COMMAND,
+                    "command": Self::hook_command(endpoint, token),
                 }
             ]
         });
@@ -35,6 +35,14 @@ pub fn get_time_range_bounds(time_range: TimeRange) -> (i64, i64) {
     (start_time, end_time)
 }
 
+/// `strftime` format of the labels from [`generate_date_labels`]
+pub fn period_format(time_range: TimeRange) -> &'static str {
+    match time_range {
+        TimeRange::Today => "%H:00",
+        TimeRange::Week | TimeRange::Month => "%Y-%m-%d",
+    }
+}
+
 /// Generate all date/hour labels for the given time range.
 ///
 /// - `Today`: hourly labels from "00:00" to current hour
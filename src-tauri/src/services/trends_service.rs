@@ -5,65 +5,48 @@
 use std::collections::{HashMap, HashSet};
 
 use anyhow::Result;
+use shared::{RollupGroup, RollupRepository};
 use sqlx::SqlitePool;
 
-use super::time_range::{generate_date_labels, get_time_range_bounds};
+use super::time_range::{generate_date_labels, get_time_range_bounds, period_format};
 use crate::types::{CostByModelTrend, CostEfficiencyTrend, TimeRange, UsageTrend};
 
+/// Event carrying the cost and token usage of each model request
+const API_REQUEST: &str = "claude_code.api_request";
+
 /// Service for trends operations
 pub struct TrendsService;
 
 impl TrendsService {
-    /// Get usage trends for a time range
+    /// Get usage trends for a time range, optionally for one telemetry source
     pub async fn get_usage_trends(
         pool: &SqlitePool,
         time_range: TimeRange,
+        source: Option<&str>,
     ) -> Result<Vec<UsageTrend>> {
         let (start_time, end_time) = get_time_range_bounds(time_range);
 
-        // Format string and grouping depends on time range
-        let (format_str, group_expr) = match time_range {
-            TimeRange::Today => (
-                "%H:00",
-                "strftime('%Y-%m-%d %H', datetime(timestamp / 1000, 'unixepoch', 'localtime'))",
-            ),
-            TimeRange::Week | TimeRange::Month => (
-                "%Y-%m-%d",
-                "strftime('%Y-%m-%d', datetime(timestamp / 1000, 'unixepoch', 'localtime'))",
-            ),
-        };
-
-        let query = format!(
-            r#"
-            SELECT
-                strftime('{}', datetime(timestamp / 1000, 'unixepoch', 'localtime')) as date,
-                COALESCE(SUM(cost_usd), 0) as cost,
-                COALESCE(SUM(input_tokens), 0) as input_tokens,
-                COALESCE(SUM(output_tokens), 0) as output_tokens,
-                COALESCE(SUM(cache_read_tokens), 0) as cache_read_tokens,
-                COALESCE(SUM(cache_creation_tokens), 0) as cache_creation_tokens
-            FROM events
-            WHERE timestamp >= ? AND timestamp <= ?
-                AND name = 'claude_code.api_request'
-            GROUP BY {}
-            ORDER BY MIN(timestamp) ASC
-            "#,
-            format_str, group_expr
-        );
-
-        let rows: Vec<UsageTrendRow> = sqlx::query_as(&query)
-            .bind(start_time)
-            .bind(end_time)
-            .fetch_all(pool)
-            .await?;
+        let rows = RollupRepository::sum_events(
+            pool,
+            API_REQUEST,
+            start_time,
+            end_time,
+            source,
+            RollupGroup {
+                period: Some(period_format(time_range)),
+                model: false,
+            },
+        )
+        .await?;
 
         let mut trend_map: HashMap<String, UsageTrend> = HashMap::new();
         for r in rows {
+            let date = r.period.unwrap_or_default();
             trend_map.insert(
-                r.date.clone(),
+                date.clone(),
                 UsageTrend {
-                    date: r.date,
-                    cost: r.cost as f32,
+                    date,
+                    cost: r.cost_usd as f32,
                     input_tokens: r.input_tokens as i32,
                     output_tokens: r.output_tokens as i32,
                     cache_read_tokens: r.cache_read_tokens as i32,
@@ -92,50 +75,34 @@ impl TrendsService {
     pub async fn get_cost_by_model_trends(
         pool: &SqlitePool,
         time_range: TimeRange,
+        source: Option<&str>,
     ) -> Result<Vec<CostByModelTrend>> {
         let (start_time, end_time) = get_time_range_bounds(time_range);
 
-        let format_str = match time_range {
-            TimeRange::Today => "%H:00",
-            TimeRange::Week | TimeRange::Month => "%Y-%m-%d",
-        };
-
-        let group_expr = match time_range {
-            TimeRange::Today => {
-                "strftime('%Y-%m-%d %H', datetime(timestamp / 1000, 'unixepoch', 'localtime'))"
-            }
-            TimeRange::Week | TimeRange::Month => {
-                "strftime('%Y-%m-%d', datetime(timestamp / 1000, 'unixepoch', 'localtime'))"
-            }
-        };
-
-        let query = format!(
-            r#"
-            SELECT
-                strftime('{}', datetime(timestamp / 1000, 'unixepoch', 'localtime')) as date,
-                COALESCE(model, 'unknown') as model,
-                COALESCE(SUM(cost_usd), 0) as cost
-            FROM events
-            WHERE timestamp >= ? AND timestamp <= ?
-                AND name = 'claude_code.api_request'
-            GROUP BY {}, model
-            ORDER BY MIN(timestamp) ASC, model ASC
-            "#,
-            format_str, group_expr
-        );
-
-        let rows: Vec<CostByModelRow> = sqlx::query_as(&query)
-            .bind(start_time)
-            .bind(end_time)
-            .fetch_all(pool)
-            .await?;
-
-        let models: HashSet<String> = rows.iter().map(|r| r.model.clone()).collect();
+        let rows = RollupRepository::sum_events(
+            pool,
+            API_REQUEST,
+            start_time,
+            end_time,
+            source,
+            RollupGroup {
+                period: Some(period_format(time_range)),
+                model: true,
+            },
+        )
+        .await?;
+
+        let models: HashSet<String> = rows
+            .iter()
+            .map(|r| r.model.clone().unwrap_or_else(|| "unknown".to_string()))
+            .collect();
         let mut models: Vec<String> = models.into_iter().collect();
         models.sort();
         let mut cost_map: HashMap<(String, String), f32> = HashMap::new();
         for r in rows {
-            cost_map.insert((r.date, r.model), r.cost as f32);
+            let date = r.period.unwrap_or_default();
+            let model = r.model.unwrap_or_else(|| "unknown".to_string());
+            *cost_map.entry((date, model)).or_default() += r.cost_usd as f32;
         }
 
         let all_labels = generate_date_labels(time_range);
@@ -161,6 +128,7 @@ impl TrendsService {
     pub async fn get_cost_efficiency_trend(
         pool: &SqlitePool,
         time_range: TimeRange,
+        source: Option<&str>,
     ) -> Result<Vec<CostEfficiencyTrend>> {
         let (start_time, end_time) = get_time_range_bounds(time_range);
 
@@ -184,6 +152,7 @@ impl TrendsService {
             FROM sessions
             WHERE start_time >= ? AND start_time <= ?
                 AND id != 'unknown'
+                AND (? IS NULL OR source = ?)
             GROUP BY {}
             ORDER BY MIN(start_time) ASC
             "#,
@@ -193,6 +162,8 @@ impl TrendsService {
         let rows: Vec<CostEfficiencyRow> = sqlx::query_as(&query)
             .bind(start_time)
             .bind(end_time)
+            .bind(source)
+            .bind(source)
             .fetch_all(pool)
             .await?;
 
@@ -229,23 +200,6 @@ impl TrendsService {
     }
 }
 
-#[derive(Debug, sqlx::FromRow)]
-struct CostByModelRow {
-    date: String,
-    model: String,
-    cost: f64,
-}
-
-#[derive(Debug, sqlx::FromRow)]
-struct UsageTrendRow {
-    date: String,
-    cost: f64,
-    input_tokens: i64,
-    output_tokens: i64,
-    cache_read_tokens: i64,
-    cache_creation_tokens: i64,
-}
-
 #[derive(Debug, sqlx::FromRow)]
 struct CostEfficiencyRow {
     date: String,
@@ -24,10 +24,16 @@ pub struct Session {
     pub total_input_tokens: i32,
     pub total_output_tokens: i32,
     pub total_cache_read_tokens: i32,
+    pub total_cache_creation_tokens: i32,
+    pub models: Vec<String>,
+    pub last_event_name: Option<String>,
     pub account_uuid: Option<String>,
     pub organization_id: Option<String>,
     pub terminal_type: Option<String>,
     pub app_version: Option<String>,
+    pub cwd: Option<String>,
+    /// "active", "idle" or "ended"
+    pub status: String,
 }
 
 impl From<shared::Session> for Session {
@@ -46,10 +52,15 @@ impl From<shared::Session> for Session {
             total_input_tokens: s.total_input_tokens as i32,
             total_output_tokens: s.total_output_tokens as i32,
             total_cache_read_tokens: s.total_cache_read_tokens as i32,
+            total_cache_creation_tokens: s.total_cache_creation_tokens as i32,
+            models: s.models,
+            last_event_name: s.last_event_name,
             account_uuid: s.account_uuid,
             organization_id: s.organization_id,
             terminal_type: s.terminal_type,
             app_version: s.app_version,
+            cwd: s.cwd,
+            status: s.status,
         }
     }
 }
@@ -5,6 +5,7 @@
 mod analytics;
 mod claude_session;
 mod entities;
+mod retention;
 
 mod stats;
 mod tools;
@@ -15,6 +16,7 @@ mod wrapped;
 pub use analytics::*;
 pub use claude_session::*;
 pub use entities::*;
+pub use retention::*;
 
 pub use stats::*;
 pub use tools::*;
@@ -0,0 +1,39 @@
+//! Retention types
+//!
+//! Types for the daemon's telemetry retention policy.
+
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+/// Number of days to keep each kind of telemetry; `None` keeps it forever
+#[typeshare]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    pub events_days: Option<u32>,
+    pub metrics_days: Option<u32>,
+    pub spans_days: Option<u32>,
+    pub notifications_days: Option<u32>,
+}
+
+impl From<shared::RetentionPolicy> for RetentionPolicy {
+    fn from(p: shared::RetentionPolicy) -> Self {
+        Self {
+            events_days: p.events_days,
+            metrics_days: p.metrics_days,
+            spans_days: p.spans_days,
+            notifications_days: p.notifications_days,
+        }
+    }
+}
+
+impl From<RetentionPolicy> for shared::RetentionPolicy {
+    fn from(p: RetentionPolicy) -> Self {
+        Self {
+            events_days: p.events_days,
+            metrics_days: p.metrics_days,
+            spans_days: p.spans_days,
+            notifications_days: p.notifications_days,
+        }
+    }
+}